@@ -1,15 +1,63 @@
+use tauri::Emitter;
+
 // Learn more about Tauri commands at https://tauri.app/v1/guides/features/command
 #[tauri::command]
 fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
+fn theme_code(theme: tauri::Theme) -> &'static str {
+    match theme {
+        tauri::Theme::Dark => "dark",
+        _ => "light",
+    }
+}
+
+// the webview does not follow the os theme on every platform, so the frontend asks here
+#[tauri::command]
+fn system_theme(window: tauri::Window) -> String {
+    window
+        .theme()
+        .map(theme_code)
+        .unwrap_or("light")
+        .to_string()
+}
+
+const ERROR_LOG_FILE: &str = "error.log";
+
+// the frontend keeps its error log in memory; this copy survives a reload or a crash
+#[tauri::command]
+fn append_error_log(app: tauri::AppHandle, entry: String) -> Result<(), String> {
+    use std::io::Write;
+    use tauri::Manager;
+
+    let dir = app.path().app_log_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(ERROR_LOG_FILE))
+        .map_err(|e| e.to_string())?;
+    writeln!(file, "{}", entry).map_err(|e| e.to_string())
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_window::init())
         .plugin(tauri_plugin_shell::init())
-        .invoke_handler(tauri::generate_handler![greet])
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::ThemeChanged(theme) = event {
+                if let Err(err) = window.emit("theme-changed", theme_code(*theme)) {
+                    log::error!("emit theme-changed failed: {}", err);
+                }
+            }
+        })
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            system_theme,
+            append_error_log
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -0,0 +1,349 @@
+use crate::component::message_item::MessageItemValue;
+use crate::component::message_list::{self, MessageList};
+use crate::component::pager::{self, Page, Pager};
+use crate::i18n::use_i18n::{use_i18n, I18n};
+use crate::util::request::{Host, RequestOptions};
+use crate::util::use_cancel_token::use_cancel_token;
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{Event, HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+const ENTITY_TYPES: [&str; 2] = ["user", "role"];
+const ACTIONS: [&str; 3] = ["create", "update", "delete"];
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct AuditLogValue {
+    pub id: i64,
+    pub actor_id: i64,
+    pub actor_name: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub action: String,
+    pub created_at: i64,
+    #[serde(default)]
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Clone, Default)]
+pub struct AuditLogFilter {
+    pub actor: String,
+    pub entity_type: String,
+    pub action: String,
+    pub start: Option<i64>,
+    pub end: Option<i64>,
+}
+
+impl AuditLogFilter {
+    fn to_params(&self, index: usize, size: usize) -> Vec<(&'static str, String)> {
+        let mut params = vec![("index", index.to_string()), ("size", size.to_string())];
+        if !self.actor.is_empty() {
+            params.push(("actor", self.actor.clone()));
+        }
+        if !self.entity_type.is_empty() {
+            params.push(("entity_type", self.entity_type.clone()));
+        }
+        if !self.action.is_empty() {
+            params.push(("action", self.action.clone()));
+        }
+        if let Some(start) = self.start {
+            params.push(("start", start.to_string()));
+        }
+        if let Some(end) = self.end {
+            params.push(("end", end.to_string()));
+        }
+        params
+    }
+}
+
+// "2023-10-01" -> local timestamp of 00:00:00 (or 23:59:59 when end_of_day)
+fn parse_date(date: &str, end_of_day: bool) -> Option<i64> {
+    let date = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    let time = if end_of_day {
+        date.and_hms_opt(23, 59, 59)?
+    } else {
+        date.and_hms_opt(0, 0, 0)?
+    };
+    time.and_local_timezone(chrono::Local)
+        .single()
+        .map(|x| x.timestamp())
+}
+
+fn display_value(value: &Option<serde_json::Value>) -> String {
+    match value {
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(serde_json::Value::Null) | None => "-".to_string(),
+        Some(v) => v.to_string(),
+    }
+}
+
+// catalog label of a known entity type or action, anything else is shown as sent
+fn label(i18n: &I18n, known: &[&str], prefix: &str, value: &str) -> String {
+    if known.contains(&value) {
+        i18n.t(&format!("{}.{}", prefix, value))
+    } else {
+        value.to_string()
+    }
+}
+
+fn action_class(action: &str) -> &'static str {
+    match action {
+        "create" => "tag is-success is-light",
+        "update" => "tag is-warning is-light",
+        "delete" => "tag is-danger is-light",
+        _ => "tag is-light",
+    }
+}
+
+#[function_component(AuditLog)]
+pub fn audit_log() -> Html {
+    let i18n = use_i18n();
+    let refresh_list = use_state(|| false);
+    let force_update = use_force_update();
+    let message: Rc<RefCell<Option<MessageItemValue>>> = use_mut_ref(|| None);
+    let filter = use_mut_ref(AuditLogFilter::default);
+    let expanded: Rc<RefCell<HashSet<i64>>> = use_mut_ref(Default::default);
+    let loading = use_mut_ref(|| false);
+    let index = use_mut_ref(|| 1);
+    let total = use_mut_ref(|| 0);
+    let size = use_mut_ref(|| pager::DEFAULT_PAGE_SIZE);
+    let data: Rc<RefCell<Vec<AuditLogValue>>> = use_mut_ref(Default::default);
+    let cancel = use_cancel_token();
+    {
+        let message = message.clone();
+        let filter = filter.clone();
+        let index = index.clone();
+        let size = size.clone();
+        let total = total.clone();
+        let loading = loading.clone();
+        let data = data.clone();
+        let force_update = force_update.clone();
+        let refresh_list = refresh_list.clone();
+        let cancel = cancel.clone();
+        use_effect_with(refresh_list, move |_| {
+            *loading.borrow_mut() = true;
+            let params = filter.borrow().to_params(*index.borrow(), *size.borrow());
+            spawn_local(async move {
+                let res = RequestOptions::new()
+                    .cancel(&cancel)
+                    .get::<Vec<AuditLogValue>, _, _>(
+                        Host::ApiBase,
+                        "/audit_log/search",
+                        Some(params.iter().map(|(k, v)| (*k, v.as_str()))),
+                    )
+                    .await;
+                match res {
+                    Ok(res) => {
+                        *data.borrow_mut() = res.data.unwrap_or_default();
+                        *total.borrow_mut() = res.total.unwrap_or_default();
+                    }
+                    Err(err) if err.cancelled => return,
+                    Err(err) => {
+                        *message.borrow_mut() = Some(message_list::api_error(&err));
+                    }
+                };
+                *loading.borrow_mut() = false;
+                force_update.force_update();
+            });
+        })
+    }
+
+    let search = {
+        let refresh = refresh_list.clone();
+        let index = index.clone();
+        move || {
+            *index.borrow_mut() = 1;
+            refresh.set(!*refresh);
+        }
+    };
+
+    let actor_change = {
+        let filter = filter.clone();
+        let search = search.clone();
+        Callback::from(move |e: Event| {
+            let el: HtmlInputElement = e.target_unchecked_into();
+            filter.borrow_mut().actor = el.value().trim().to_string();
+            search();
+        })
+    };
+
+    let entity_type_change = {
+        let filter = filter.clone();
+        let search = search.clone();
+        Callback::from(move |e: Event| {
+            let el: HtmlSelectElement = e.target_unchecked_into();
+            filter.borrow_mut().entity_type = el.value();
+            search();
+        })
+    };
+
+    let action_change = {
+        let filter = filter.clone();
+        let search = search.clone();
+        Callback::from(move |e: Event| {
+            let el: HtmlSelectElement = e.target_unchecked_into();
+            filter.borrow_mut().action = el.value();
+            search();
+        })
+    };
+
+    let start_change = {
+        let filter = filter.clone();
+        let search = search.clone();
+        Callback::from(move |e: Event| {
+            let el: HtmlInputElement = e.target_unchecked_into();
+            filter.borrow_mut().start = parse_date(&el.value(), false);
+            search();
+        })
+    };
+
+    let end_change = {
+        let filter = filter.clone();
+        let search = search.clone();
+        Callback::from(move |e: Event| {
+            let el: HtmlInputElement = e.target_unchecked_into();
+            filter.borrow_mut().end = parse_date(&el.value(), true);
+            search();
+        })
+    };
+
+    let page_change = {
+        let index = index.clone();
+        let size = size.clone();
+        let refresh = refresh_list.clone();
+        Callback::from(move |page: Page| {
+            *index.borrow_mut() = page.index;
+            *size.borrow_mut() = page.size;
+            refresh.set(!*refresh);
+        })
+    };
+
+    html! {
+    <>
+    <MessageList value = {(*message.borrow()).clone()}/>
+    <div class="search-container">
+        <div class="field is-grouped is-grouped-multiline">
+        <p class="control is-expanded">
+            <input class="input" type="text" onchange={actor_change} placeholder={i18n.t("audit_log.actor")}/>
+        </p>
+        <div class="control">
+            <div class="select">
+                <select onchange={entity_type_change}>
+                    <option value="">{i18n.t("audit_log.all_entities")}</option>
+                    {
+                        ENTITY_TYPES.iter().map(|x| html!{ <option value={*x}>{label(&i18n, &ENTITY_TYPES, "audit_log.entity", x)}</option> }).collect::<Html>()
+                    }
+                </select>
+            </div>
+        </div>
+        <div class="control">
+            <div class="select">
+                <select onchange={action_change}>
+                    <option value="">{i18n.t("audit_log.all_actions")}</option>
+                    {
+                        ACTIONS.iter().map(|x| html!{ <option value={*x}>{label(&i18n, &ACTIONS, "audit_log.action", x)}</option> }).collect::<Html>()
+                    }
+                </select>
+            </div>
+        </div>
+        <p class="control">
+            <input class="input" type="date" onchange={start_change} title={i18n.t("audit_log.from")}/>
+        </p>
+        <p class="control">
+            <input class="input" type="date" onchange={end_change} title={i18n.t("audit_log.to")}/>
+        </p>
+        </div>
+    </div>
+    <div class="table-container">
+        if *loading.borrow() {
+            <div class="table-loading"></div>
+        }
+        <table class="table is-bordered is-striped is-narrow is-hoverable">
+        <thead>
+            <tr>
+            <th><abbr title={i18n.t("audit_log.time")}>{i18n.t("audit_log.time")}</abbr></th>
+            <th><abbr title={i18n.t("audit_log.actor")}>{i18n.t("audit_log.actor")}</abbr></th>
+            <th><abbr title={i18n.t("audit_log.action")}>{i18n.t("audit_log.action")}</abbr></th>
+            <th><abbr title={i18n.t("audit_log.entity")}>{i18n.t("audit_log.entity")}</abbr></th>
+            <th><abbr title={i18n.t("audit_log.changes")}>{i18n.t("audit_log.changes")}</abbr></th>
+            </tr>
+        </thead>
+        <tbody>
+        {
+            data.borrow().iter().map(|x| {
+                let id = x.id;
+                let is_expanded = expanded.borrow().contains(&id);
+                let toggle = {
+                    let expanded = expanded.clone();
+                    let force_update = force_update.clone();
+                    Callback::from(move |_| {
+                        let mut expanded = expanded.borrow_mut();
+                        if !expanded.remove(&id) {
+                            expanded.insert(id);
+                        }
+                        force_update.force_update();
+                    })
+                };
+                html!{
+                    <>
+                    <tr>
+                        <td>{i18n.format_datetime(x.created_at)}</td>
+                        <td>{format!("{} (#{})", x.actor_name, x.actor_id)}</td>
+                        <td><span class={action_class(&x.action)}>{label(&i18n, &ACTIONS, "audit_log.action", &x.action)}</span></td>
+                        <td>{format!("{} #{}", label(&i18n, &ENTITY_TYPES, "audit_log.entity", &x.entity_type), x.entity_id)}</td>
+                        <td>
+                        if x.changes.is_empty() {
+                            {"-"}
+                        } else {
+                            <a href="javascript:void(0)" onclick={toggle}>
+                                {i18n.t_args("audit_log.field_count", &[("count", &x.changes.len().to_string())])}{" "}
+                                <i class={if is_expanded {"fa-solid fa-chevron-up"} else {"fa-solid fa-chevron-down"}}></i>
+                            </a>
+                        }
+                        </td>
+                    </tr>
+                    if is_expanded {
+                        <tr>
+                            <td colspan="5">
+                                <table class="table is-narrow is-fullwidth audit-diff">
+                                <thead>
+                                    <tr><th>{i18n.t("audit_log.field")}</th><th>{i18n.t("audit_log.before")}</th><th>{i18n.t("audit_log.after")}</th></tr>
+                                </thead>
+                                <tbody>
+                                {
+                                    x.changes.iter().map(|c| html!{
+                                        <tr>
+                                            <td><b>{c.field.clone()}</b></td>
+                                            <td class="has-background-danger-light"><del>{display_value(&c.before)}</del></td>
+                                            <td class="has-background-success-light"><ins>{display_value(&c.after)}</ins></td>
+                                        </tr>
+                                    }).collect::<Html>()
+                                }
+                                </tbody>
+                                </table>
+                            </td>
+                        </tr>
+                    }
+                    </>
+                }
+            }).collect::<Html>()
+        }
+        </tbody>
+        </table>
+    </div>
+    <div class="pager-container">
+        <Pager total = { *total.borrow() } index = {*index.borrow()} onpagechanged = {page_change}/>
+    </div>
+    </>
+    }
+}
@@ -0,0 +1,285 @@
+use crate::component::message_item::MessageItemValue;
+use crate::component::message_list::{self, MessageList};
+use crate::component::password_strength::PasswordStrength;
+use crate::component::session_list::SessionList;
+use crate::form::ValidStatus;
+use crate::i18n::{self, use_i18n::use_i18n};
+use crate::storage::keys;
+use crate::util::common;
+use crate::util::request::{self, Host};
+use crate::util::validation::{self, Validator};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+#[derive(Serialize)]
+pub struct UpdateCurrentUserReq {
+    pub name: Option<String>,
+    pub mobile: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ChangeCurrentPwdReq {
+    pub old_pwd: String,
+    pub pwd: String,
+}
+
+fn input_value(node: &NodeRef) -> String {
+    node.cast::<HtmlInputElement>()
+        .map(|x| x.value())
+        .unwrap_or_default()
+}
+
+#[function_component(MyAccount)]
+pub fn my_account() -> Html {
+    let i18n = use_i18n();
+    let force_update = use_force_update();
+    let message: Rc<RefCell<Option<MessageItemValue>>> = use_mut_ref(|| None);
+    let old_pwd_valid = use_mut_ref(ValidStatus::default);
+    let pwd_valid = use_mut_ref(ValidStatus::default);
+    let pwd_confirm_valid = use_mut_ref(ValidStatus::default);
+    let name_ref = use_node_ref();
+    let mobile_ref = use_node_ref();
+    let old_pwd_ref = use_node_ref();
+    let pwd_ref = use_node_ref();
+    let pwd_confirm_ref = use_node_ref();
+
+    let user = common::get_current_user().ok();
+
+    let save_profile = {
+        let name_ref = name_ref.clone();
+        let mobile_ref = mobile_ref.clone();
+        let message = message.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            let req = UpdateCurrentUserReq {
+                name: Some(input_value(&name_ref)),
+                mobile: Some(input_value(&mobile_ref)),
+            };
+            let message = message.clone();
+            let force_update = force_update.clone();
+            spawn_local(async move {
+                match request::put::<serde_json::Value, _>(
+                    Host::ApiBase,
+                    "/user/update_current",
+                    &req,
+                )
+                .await
+                {
+                    Ok(_) => {
+                        if let Ok(mut user) = common::get_current_user() {
+                            user.name = req.name;
+                            user.mobile = req.mobile;
+                            keys::CURRENT_USER.set(&user);
+                        }
+                        *message.borrow_mut() =
+                            Some(message_list::ok(&i18n::t("account.profile_saved")));
+                    }
+                    Err(err) => {
+                        *message.borrow_mut() = Some(message_list::api_error(&err));
+                    }
+                }
+                force_update.force_update();
+            });
+        })
+    };
+
+    let on_old_pwd_change = {
+        let old_pwd_ref = old_pwd_ref.clone();
+        let old_pwd_valid = old_pwd_valid.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            // the current password may predate the policy, so only require it
+            *old_pwd_valid.borrow_mut() = match validation::required("current_password")
+                .validate(&input_value(&old_pwd_ref), &())
+            {
+                Ok(_) => ValidStatus::Valid,
+                Err(e) => ValidStatus::InValid(format!("{}", e)),
+            };
+            force_update.force_update();
+        })
+    };
+
+    let on_pwd_change = {
+        let old_pwd_ref = old_pwd_ref.clone();
+        let pwd_ref = pwd_ref.clone();
+        let pwd_valid = pwd_valid.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            let pwd = input_value(&pwd_ref);
+            *pwd_valid.borrow_mut() = match common::validate_pwd(&pwd) {
+                Ok(_) if pwd == input_value(&old_pwd_ref) => {
+                    ValidStatus::InValid(i18n::t("account.same_password"))
+                }
+                Ok(_) => ValidStatus::Valid,
+                Err(e) => ValidStatus::InValid(format!("{}", e)),
+            };
+            force_update.force_update();
+        })
+    };
+
+    let on_pwd_confirm_change = {
+        let pwd_ref = pwd_ref.clone();
+        let pwd_confirm_ref = pwd_confirm_ref.clone();
+        let pwd_confirm_valid = pwd_confirm_valid.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            *pwd_confirm_valid.borrow_mut() = match common::validate_pwd_confirm(
+                &input_value(&pwd_ref),
+                &input_value(&pwd_confirm_ref),
+            ) {
+                Ok(_) => ValidStatus::Valid,
+                Err(e) => ValidStatus::InValid(format!("{}", e)),
+            };
+            force_update.force_update();
+        })
+    };
+
+    let change_pwd = {
+        let old_pwd_ref = old_pwd_ref.clone();
+        let pwd_ref = pwd_ref.clone();
+        let pwd_confirm_ref = pwd_confirm_ref.clone();
+        let old_pwd_valid = old_pwd_valid.clone();
+        let pwd_valid = pwd_valid.clone();
+        let pwd_confirm_valid = pwd_confirm_valid.clone();
+        let message = message.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            let old_pwd = input_value(&old_pwd_ref);
+            let pwd = input_value(&pwd_ref);
+            let pwd_confirm = input_value(&pwd_confirm_ref);
+            if let Err(e) = validation::required("current_password").validate(&old_pwd, &()) {
+                *old_pwd_valid.borrow_mut() = ValidStatus::InValid(format!("{}", e));
+                force_update.force_update();
+                return;
+            }
+            if let Err(e) = common::validate_pwd(&pwd) {
+                *pwd_valid.borrow_mut() = ValidStatus::InValid(format!("{}", e));
+                force_update.force_update();
+                return;
+            }
+            if let Err(e) = common::validate_pwd_confirm(&pwd, &pwd_confirm) {
+                *pwd_confirm_valid.borrow_mut() = ValidStatus::InValid(format!("{}", e));
+                force_update.force_update();
+                return;
+            }
+            let old_pwd_ref = old_pwd_ref.clone();
+            let pwd_ref = pwd_ref.clone();
+            let pwd_confirm_ref = pwd_confirm_ref.clone();
+            let old_pwd_valid = old_pwd_valid.clone();
+            let pwd_valid = pwd_valid.clone();
+            let pwd_confirm_valid = pwd_confirm_valid.clone();
+            let message = message.clone();
+            let force_update = force_update.clone();
+            spawn_local(async move {
+                match request::post::<serde_json::Value, _>(
+                    Host::ApiBase,
+                    "/user/change_current_pwd",
+                    &ChangeCurrentPwdReq { old_pwd, pwd },
+                )
+                .await
+                {
+                    Ok(_) => {
+                        for node in [&old_pwd_ref, &pwd_ref, &pwd_confirm_ref] {
+                            if let Some(input) = node.cast::<HtmlInputElement>() {
+                                input.set_value("");
+                            }
+                        }
+                        *old_pwd_valid.borrow_mut() = ValidStatus::None;
+                        *pwd_valid.borrow_mut() = ValidStatus::None;
+                        *pwd_confirm_valid.borrow_mut() = ValidStatus::None;
+                        *message.borrow_mut() =
+                            Some(message_list::ok(&i18n::t("account.password_changed")));
+                    }
+                    Err(err) => {
+                        // the server verifies the current password
+                        *old_pwd_valid.borrow_mut() = ValidStatus::InValid(err.user_message());
+                    }
+                }
+                force_update.force_update();
+            });
+        })
+    };
+
+    let (name, mobile, email) = match &user {
+        Some(v) => (v.name.clone(), v.mobile.clone(), v.email.clone()),
+        None => (None, None, String::default()),
+    };
+
+    html! {
+    <>
+    <MessageList value = {(*message.borrow()).clone()}/>
+    <div class="detail-container">
+        <div class="box">
+            <p class="title is-6">{i18n.t("account.profile")}</p>
+            <div class="field">
+                <label class="label">{i18n.t("user.email")}</label>
+                <div class="control">
+                <input class="input" value={email} type="email" disabled={true}/>
+                </div>
+            </div>
+            <div class="field">
+                <label class="label">{i18n.t("user.name")}</label>
+                <div class="control">
+                <input ref={name_ref} class="input" value={name} type="text" placeholder="Scarlett"/>
+                </div>
+            </div>
+            <div class="field">
+                <label class="label">{i18n.t("user.mobile")}</label>
+                <div class="control">
+                <input ref={mobile_ref} class="input" value={mobile} type="text" placeholder="13800001111"/>
+                </div>
+            </div>
+            <button class="button is-success" onclick={save_profile}>{i18n.t("account.save")}</button>
+        </div>
+        <div class="box">
+            <p class="title is-6">{i18n.t("account.change_password")}</p>
+            <div class="field">
+                <label class="label">{i18n.t("account.current_password")}</label>
+                <p class="control has-icons-left">
+                    <input ref={old_pwd_ref} class={format!("input {}", old_pwd_valid.borrow().class())} type="password" onkeyup={on_old_pwd_change} placeholder="**********"/>
+                    <span class="icon is-small is-left">
+                    <i class="fas fa-lock"></i>
+                    </span>
+                </p>
+                <p class="help is-danger">{old_pwd_valid.borrow().message()}</p>
+            </div>
+            <div class="field">
+                <label class="label">{i18n.t("account.new_password")}</label>
+                <p class="control has-icons-left">
+                    <input ref={pwd_ref} class={format!("input {}", pwd_valid.borrow().class())} type="password" onkeyup={on_pwd_change} placeholder="**********"/>
+                    <span class="icon is-small is-left">
+                    <i class="fas fa-lock"></i>
+                    </span>
+                </p>
+                <p class="help is-danger">{pwd_valid.borrow().message()}</p>
+            </div>
+            <PasswordStrength value={input_value(&pwd_ref)}/>
+            <div class="field">
+                <label class="label">{i18n.t("account.new_password_confirm")}</label>
+                <p class="control has-icons-left">
+                    <input ref={pwd_confirm_ref} class={format!("input {}", pwd_confirm_valid.borrow().class())} type="password" onkeyup={on_pwd_confirm_change} placeholder="**********"/>
+                    <span class="icon is-small is-left">
+                    <i class="fas fa-lock"></i>
+                    </span>
+                </p>
+                <p class="help is-danger">{pwd_confirm_valid.borrow().message()}</p>
+            </div>
+            <button class="button is-primary" onclick={change_pwd}>{i18n.t("account.change_password")}</button>
+        </div>
+        <div class="box">
+            <p class="title is-6">{i18n.t("nav.two_factor")}</p>
+            <p class="block">{i18n.t("account.two_factor_desc")}</p>
+            <a class="button is-light is-info" href="/main/account/two_factor">{i18n.t("account.manage")}</a>
+        </div>
+        <div class="box">
+            <p class="title is-6">{i18n.t("account.sessions")}</p>
+            <SessionList />
+        </div>
+    </div>
+    </>
+    }
+}
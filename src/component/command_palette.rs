@@ -0,0 +1,406 @@
+use crate::component::message_list;
+use crate::i18n::use_i18n::use_i18n;
+use crate::layout::nav;
+use crate::shortcut::use_shortcut::use_shortcut;
+use crate::theme::use_theme::use_theme;
+use crate::util::common;
+use crate::util::error_log;
+use crate::util::middleware::CancelToken;
+use crate::util::request::RequestOptions;
+use evolve_axum_cli::apis::user_controller_api;
+use evolve_axum_cli::models::User;
+use gloo::timers::callback::Timeout;
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+const USER_SEARCH_DELAY_MS: u32 = 250;
+const USER_SEARCH_SIZE: usize = 5;
+const MAX_RESULTS: usize = 12;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Group {
+    Navigation,
+    Users,
+    Actions,
+}
+
+impl Group {
+    fn label(&self) -> &'static str {
+        match self {
+            Group::Navigation => "palette.group.navigation",
+            Group::Users => "palette.group.users",
+            Group::Actions => "palette.group.actions",
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Command {
+    group: Group,
+    title: String,
+    subtitle: Option<String>,
+    icon: &'static str,
+    run: Rc<dyn Fn()>,
+}
+
+// subsequence match; consecutive characters and word starts score higher, `None` if it does not match
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    if query.is_empty() {
+        return Some(0);
+    }
+    let text = text.to_lowercase().chars().collect::<Vec<_>>();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut last = None;
+    for q in query.to_lowercase().chars().filter(|x| !x.is_whitespace()) {
+        let i = (pos..text.len()).find(|i| text[*i] == q)?;
+        score += 1;
+        if last.is_some_and(|x: usize| x + 1 == i) {
+            score += 5;
+        }
+        if i == 0 || !text[i - 1].is_alphanumeric() {
+            score += 3;
+        }
+        last = Some(i);
+        pos = i + 1;
+    }
+    // shorter texts win ties
+    Some(score * 100 - text.len() as i32)
+}
+
+#[function_component(CommandPalette)]
+pub fn command_palette() -> Html {
+    let i18n = use_i18n();
+    let theme = use_theme();
+    let force_update = use_force_update();
+    let open = use_mut_ref(|| false);
+    let query = use_mut_ref(String::new);
+    let selected = use_mut_ref(|| 0usize);
+    let users: std::rc::Rc<std::cell::RefCell<Vec<User>>> = use_mut_ref(Vec::new);
+    let search_timer: std::rc::Rc<std::cell::RefCell<Option<Timeout>>> = use_mut_ref(|| None);
+    // aborts the search still in flight when the next one starts
+    let search_cancel = use_mut_ref(CancelToken::new);
+    let input_ref = use_node_ref();
+
+    let close = {
+        let open = open.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            *open.borrow_mut() = false;
+            force_update.force_update();
+        })
+    };
+
+    {
+        let open = open.clone();
+        let query = query.clone();
+        let selected = selected.clone();
+        let users = users.clone();
+        let force_update = force_update.clone();
+        use_shortcut(
+            "command_palette",
+            "Mod+K",
+            "shortcut.open_palette",
+            Callback::from(move |_| {
+                let v = !*open.borrow();
+                *open.borrow_mut() = v;
+                query.borrow_mut().clear();
+                users.borrow_mut().clear();
+                *selected.borrow_mut() = 0;
+                force_update.force_update();
+            }),
+        );
+    }
+
+    {
+        let input_ref = input_ref.clone();
+        let is_open = *open.borrow();
+        use_effect_with(is_open, move |is_open| {
+            if *is_open {
+                if let Some(input) = input_ref.cast::<HtmlInputElement>() {
+                    input.focus().unwrap_or_default();
+                }
+            }
+        });
+    }
+
+    if !*open.borrow() {
+        return html! {};
+    }
+
+    let user = common::get_current_user().ok();
+    let mut commands: Vec<Command> = nav::entries()
+        .iter()
+        .filter(|x| x.chrome != nav::Chrome::Bare && x.is_allowed(user.as_ref()))
+        // pages with path params are reached through their parents
+        .filter_map(|x| {
+            let path = x.path.filter(|p| !p.contains(':'))?;
+            Some(Command {
+                group: Group::Navigation,
+                title: i18n.t(x.title),
+                subtitle: Some(path.to_string()),
+                icon: x.icon.unwrap_or("fa-solid fa-arrow-right"),
+                run: Rc::new(move || common::redirect(path)),
+            })
+        })
+        .collect();
+    let can_open_user = nav::get("user_detail").is_some_and(|x| x.is_allowed(user.as_ref()));
+    commands.extend(users.borrow().iter().filter(|_| can_open_user).map(|x| {
+        let id = x.id;
+        Command {
+            group: Group::Users,
+            title: x.name.clone().unwrap_or_else(|| x.email.clone()),
+            subtitle: Some(x.email.clone()),
+            icon: "fa-solid fa-user",
+            run: Rc::new(move || common::redirect(&format!("/main/user/{}", id))),
+        }
+    }));
+    {
+        let theme = theme.clone();
+        commands.push(Command {
+            group: Group::Actions,
+            title: i18n.t("palette.action.toggle_theme"),
+            subtitle: Some(i18n.t(theme.mode().label())),
+            icon: theme.mode().icon(),
+            run: Rc::new(move || theme.toggle()),
+        });
+    }
+    if message_list::chat_available() {
+        commands.push(Command {
+            group: Group::Actions,
+            title: i18n.t("palette.action.open_chat"),
+            subtitle: None,
+            icon: "fa-solid fa-comments",
+            run: Rc::new(message_list::open_chat),
+        });
+    }
+    commands.push(Command {
+        group: Group::Actions,
+        title: i18n.t("palette.action.copy_error_report"),
+        subtitle: Some(i18n.t_args(
+            "palette.error_count",
+            &[("count", error_log::entries().len().to_string().as_str())],
+        )),
+        icon: "fa-solid fa-bug",
+        run: Rc::new(|| {
+            if !error_log::copy_report() {
+                log::warn!("copy error report failed: clipboard is not available");
+            }
+        }),
+    });
+    commands.push(Command {
+        group: Group::Actions,
+        title: i18n.t("palette.action.logout"),
+        subtitle: None,
+        icon: "fa-solid fa-right-from-bracket",
+        run: Rc::new(common::logout),
+    });
+
+    let q = query.borrow().clone();
+    let mut results = commands
+        .into_iter()
+        .filter_map(|x| {
+            // users come back already matched by the server
+            let score = if x.group == Group::Users {
+                Some(0)
+            } else {
+                fuzzy_score(&q, &x.title)
+            }?;
+            Some((score, x))
+        })
+        .collect::<Vec<_>>();
+    results.sort_by_key(|(score, x)| (x.group as u8, -score));
+    let results = results
+        .into_iter()
+        .map(|(_, x)| x)
+        .take(MAX_RESULTS)
+        .collect::<Vec<_>>();
+    let sel = (*selected.borrow()).min(results.len().saturating_sub(1));
+
+    let oninput = {
+        let query = query.clone();
+        let selected = selected.clone();
+        let users = users.clone();
+        let search_timer = search_timer.clone();
+        let search_cancel = search_cancel.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let value = input.value();
+            *query.borrow_mut() = value.clone();
+            *selected.borrow_mut() = 0;
+            force_update.force_update();
+
+            let users = users.clone();
+            let force_update = force_update.clone();
+            let query = query.clone();
+            let search_cancel = search_cancel.clone();
+            // replacing the timer cancels the pending search
+            *search_timer.borrow_mut() = Some(Timeout::new(USER_SEARCH_DELAY_MS, move || {
+                search_cancel.borrow().cancel();
+                let cancel = CancelToken::new();
+                *search_cancel.borrow_mut() = cancel.clone();
+                if value.trim().is_empty() {
+                    users.borrow_mut().clear();
+                    force_update.force_update();
+                    return;
+                }
+                spawn_local(async move {
+                    let key_word = value.trim().to_string();
+                    let res = RequestOptions::new()
+                        .cancel(&cancel)
+                        .cli("user_controller_api::search", true, |config| {
+                            let key_word = key_word.clone();
+                            async move {
+                                user_controller_api::search(
+                                    &config,
+                                    &key_word,
+                                    1,
+                                    USER_SEARCH_SIZE as i64,
+                                )
+                                .await
+                            }
+                        })
+                        .await;
+                    // drop answers for an outdated query
+                    if *query.borrow() != value {
+                        return;
+                    }
+                    match res {
+                        Ok(res) => {
+                            *users.borrow_mut() = res.data.into_iter().map(|x| x.user).collect()
+                        }
+                        Err(err) if err.cancelled => return,
+                        Err(err) => log::warn!("palette user search failed: {}", err),
+                    }
+                    force_update.force_update();
+                });
+            }));
+        })
+    };
+
+    let run = {
+        let close = close.clone();
+        Callback::from(move |command: Command| {
+            close.emit(());
+            (command.run)();
+        })
+    };
+
+    let onkeydown = {
+        let selected = selected.clone();
+        let results = results.clone();
+        let run = run.clone();
+        let close = close.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            match e.key().as_str() {
+                "ArrowDown" => {
+                    *selected.borrow_mut() = (sel + 1).min(results.len().saturating_sub(1))
+                }
+                "ArrowUp" => *selected.borrow_mut() = sel.saturating_sub(1),
+                "Enter" => {
+                    if let Some(command) = results.get(sel) {
+                        run.emit(command.clone());
+                    }
+                }
+                "Escape" => close.emit(()),
+                _ => return,
+            }
+            e.prevent_default();
+            force_update.force_update();
+        })
+    };
+
+    let mut last_group = None;
+    html! {
+        <div class="modal is-active command-palette" onkeydown={onkeydown}>
+            <div class="modal-background" onclick={close.reform(|_| ())}></div>
+            <div class="modal-card">
+                <section class="modal-card-body">
+                    <div class="control has-icons-left">
+                        <input ref={input_ref} class="input" type="text" value={q} oninput={oninput}
+                            placeholder={i18n.t("palette.placeholder")} aria-label={i18n.t("palette.placeholder")}/>
+                        <span class="icon is-left"><i class="fa-solid fa-magnifying-glass"></i></span>
+                    </div>
+                    {
+                        if results.is_empty() {
+                            html! { <p class="has-text-grey command-palette-empty">{i18n.t("palette.no_results")}</p> }
+                        } else {
+                            html! {}
+                        }
+                    }
+                    <ul class="command-palette-list">
+                    {
+                        results.iter().enumerate().map(|(i, command)| {
+                            let header = if last_group != Some(command.group) {
+                                last_group = Some(command.group);
+                                html! { <li class="command-palette-group">{i18n.t(command.group.label())}</li> }
+                            } else {
+                                html! {}
+                            };
+                            let onclick = {
+                                let run = run.clone();
+                                let command = command.clone();
+                                Callback::from(move |_| run.emit(command.clone()))
+                            };
+                            let onmouseenter = {
+                                let selected = selected.clone();
+                                let force_update = force_update.clone();
+                                Callback::from(move |_| {
+                                    *selected.borrow_mut() = i;
+                                    force_update.force_update();
+                                })
+                            };
+                            html! {
+                                <>
+                                {header}
+                                <li class={if i == sel {"command-palette-item is-selected"} else {"command-palette-item"}}
+                                    onclick={onclick} onmouseenter={onmouseenter}>
+                                    <span class="icon"><i class={command.icon}></i></span>
+                                    <span class="command-palette-title">{&command.title}</span>
+                                    {
+                                        match &command.subtitle {
+                                            Some(v) => html! { <span class="command-palette-subtitle">{v}</span> },
+                                            None => html! {},
+                                        }
+                                    }
+                                </li>
+                                </>
+                            }
+                        }).collect::<Html>()
+                    }
+                    </ul>
+                </section>
+            </div>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_score_matches_subsequences() {
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        assert!(fuzzy_score("usr", "User").is_some());
+        assert!(fuzzy_score("audit log", "Audit Log").is_some());
+        assert!(fuzzy_score("AL", "audit log").is_some());
+        assert_eq!(fuzzy_score("rsu", "User"), None);
+        assert_eq!(fuzzy_score("users", "User"), None);
+    }
+
+    #[test]
+    fn fuzzy_score_ranking() {
+        let score = |q, t| fuzzy_score(q, t).unwrap();
+        // consecutive characters beat scattered ones
+        assert!(score("log", "Logout") > score("log", "Lock Group"));
+        // word starts beat the middle of a word
+        assert!(score("al", "Audit Log") > score("al", "Wallet"));
+        // shorter texts win ties
+        assert!(score("user", "User") > score("user", "User Detail"));
+    }
+}
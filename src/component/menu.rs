@@ -1,4 +1,9 @@
+use crate::i18n::use_i18n::use_i18n;
+use crate::storage::StorageKey;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use wasm_bindgen::JsCast;
+use web_sys::HtmlInputElement;
 use yew::prelude::*;
 use yew::Properties;
 
@@ -12,46 +17,242 @@ pub struct MenuProps {
     pub onselect: Callback<String>,
     #[prop_or_default]
     pub selected_name: Option<String>,
+    // icons only, for a narrow sidebar
+    #[prop_or_default]
+    pub collapsed: bool,
+    // shows a filter box above the labels
+    #[prop_or_default]
+    pub filterable: bool,
+    // where the expanded groups are remembered
+    #[prop_or_default]
+    pub storage_key: Option<StorageKey<SavedMenu>>,
 }
 
 #[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
 pub struct MenuLabel {
     pub label: Option<String>,
+    // remembers whether the label is closed; defaults to the label, which changes with the
+    // language, so translated labels should set one
+    #[serde(default)]
+    pub key: Option<String>,
     pub nodes: Vec<MenuNode>,
 }
 
-#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+impl MenuLabel {
+    // labels expand and collapse like groups, so they need a key of their own
+    fn key(&self) -> Option<String> {
+        self.key
+            .as_ref()
+            .or(self.label.as_ref())
+            .map(|x| format!("label:{}", x))
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug, Default)]
 pub struct MenuNode {
     pub name: String,
+    // emitted on select and compared with `selected_name`; defaults to the name
+    #[serde(default)]
+    pub key: Option<String>,
+    // fontawesome classes, e.g. "fa-solid fa-user"
+    #[serde(default)]
+    pub icon: Option<String>,
     pub children: Vec<MenuNode>,
 }
 
 impl MenuNode {
-    fn render(&self, props: &MenuProps) -> Html {
-        let mut class = "";
-        if let Some(v) = props.selected_name.as_deref() {
-            if v == &self.name {
-                class = "is-active"
-            }
+    pub fn key(&self) -> &str {
+        self.key.as_deref().unwrap_or(&self.name)
+    }
+
+    // `filter` is lowercase; a group stays visible while any descendant matches
+    fn matches(&self, filter: &str) -> bool {
+        filter.is_empty()
+            || self.name.to_lowercase().contains(filter)
+            || self.children.iter().any(|x| x.matches(filter))
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        self.children
+            .iter()
+            .any(|x| x.key() == key || x.contains(key))
+    }
+}
+
+// the part of the menu state that survives a reload
+#[derive(Default, Serialize, Deserialize)]
+pub struct SavedMenu {
+    pub expanded: Vec<String>,
+    pub closed_labels: Vec<String>,
+}
+
+#[derive(Default)]
+struct MenuState {
+    expanded: HashSet<String>,
+    // labels are open unless collapsed, so they are tracked the other way round
+    closed_labels: HashSet<String>,
+    filter: String,
+    focused: Option<String>,
+}
+
+impl MenuState {
+    fn is_expanded(&self, key: &str) -> bool {
+        !self.filter.is_empty() || self.expanded.contains(key)
+    }
+
+    fn is_label_open(&self, label: &MenuLabel) -> bool {
+        !self.filter.is_empty() || label.key().is_none_or(|x| !self.closed_labels.contains(&x))
+    }
+
+    fn load(storage_key: Option<&StorageKey<SavedMenu>>) -> Self {
+        let saved = storage_key.and_then(|x| x.get()).unwrap_or_default();
+        MenuState {
+            expanded: saved.expanded.into_iter().collect(),
+            closed_labels: saved.closed_labels.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    fn save(&self, storage_key: Option<&StorageKey<SavedMenu>>) {
+        if let Some(storage_key) = storage_key {
+            storage_key.set(&SavedMenu {
+                expanded: self.expanded.iter().cloned().collect(),
+                closed_labels: self.closed_labels.iter().cloned().collect(),
+            });
+        }
+    }
+}
+
+// a row the keyboard can move to
+struct Row {
+    key: String,
+    parent: Option<String>,
+    has_children: bool,
+    expanded: bool,
+}
+
+fn collect_rows(
+    nodes: &[MenuNode],
+    parent: Option<&str>,
+    state: &MenuState,
+    collapsed: bool,
+    rows: &mut Vec<Row>,
+) {
+    // the filter box is hidden while collapsed
+    let filter = if collapsed {
+        String::new()
+    } else {
+        state.filter.to_lowercase()
+    };
+    for node in nodes.iter().filter(|x| x.matches(&filter)) {
+        let expanded = !collapsed && state.is_expanded(node.key());
+        rows.push(Row {
+            key: node.key().to_string(),
+            parent: parent.map(|x| x.to_string()),
+            has_children: !node.children.is_empty(),
+            expanded,
+        });
+        if expanded {
+            collect_rows(&node.children, Some(node.key()), state, collapsed, rows);
+        }
+    }
+}
+
+fn visible_rows(labels: &[MenuLabel], state: &MenuState, collapsed: bool) -> Vec<Row> {
+    let mut rows = vec![];
+    for label in labels
+        .iter()
+        .filter(|x| collapsed || state.is_label_open(x))
+    {
+        collect_rows(&label.nodes, None, state, collapsed, &mut rows);
+    }
+    rows
+}
+
+fn open_branch(nodes: &[MenuNode], selected: &str, expanded: &mut HashSet<String>) {
+    for node in nodes.iter().filter(|x| x.contains(selected)) {
+        expanded.insert(node.key().to_string());
+        open_branch(&node.children, selected, expanded);
+    }
+}
+
+struct RenderCtx<'a> {
+    props: &'a MenuProps,
+    state: &'a MenuState,
+    filter: String,
+    select: Callback<String>,
+    toggle: Callback<String>,
+}
+
+impl MenuNode {
+    fn render(&self, ctx: &RenderCtx) -> Html {
+        if !self.matches(&ctx.filter) {
+            return html! {};
+        }
+        let key = self.key().to_string();
+        let mut class = classes!();
+        if ctx.props.selected_name.as_deref() == Some(self.key()) {
+            class.push("is-active");
+        }
+        if ctx.state.focused.as_deref() == Some(self.key()) {
+            class.push("is-focused");
         }
 
         let onclick = {
-            let onselect = props.onselect.clone();
-            let name = self.name.clone();
-            Callback::from(move |_| onselect.emit(name.clone()))
+            let select = ctx.select.clone();
+            let key = key.clone();
+            Callback::from(move |_| select.emit(key.clone()))
+        };
+        let icon = match &self.icon {
+            Some(v) => {
+                html! { <span class="icon menu-icon"><i class={v.clone()} aria-hidden="true"></i></span> }
+            }
+            None if ctx.props.collapsed => html! {
+                <span class="icon menu-icon">{self.name.chars().next().map(|x| x.to_string()).unwrap_or_default()}</span>
+            },
+            None => html! {},
+        };
+
+        if ctx.props.collapsed {
+            return html! {
+                <li>
+                    <a href={String::from("javascript:void(0)")} class={class} onclick = {onclick} title={self.name.clone()}>{icon}</a>
+                </li>
+            };
+        }
+
+        let expanded = ctx.state.is_expanded(self.key());
+        let toggle = if self.children.is_empty() {
+            html! {}
+        } else {
+            let ontoggle = {
+                let toggle = ctx.toggle.clone();
+                let key = key.clone();
+                Callback::from(move |e: MouseEvent| {
+                    e.stop_propagation();
+                    toggle.emit(key.clone());
+                })
+            };
+            html! {
+                <span class="icon is-small menu-toggle" onclick={ontoggle}>
+                    <i class={if expanded {"fa-solid fa-chevron-down"} else {"fa-solid fa-chevron-right"}} aria-hidden="true"></i>
+                </span>
+            }
         };
 
         html! {
             <li>
-                <a href={String::from("javascript:void(0)")} class={class} onclick = {onclick}>{&self.name}</a>
+                <a href={String::from("javascript:void(0)")} class={class} onclick = {onclick}>
+                    {icon}<span class="menu-name">{&self.name}</span>{toggle}
+                </a>
                 {
-                    if self.children.is_empty() {
+                    if self.children.is_empty() || !expanded {
                         html!{}
                     } else{
                         html!{
                             <ul>
                                 {
-                                    self.children.iter().map(|n|n.render(props)).collect::<Html>()
+                                    self.children.iter().map(|n|n.render(ctx)).collect::<Html>()
                                 }
                             </ul>
                         }
@@ -64,30 +265,224 @@ impl MenuNode {
 
 #[function_component(Menu)]
 pub fn menu(props: &MenuProps) -> Html {
+    let i18n = use_i18n();
+    let force_update = use_force_update();
+    let state = {
+        let storage_key = props.storage_key.clone();
+        let labels = props.labels.clone();
+        let selected = props.selected_name.clone();
+        use_mut_ref(move || {
+            let mut state = MenuState::load(storage_key.as_ref());
+            // the branch holding the current page starts open
+            if let Some(selected) = selected.as_deref() {
+                for label in labels.iter() {
+                    open_branch(&label.nodes, selected, &mut state.expanded);
+                }
+            }
+            state
+        })
+    };
+    let menu_ref = use_node_ref();
+
+    let select = {
+        let onselect = props.onselect.clone();
+        let state = state.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |key: String| {
+            state.borrow_mut().focused = Some(key.clone());
+            force_update.force_update();
+            onselect.emit(key);
+        })
+    };
+
+    let toggle = {
+        let state = state.clone();
+        let storage_key = props.storage_key.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |key: String| {
+            let mut state = state.borrow_mut();
+            if !state.expanded.remove(&key) {
+                state.expanded.insert(key);
+            }
+            state.save(storage_key.as_ref());
+            force_update.force_update();
+        })
+    };
+
+    let toggle_label = {
+        let state = state.clone();
+        let storage_key = props.storage_key.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |key: String| {
+            let mut state = state.borrow_mut();
+            if !state.closed_labels.remove(&key) {
+                state.closed_labels.insert(key);
+            }
+            state.save(storage_key.as_ref());
+            force_update.force_update();
+        })
+    };
+
+    let oninput_filter = {
+        let state = state.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let mut state = state.borrow_mut();
+            state.filter = input.value();
+            state.focused = None;
+            force_update.force_update();
+        })
+    };
+
+    let onkeydown = {
+        let state = state.clone();
+        let labels = props.labels.clone();
+        let collapsed = props.collapsed;
+        let storage_key = props.storage_key.clone();
+        let select = select.clone();
+        let menu_ref = menu_ref.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            let in_filter = e
+                .target()
+                .and_then(|x| x.dyn_into::<HtmlInputElement>().ok())
+                .is_some();
+            let key = e.key();
+            // left, right, home and end keep editing the filter text
+            let filter_key = matches!(key.as_str(), "ArrowDown" | "ArrowUp" | "Enter" | "Escape");
+            if in_filter && !filter_key {
+                return;
+            }
+
+            let rows = visible_rows(&labels, &state.borrow(), collapsed);
+            let focused = state.borrow().focused.clone();
+            let pos = focused
+                .as_deref()
+                .and_then(|f| rows.iter().position(|x| x.key == f));
+            let mut next = None;
+            match key.as_str() {
+                "ArrowDown" => {
+                    let last = rows.len().saturating_sub(1);
+                    next = Some(pos.map_or(0, |i| (i + 1).min(last)));
+                    if in_filter {
+                        if let Some(el) = menu_ref.cast::<web_sys::HtmlElement>() {
+                            el.focus().unwrap_or_default();
+                        }
+                    }
+                }
+                "ArrowUp" => next = pos.map(|i| i.saturating_sub(1)),
+                "Home" => next = Some(0),
+                "End" => next = Some(rows.len().saturating_sub(1)),
+                "ArrowRight" => {
+                    if let Some(row) = pos.map(|i| &rows[i]) {
+                        if row.has_children && !row.expanded {
+                            state.borrow_mut().expanded.insert(row.key.clone());
+                            state.borrow().save(storage_key.as_ref());
+                        } else if row.expanded {
+                            next = pos.map(|i| i + 1);
+                        }
+                    }
+                }
+                "ArrowLeft" => {
+                    if let Some(row) = pos.map(|i| &rows[i]) {
+                        if row.expanded {
+                            state.borrow_mut().expanded.remove(&row.key);
+                            state.borrow().save(storage_key.as_ref());
+                        } else if let Some(parent) = &row.parent {
+                            next = rows.iter().position(|x| &x.key == parent);
+                        }
+                    }
+                }
+                "Enter" | " " => {
+                    // from the filter box, enter opens the first match
+                    if let Some(row) = pos.or(in_filter.then_some(0)).and_then(|i| rows.get(i)) {
+                        select.emit(row.key.clone());
+                    }
+                }
+                "Escape" => state.borrow_mut().filter.clear(),
+                _ => return,
+            }
+            e.prevent_default();
+            if let Some(row) = next.and_then(|i| rows.get(i)) {
+                state.borrow_mut().focused = Some(row.key.clone());
+            }
+            force_update.force_update();
+        })
+    };
+
+    let state = state.borrow();
+    let ctx = RenderCtx {
+        props,
+        state: &state,
+        filter: if props.collapsed {
+            String::new()
+        } else {
+            state.filter.to_lowercase()
+        },
+        select,
+        toggle,
+    };
+    let class = if props.collapsed {
+        "menu is-collapsed"
+    } else {
+        "menu"
+    };
     html! {
-        <aside class="menu">
+        <aside class={class} ref={menu_ref} tabindex="0" onkeydown={onkeydown}>
     {
-        props.labels.iter().map(|x|html!{
+        if props.filterable && !props.collapsed {
+            html! {
+                <div class="control has-icons-left menu-filter">
+                    <input class="input is-small" type="search" value={state.filter.clone()} oninput={oninput_filter}
+                        placeholder={i18n.t("menu.filter")} aria-label={i18n.t("menu.filter")}/>
+                    <span class="icon is-small is-left"><i class="fa-solid fa-magnifying-glass"></i></span>
+                </div>
+            }
+        } else {
+            html! {}
+        }
+    }
+    {
+        props.labels.iter().map(|x| {
+            let open = props.collapsed || state.is_label_open(x);
+            html!{
         <>
             {
-                if let Some(label) = &x.label{
+                match (&x.label, x.key()) {
+                    (Some(label), Some(key)) if !props.collapsed => {
+                        let onclick = {
+                            let toggle_label = toggle_label.clone();
+                            Callback::from(move |_| toggle_label.emit(key.clone()))
+                        };
+                        html!{
+                            <p class="menu-label is-clickable" onclick={onclick}>
+                            {label}
+                            <span class="icon is-small">
+                                <i class={if open {"fa-solid fa-chevron-down"} else {"fa-solid fa-chevron-right"}} aria-hidden="true"></i>
+                            </span>
+                            </p>
+                        }
+                    }
+                    _ => html!{},
+                }
+            }
+            {
+                if open {
                     html!{
-                        <p class="menu-label">
-                        {label}
-                        </p>
+                        <ul class="menu-list">
+                            {
+                                x.nodes.iter().map(|n|html!{
+                                    n.render(&ctx)
+                                }).collect::<Html>()
+                            }
+                        </ul>
                     }
-                } else{
+                } else {
                     html!{}
                 }
             }
-            <ul class="menu-list">
-                {
-                    x.nodes.iter().map(|n|html!{
-                        n.render(props)
-                    }).collect::<Html>()
-                }
-            </ul>
-        </>}).collect::<Html>()
+        </>}}).collect::<Html>()
     }
     </aside>
     }
@@ -1,13 +1,14 @@
 use crate::component::menu::{Menu, MenuLabel, MenuNode};
 use crate::component::message_list::MessageContent;
-use futures::stream::SplitSink;
-use futures::SinkExt;
-use gloo_net::websocket::{futures::WebSocket, Message};
+use crate::i18n::use_i18n::use_i18n;
+use crate::realtime::presence;
+use crate::realtime::use_connection_state::use_connection_state;
+use crate::realtime::use_presence::use_presence;
+use crate::realtime::{self, ConnectionState};
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::collections::LinkedList;
 use std::rc::Rc;
-use wasm_bindgen_futures::spawn_local;
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
 use yew::Properties;
@@ -23,9 +24,6 @@ pub struct MessageDialogProps {
     #[prop_or_default]
     pub messages: Rc<RefCell<HashMap<String, LinkedList<MessageContent>>>>,
 
-    #[prop_or_default]
-    pub ws_writer: Rc<RefCell<Option<SplitSink<WebSocket, Message>>>>,
-
     #[prop_or_default]
     pub current_room: Rc<RefCell<Option<String>>>,
 
@@ -41,6 +39,10 @@ impl PartialEq for MessageDialogProps {
 #[function_component(MessageDialog)]
 pub fn message_dialog(props: &MessageDialogProps) -> Html {
     let force_update = use_force_update();
+    let connection = use_connection_state();
+    let i18n = use_i18n();
+    // re-renders the dots and the typing line
+    use_presence();
     let borrow = props.current_room.borrow();
     let title = borrow.as_deref().unwrap_or("Dialog");
     let closedialog = {
@@ -56,12 +58,13 @@ pub fn message_dialog(props: &MessageDialogProps) -> Html {
         .iter()
         .map(|(room, _)| MenuNode {
             name: room.to_string(),
-            children: vec![],
+            ..Default::default()
         })
         .collect::<Vec<MenuNode>>();
 
     let room_labels = vec![MenuLabel {
         label: None,
+        key: None,
         nodes: room_nodes,
     }];
 
@@ -82,7 +85,6 @@ pub fn message_dialog(props: &MessageDialogProps) -> Html {
         let ref2 = ref2.clone();
         let messages = props.messages.clone();
         let current_room = props.current_room.clone();
-        let ws_writer = props.ws_writer.clone();
         let session_id = props.session_id.clone();
         Callback::from(move |e: KeyboardEvent| {
             if e.key_code() == 13 {
@@ -96,34 +98,29 @@ pub fn message_dialog(props: &MessageDialogProps) -> Html {
                         if let Some(room) = current_room.borrow().as_deref() {
                             let room = room.to_string();
                             let session_id = session_id.clone();
-                            let messages = messages.clone();
-                            let force_update = force_update.clone();
-                            let ws_writer = ws_writer.clone();
-                            spawn_local(async move {
-                                if let Some(ss) = &mut *ws_writer.borrow_mut() {
-                                    ss.send(Message::Text(content.clone())).await.unwrap();
-                                    messages
-                                        .borrow_mut()
-                                        .entry(room.clone())
-                                        .or_insert(Default::default())
-                                        .push_back(MessageContent {
-                                            id: 0,
-                                            room: room.to_string(),
-                                            from_id: "".to_string(),
-                                            from_name: session_id,
-                                            content: content.clone(),
-                                            time: "".to_string(), //chrono::Utc::now().to_default(),
-                                            is_own: Some(()),
-                                        });
-                                    force_update.force_update()
-                                }
-                            });
+                            realtime::send_text(content.clone());
+                            messages
+                                .borrow_mut()
+                                .entry(room.clone())
+                                .or_insert(Default::default())
+                                .push_back(MessageContent {
+                                    id: 0,
+                                    room: room.to_string(),
+                                    from_id: "".to_string(),
+                                    from_name: session_id,
+                                    content: content.clone(),
+                                    time: "".to_string(), //chrono::Utc::now().to_default(),
+                                    is_own: Some(()),
+                                });
+                            force_update.force_update()
                         }
                     }
                     input.set_value("");
                 } else {
                     input.set_value(&(input.value() + "\n"));
                 }
+            } else if let Some(room) = current_room.borrow().as_deref() {
+                presence::typed(room);
             }
             // false
         })
@@ -133,10 +130,12 @@ pub fn message_dialog(props: &MessageDialogProps) -> Html {
 
     if let Some(room) = props.current_room.borrow().as_deref() {
         if let Some(sessions) = props.rooms.borrow().get(room) {
-            for (_, name) in sessions.iter() {
+            for (session, name) in sessions.iter() {
                 session_nodes.push(MenuNode {
                     name: name.to_string(),
-                    children: vec![],
+                    key: Some(session.to_string()),
+                    icon: Some(presence::of(session).icon()),
+                    ..Default::default()
                 });
             }
         }
@@ -144,6 +143,7 @@ pub fn message_dialog(props: &MessageDialogProps) -> Html {
 
     let session_labels = vec![MenuLabel {
         label: None,
+        key: None,
         nodes: session_nodes,
     }];
 
@@ -172,33 +172,52 @@ pub fn message_dialog(props: &MessageDialogProps) -> Html {
         });
     }
     let current_room = (&*props.current_room.borrow()).clone();
+    let typing = current_room
+        .as_deref()
+        .map(presence::typing)
+        .unwrap_or_default();
+    let typing = if typing.is_empty() {
+        html! {}
+    } else {
+        html! {
+            <p class="help message-dialog-typing">
+                {i18n.t_args("presence.typing", &[("names", typing.join(", ").as_str())])}
+            </p>
+        }
+    };
 
     html! {
         <div class="modal is-active">
             <div class="modal-background"></div>
-            <div class="modal-card" style="height:70%;width:60%;">
+            <div class="modal-card message-dialog">
                 <header class="modal-card-head">
-                <p class="modal-card-title">{title}</p>
+                <p class="modal-card-title">
+                    {title}
+                    if connection != ConnectionState::Connected {
+                        {" "}<span class="tag is-warning is-light">{format!("{:?}", connection).to_lowercase()}</span>
+                    }
+                </p>
                 <button class="delete" aria-label="close" onclick={closedialog}></button>
                 </header>
 
                 <section class="modal-card-body">
-                <div class="columns" style="height:100%;">
+                <div class="columns message-dialog-columns">
                 <div class="column is-2">
-                    <div style="height: 100%; overflow: scroll;">
+                    <div class="message-dialog-rooms">
                         <Menu labels = {room_labels} selected_name = {current_room} onselect = {click_room}/>
                     </div>
                 </div>
                 <div class="column is-7">
-                    <div style="height: 70%;">
-                        <textarea ref={ref1} style="height: 100%;" readonly={true} class="textarea has-fixed-size"></textarea>
+                    <div class="message-dialog-history">
+                        <textarea ref={ref1} readonly={true} class="textarea has-fixed-size"></textarea>
                     </div>
-                    <div style="margin-top: 0.8em;">
+                    <div class="message-dialog-input">
                         <textarea ref={ref2} class="textarea has-fixed-size" onkeydown={key_send} />
                     </div>
+                    {typing}
                 </div>
                 <div class="column is-3">
-                    <div style="height: 100%; overflow: scroll;">
+                    <div class="message-dialog-members">
                         <Menu labels = {session_labels}/>
                     </div>
                 </div>
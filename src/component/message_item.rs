@@ -25,6 +25,14 @@ pub struct MessageItemValue {
     pub r#type: MessageItemType,
     // seconds
     pub timeout: Option<u32>,
+    pub action: Option<MessageAction>,
+}
+
+// a button next to the content, e.g. undo; the message closes once it is clicked
+#[derive(PartialEq, Debug, Clone)]
+pub struct MessageAction {
+    pub label: String,
+    pub onclick: Callback<()>,
 }
 
 #[derive(PartialEq, Properties, Debug)]
@@ -51,8 +59,17 @@ impl MessageItemValue {
             from_id: from_id.and_then(|x| Some(x.to_string())),
             from: from.and_then(|x| Some(x.to_string())),
             timeout,
+            action: None,
         }
     }
+
+    pub fn with_action(mut self, label: &str, onclick: Callback<()>) -> Self {
+        self.action = Some(MessageAction {
+            label: label.to_string(),
+            onclick,
+        });
+        self
+    }
 }
 
 #[function_component(MessageItem)]
@@ -86,6 +103,20 @@ pub fn message_item(props: &MessageItemProps) -> Html {
         }
     };
 
+    let action = value
+        .action
+        .clone()
+        .map(|MessageAction { label, onclick }| {
+            let onclose = props.onclose.clone();
+            let onclick = Callback::from(move |_| {
+                onclick.emit(());
+                onclose.emit(id);
+            });
+            html! {
+                <button class="button is-small is-light ml-2" onclick={onclick}>{label}</button>
+            }
+        });
+
     if let Some(timeout) = value.timeout {
         let onclose = props.onclose.clone();
         Timeout::new(1000 * timeout, move || onclose.emit(id)).forget();
@@ -99,6 +130,7 @@ pub fn message_item(props: &MessageItemProps) -> Html {
         </div>
         <div class="message-body"  style={cursor_style} onclick = {open_dialog}>
            {content}
+           {action}
         </div>
         </article>
     }
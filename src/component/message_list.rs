@@ -8,13 +8,12 @@ use yew::prelude::*;
 use yew::Properties;
 
 use super::message_dialog::MessageDialog;
-use crate::util::request;
-use futures::stream::SplitSink;
-use futures::{SinkExt, StreamExt};
-use gloo_net::websocket::{futures::WebSocket, Message};
+use crate::i18n;
+use crate::realtime;
+use crate::util::api_error::ApiError;
+use crate::util::error_log::{self, ErrorSource};
 use std::collections::HashMap;
 use std::collections::LinkedList;
-use wasm_bindgen_futures::spawn_local;
 
 const DEFAULT_ROOM: &str = "main";
 const UPDATE_SESSION_PRE: &str = "update_session:";
@@ -24,6 +23,23 @@ const QUIT_ROOM_PRE: &str = "quit_room:";
 const UPDATE_NAME_PRE: &str = "update_name:";
 const MESSAGE_PRE: &str = "message:";
 
+thread_local! {
+    // set while a list with a live connection is mounted
+    static OPEN_CHAT: RefCell<Option<Callback<()>>> = const { RefCell::new(None) };
+}
+
+pub fn chat_available() -> bool {
+    OPEN_CHAT.with(|x| x.borrow().is_some())
+}
+
+// opens the chat dialog of the mounted list, e.g. from the command palette
+pub fn open_chat() {
+    let open = OPEN_CHAT.with(|x| x.borrow().clone());
+    if let Some(open) = open {
+        open.emit(());
+    }
+}
+
 #[derive(Deserialize)]
 struct UpdateSession<'a> {
     pub room: &'a str,
@@ -44,6 +60,13 @@ struct RoomChange<'a> {
     pub room: &'a str,
 }
 
+// a malformed frame is logged and skipped, one bad frame must not take the page down
+fn parse_frame<'a, T: Deserialize<'a>>(content: &'a str, pre: &str) -> Option<T> {
+    serde_json::from_str(content.trim_start_matches(pre))
+        .map_err(|e| log::error!("skipping malformed {} frame: {}", pre, e))
+        .ok()
+}
+
 #[derive(Clone, Properties)]
 pub struct MessageListProps {
     #[prop_or_default]
@@ -74,7 +97,7 @@ pub struct MessageContent {
 pub fn ok(msg: &str) -> MessageItemValue {
     MessageItemValue::new(
         MessageItemType::Success,
-        "Success",
+        &i18n::t("message.success"),
         msg,
         Some(5),
         None,
@@ -85,7 +108,7 @@ pub fn ok(msg: &str) -> MessageItemValue {
 pub fn warn(msg: &str) -> MessageItemValue {
     MessageItemValue::new(
         MessageItemType::Warning,
-        "Warning",
+        &i18n::t("message.warning"),
         msg,
         Some(10),
         None,
@@ -94,11 +117,59 @@ pub fn warn(msg: &str) -> MessageItemValue {
 }
 
 pub fn info(msg: &str) -> MessageItemValue {
-    MessageItemValue::new(MessageItemType::Info, "Info", msg, Some(8), None, None)
+    MessageItemValue::new(
+        MessageItemType::Info,
+        &i18n::t("message.info"),
+        msg,
+        Some(8),
+        None,
+        None,
+    )
 }
 
 pub fn error(msg: &str) -> MessageItemValue {
-    MessageItemValue::new(MessageItemType::Danger, "Error", msg, None, None, None)
+    MessageItemValue::new(
+        MessageItemType::Danger,
+        &i18n::t("message.error"),
+        msg,
+        None,
+        None,
+        None,
+    )
+}
+
+// stays up for the undo window of `seconds`
+pub fn undo(msg: &str, seconds: u32, onundo: Callback<()>) -> MessageItemValue {
+    MessageItemValue::new(
+        MessageItemType::Info,
+        &i18n::t("message.info"),
+        msg,
+        Some(seconds),
+        None,
+        None,
+    )
+    .with_action(&i18n::t("common.undo"), onundo)
+}
+
+// hints such as "code already sent" are warnings, everything else an error
+pub fn api_error(err: &ApiError) -> MessageItemValue {
+    if err.is_network() || err.status >= 500 {
+        error_log::record(ErrorSource::Api, &err.user_message());
+    }
+    if err.is_hint() {
+        warn(&err.user_message())
+    } else {
+        error(&err.user_message())
+    }
+}
+
+// an optimistic change the server refused, shown after the table was put back
+pub fn rolled_back(err: &ApiError) -> MessageItemValue {
+    let message = err.user_message();
+    MessageItemValue {
+        content: i18n::t_args("api_error.rolled_back", &[("message", message.as_str())]),
+        ..api_error(err)
+    }
 }
 
 pub fn message(room: &str, from_id: &str, from_name: &str, content: &str) -> MessageItemValue {
@@ -116,8 +187,6 @@ pub fn message(room: &str, from_id: &str, from_name: &str, content: &str) -> Mes
 pub fn message_list(props: &MessageListProps) -> Html {
     let force_update = use_force_update();
     let dialog_closed: Rc<RefCell<bool>> = use_mut_ref(|| true);
-    let ws_writer: Rc<RefCell<Option<SplitSink<WebSocket, Message>>>> =
-        use_mut_ref(|| Default::default());
     let rooms: Rc<RefCell<HashMap<String, HashMap<String, String>>>> =
         use_mut_ref(|| Default::default());
     let session_id: Rc<RefCell<Option<String>>> = use_mut_ref(|| Default::default());
@@ -140,140 +209,134 @@ pub fn message_list(props: &MessageListProps) -> Html {
         let force_update = force_update.clone();
         let dialog_closed = dialog_closed.clone();
         let ws = props.ws;
-        let ws_writer = ws_writer.clone();
         let text_messages = text_messages.clone();
         let self_rooms = rooms.clone();
         let session_id = session_id.clone();
         let session_id_c = session_id.clone();
         let message_list = message_list.clone();
         let current_room = current_room.clone();
-        // depends on (), only effected once
+        // depends on (), only effected once; the socket itself belongs to the realtime service
         use_effect_with((), move |_| {
+            let mut subscription = None;
             if ws {
-                let ws = request::open_ws().unwrap();
-                let (writer, mut reader) = ws.split();
-                *ws_writer.borrow_mut() = Some(writer);
-                let w1 = Rc::clone(&ws_writer);
-                spawn_local(async move {
-                    if let Some(ss) = &mut *w1.borrow_mut() {
-                        ss.send(Message::Text(String::from("i am back online!")))
-                            .await
-                            .unwrap();
-                    }
-                });
+                realtime::send_text(String::from("i am back online!"));
 
                 let mut sid = session_id_c.borrow_mut();
                 if sid.is_none() {
-                    let user = &crate::util::common::get_current_user().unwrap();
-                    *sid = Some(user.name.clone().unwrap_or(user.email.clone()));
+                    if let Ok(user) = crate::util::common::get_current_user() {
+                        *sid = Some(user.name.clone().unwrap_or(user.email.clone()));
+                    }
                 }
-                spawn_local(async move {
-                    while let Some(msg) = reader.next().await {
-                        match msg {
-                            Ok(msg) => {
-                                // log::info!("{:?}", msg);
-                                // 【{room}】{name}: {msg}
-                                if let Message::Text(content) = msg {
-                                    if content.starts_with(MESSAGE_PRE) {
-                                        let message_content: MessageContent = serde_json::from_str(
-                                            content.trim_start_matches(MESSAGE_PRE),
-                                        )
-                                        .unwrap();
-                                        text_messages
-                                            .borrow_mut()
-                                            .entry(message_content.room.clone())
-                                            .or_insert(Default::default())
-                                            .push_back(message_content.clone());
-
-                                        if *dialog_closed.borrow() {
-                                            message_list.borrow_mut().push_back(message(
-                                                &message_content.room,
-                                                &message_content.from_id,
-                                                &message_content.from_name,
-                                                &message_content.content,
-                                            ));
-                                        }
-                                    } else {
-                                        if content.starts_with(UPDATE_SESSION_PRE) {
-                                            let change: UpdateSession = serde_json::from_str(
-                                                content.trim_start_matches(UPDATE_SESSION_PRE),
-                                            )
-                                            .unwrap();
-
-                                            *current_room.borrow_mut() =
-                                                Some(change.room.to_string());
-                                        } else if content.starts_with(LIST_PRE) {
-                                            let rooms: HashMap<String, HashMap<String, String>> =
-                                                serde_json::from_str(
-                                                    content.trim_start_matches(LIST_PRE),
-                                                )
-                                                .unwrap();
-
-                                            *self_rooms.borrow_mut() = rooms;
-                                        } else if content.starts_with(JOIN_ROOM_PRE) {
-                                            let change: RoomChange = serde_json::from_str(
-                                                content.trim_start_matches(JOIN_ROOM_PRE),
-                                            )
-                                            .unwrap();
-                                            let mut sr = self_rooms.borrow_mut();
-
-                                            sr.entry(change.room.to_string()).or_default().insert(
-                                                change.session_id.to_string(),
-                                                change.name.to_string(),
-                                            );
-                                        } else if content.starts_with(QUIT_ROOM_PRE) {
-                                            let change: RoomChange = serde_json::from_str(
-                                                content.trim_start_matches(QUIT_ROOM_PRE),
-                                            )
-                                            .unwrap();
-
-                                            let mut sr = self_rooms.borrow_mut();
-
-                                            if let Some(current_session_id) = &*session_id.borrow()
-                                            {
-                                                if current_session_id == change.session_id {
-                                                    sr.remove(change.room);
-                                                } else {
-                                                    sr.get_mut(change.room)
-                                                        .and_then(|x| x.remove(change.session_id));
-                                                }
-                                            }
-                                        } else if content.starts_with(UPDATE_NAME_PRE) {
-                                            let change: UpdateName = serde_json::from_str(
-                                                content.trim_start_matches(UPDATE_NAME_PRE),
-                                            )
-                                            .unwrap();
-
-                                            for (_, sessions) in &mut *self_rooms.borrow_mut() {
-                                                sessions
-                                                    .entry(change.session_id.to_string())
-                                                    .and_modify(|x| *x = change.name.to_string());
-                                            }
-                                        }
-                                    }
-                                    force_update.force_update();
+                // 【{room}】{name}: {msg}
+                subscription = Some(realtime::subscribe_text(move |content| {
+                    if content.starts_with(MESSAGE_PRE) {
+                        let message_content: MessageContent =
+                            match parse_frame(content, MESSAGE_PRE) {
+                                Some(v) => v,
+                                None => return,
+                            };
+                        realtime::presence::stopped_typing(
+                            &message_content.room,
+                            &message_content.from_id,
+                        );
+                        text_messages
+                            .borrow_mut()
+                            .entry(message_content.room.clone())
+                            .or_insert(Default::default())
+                            .push_back(message_content.clone());
+
+                        if *dialog_closed.borrow() {
+                            message_list.borrow_mut().push_back(message(
+                                &message_content.room,
+                                &message_content.from_id,
+                                &message_content.from_name,
+                                &message_content.content,
+                            ));
+                        }
+                    } else {
+                        if content.starts_with(UPDATE_SESSION_PRE) {
+                            let change: UpdateSession =
+                                match parse_frame(content, UPDATE_SESSION_PRE) {
+                                    Some(v) => v,
+                                    None => return,
+                                };
+
+                            *current_room.borrow_mut() = Some(change.room.to_string());
+                        } else if content.starts_with(LIST_PRE) {
+                            let rooms: HashMap<String, HashMap<String, String>> =
+                                match parse_frame(content, LIST_PRE) {
+                                    Some(v) => v,
+                                    None => return,
+                                };
+
+                            *self_rooms.borrow_mut() = rooms;
+                        } else if content.starts_with(JOIN_ROOM_PRE) {
+                            let change: RoomChange = match parse_frame(content, JOIN_ROOM_PRE) {
+                                Some(v) => v,
+                                None => return,
+                            };
+                            let mut sr = self_rooms.borrow_mut();
+
+                            sr.entry(change.room.to_string())
+                                .or_default()
+                                .insert(change.session_id.to_string(), change.name.to_string());
+                        } else if content.starts_with(QUIT_ROOM_PRE) {
+                            let change: RoomChange = match parse_frame(content, QUIT_ROOM_PRE) {
+                                Some(v) => v,
+                                None => return,
+                            };
+
+                            let mut sr = self_rooms.borrow_mut();
+
+                            if let Some(current_session_id) = &*session_id.borrow() {
+                                if current_session_id == change.session_id {
+                                    sr.remove(change.room);
+                                } else {
+                                    sr.get_mut(change.room)
+                                        .and_then(|x| x.remove(change.session_id));
                                 }
                             }
-                            Err(err) => match err {
-                                gloo_net::websocket::WebSocketError::ConnectionError => {
-                                    log::error!("connection error: {:#?}", err);
-                                    break;
-                                }
-                                gloo_net::websocket::WebSocketError::ConnectionClose(e) => {
-                                    log::info!("connection closed, close event: {:#?}", e);
-                                    break;
-                                }
-                                gloo_net::websocket::WebSocketError::MessageSendError(e) => {
-                                    log::error!("message send error: {:#?}", e);
-                                }
-                                _ => {
-                                    log::error!("read error: {:#?}", err);
-                                }
-                            },
+                        } else if content.starts_with(UPDATE_NAME_PRE) {
+                            let change: UpdateName = match parse_frame(content, UPDATE_NAME_PRE) {
+                                Some(v) => v,
+                                None => return,
+                            };
+
+                            for (_, sessions) in &mut *self_rooms.borrow_mut() {
+                                sessions
+                                    .entry(change.session_id.to_string())
+                                    .and_modify(|x| *x = change.name.to_string());
+                            }
                         }
                     }
+                    force_update.force_update();
+                }));
+            }
+            move || drop(subscription)
+        });
+    }
+
+    {
+        let dialog_closed = dialog_closed.clone();
+        let current_room = current_room.clone();
+        let session_id = session_id.clone();
+        let force_update = force_update.clone();
+        use_effect_with(props.ws, move |ws| {
+            if *ws {
+                let open = Callback::from(move |_| {
+                    // nobody to chat as without a signed in user
+                    if session_id.borrow().is_none() {
+                        return;
+                    }
+                    *dialog_closed.borrow_mut() = false;
+                    current_room
+                        .borrow_mut()
+                        .get_or_insert_with(|| DEFAULT_ROOM.to_string());
+                    force_update.force_update();
                 });
+                OPEN_CHAT.with(|x| *x.borrow_mut() = Some(open));
             }
+            || OPEN_CHAT.with(|x| *x.borrow_mut() = None)
         });
     }
 
@@ -329,13 +392,11 @@ pub fn message_list(props: &MessageListProps) -> Html {
         }
         </div>
         {
-            if !*dialog_closed.borrow() {
-                let session_id = session_id.borrow().clone().unwrap();
-                html!{
-                    <MessageDialog session_id={session_id} rooms={rooms.clone()} messages={text_messages.clone()}  ws_writer = {ws_writer.clone()} onclose={on_close} current_room = { current_room }/>
-                }
-            }else{
-                html!{}
+            match session_id.borrow().clone() {
+                Some(session_id) if !*dialog_closed.borrow() => html!{
+                    <MessageDialog session_id={session_id} rooms={rooms.clone()} messages={text_messages.clone()} onclose={on_close} current_room = { current_room }/>
+                },
+                _ => html!{},
             }
         }
         </>
@@ -1,7 +1,11 @@
+pub mod command_palette;
 pub mod message_item;
 pub mod message_list;
 pub mod pager;
 pub mod pager_item;
 pub mod welcome;
 pub mod message_dialog;
-pub mod menu;
\ No newline at end of file
+pub mod menu;
+pub mod session_list;
+pub mod shortcut_help;
+pub mod password_strength;
\ No newline at end of file
@@ -0,0 +1,45 @@
+use crate::i18n::use_i18n::use_i18n;
+use crate::util::password_policy::use_password_policy;
+use yew::{prelude::*, Properties};
+
+#[derive(Properties, PartialEq)]
+pub struct PasswordStrengthProps {
+    pub value: String,
+}
+
+#[function_component(PasswordStrength)]
+pub fn password_strength(props: &PasswordStrengthProps) -> Html {
+    // labels come from the catalog, so re-render on language change
+    use_i18n();
+    let policy = use_password_policy();
+    if props.value.is_empty() {
+        return html! {};
+    }
+
+    let strength = policy.strength(&props.value);
+    html! {
+        <div class="field password-strength">
+            <div class="is-flex is-align-items-center">
+                <progress class={classes!("progress", "is-small", strength.class())} value={(strength.score() + 1).to_string()} max="5"></progress>
+                <span class={classes!("help", strength.class())}>{strength.label()}</span>
+            </div>
+            <ul>
+            {
+                policy.checklist(&props.value).into_iter().map(|(requirement, met)| {
+                    let (icon, class) = if met {
+                        ("fa-solid fa-check", "has-text-success")
+                    } else {
+                        ("fa-solid fa-xmark", "has-text-grey")
+                    };
+                    html! {
+                        <li class={class}>
+                            <span class="icon is-small"><i class={icon}></i></span>
+                            <span>{requirement.label()}</span>
+                        </li>
+                    }
+                }).collect::<Html>()
+            }
+            </ul>
+        </div>
+    }
+}
@@ -0,0 +1,232 @@
+use crate::component::message_item::MessageItemValue;
+use crate::component::message_list::{self, MessageList};
+use crate::confirm_form::ConfirmForm;
+use crate::i18n::{self, use_i18n::use_i18n};
+use crate::query::keys;
+use crate::query::use_mutation::use_mutation;
+use crate::query::use_query::use_query_with;
+use crate::query::QueryOptions;
+use crate::util::common;
+use crate::util::request::{self, Host};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::rc::Rc;
+use yew::prelude::*;
+use yew::Properties;
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Session {
+    pub id: String,
+    pub device: Option<String>,
+    pub ip: Option<String>,
+    pub last_seen: i64,
+    pub created_at: i64,
+    #[serde(default)]
+    pub is_current: bool,
+}
+
+#[derive(Serialize)]
+pub struct RevokeSessionReq {
+    pub id: String,
+    pub user_id: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct RevokeOtherSessionsReq {
+    pub user_id: Option<i64>,
+}
+
+#[derive(Clone, PartialEq)]
+enum Revoke {
+    One(Session),
+    Others,
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct SessionListProps {
+    // None lists the sessions of the current user
+    #[prop_or_default]
+    pub user_id: Option<i64>,
+}
+
+#[function_component(SessionList)]
+pub fn session_list(props: &SessionListProps) -> Html {
+    let i18n = use_i18n();
+    let force_update = use_force_update();
+    let message: Rc<RefCell<Option<MessageItemValue>>> = use_mut_ref(|| None);
+    let confirm: Rc<RefCell<Option<Revoke>>> = use_mut_ref(|| None);
+    let user_id = props.user_id;
+    // sessions come and go quickly, so they are never served from the cache alone
+    // and not kept once the list is closed
+    let sessions = use_query_with(
+        keys::user_sessions(user_id),
+        QueryOptions::new().stale(0).cache(0),
+        move |opts| async move {
+            let user_id = user_id.map(|x| x.to_string());
+            let params = user_id.as_deref().map(|x| vec![("user_id", x)]);
+            let res = opts
+                .get::<Vec<Session>, _, _>(Host::ApiBase, "/session/list", params)
+                .await?;
+            Ok(res.data.unwrap_or_default())
+        },
+    );
+    let revoke = use_mutation(vec![keys::sessions()], move |revoke: Revoke| async move {
+        match revoke {
+            Revoke::One(session) => {
+                request::post::<serde_json::Value, _>(
+                    Host::ApiBase,
+                    "/session/revoke",
+                    &RevokeSessionReq {
+                        id: session.id,
+                        user_id,
+                    },
+                )
+                .await
+            }
+            Revoke::Others => {
+                request::post::<serde_json::Value, _>(
+                    Host::ApiBase,
+                    "/session/revoke_others",
+                    &RevokeOtherSessionsReq { user_id },
+                )
+                .await
+            }
+        }
+    });
+
+    {
+        let message = message.clone();
+        let force_update = force_update.clone();
+        use_effect_with(sessions.error.clone(), move |err| {
+            if let Some(err) = err {
+                *message.borrow_mut() = Some(message_list::api_error(err));
+                force_update.force_update();
+            }
+        });
+    }
+
+    let confirm_close = {
+        let confirm = confirm.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            *confirm.borrow_mut() = None;
+            force_update.force_update();
+        })
+    };
+
+    let confirm_confirm = {
+        let confirm = confirm.clone();
+        let message = message.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            let current = match confirm.borrow_mut().take() {
+                Some(v) => v,
+                None => return force_update.force_update(),
+            };
+            let message = message.clone();
+            let force_update = force_update.clone();
+            revoke.run(current.clone(), move |res| {
+                match res {
+                    Ok(_) => {
+                        if let Revoke::One(session) = current {
+                            if session.is_current {
+                                common::logout();
+                                return;
+                            }
+                        }
+                        *message.borrow_mut() =
+                            Some(message_list::ok(&i18n::t("session.signed_out")));
+                    }
+                    Err(err) => *message.borrow_mut() = Some(message_list::api_error(&err)),
+                }
+                force_update.force_update();
+            });
+        })
+    };
+
+    let revoke_others = {
+        let confirm = confirm.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            *confirm.borrow_mut() = Some(Revoke::Others);
+            force_update.force_update();
+        })
+    };
+
+    let sessions = sessions.data.unwrap_or_default();
+    let has_current = sessions.iter().any(|x| x.is_current);
+    let unknown = i18n.t("session.unknown_device");
+    let confirm_content = match &*confirm.borrow() {
+        Some(Revoke::One(session)) if session.is_current => i18n.t("session.confirm_current"),
+        Some(Revoke::One(session)) => {
+            // the device is whatever user agent the client sent, the content is rendered as html
+            let device = common::escape_html(session.device.as_deref().unwrap_or(&unknown));
+            i18n.t_args("session.confirm_one", &[("device", device.as_str())])
+        }
+        Some(Revoke::Others) if has_current => i18n.t("session.confirm_others"),
+        Some(Revoke::Others) => i18n.t("session.confirm_all"),
+        None => String::default(),
+    };
+
+    html! {
+        <>
+        <MessageList value = {(*message.borrow()).clone()}/>
+        if confirm.borrow().is_some() {
+            <ConfirmForm onclose = {confirm_close} onconfirm = {confirm_confirm} content = {confirm_content}/>
+        }
+        <div class="table-container">
+            <table class="table is-bordered is-striped is-narrow is-hoverable">
+            <thead>
+                <tr>
+                <th><abbr title={i18n.t("session.device")}>{i18n.t("session.device")}</abbr></th>
+                <th><abbr title={i18n.t("session.ip")}>{i18n.t("session.ip")}</abbr></th>
+                <th><abbr title={i18n.t("session.last_seen")}>{i18n.t("session.last_seen")}</abbr></th>
+                <th><abbr title={i18n.t("user.created_at")}>{i18n.t("user.created_at")}</abbr></th>
+                <th></th>
+                </tr>
+            </thead>
+            <tbody>
+            {
+                if sessions.is_empty() {
+                    html!{
+                        <tr><td colspan="5" class="has-text-centered">{i18n.t("session.empty")}</td></tr>
+                    }
+                } else {
+                    sessions.iter().map(|x| {
+                        let revoke = {
+                            let confirm = confirm.clone();
+                            let force_update = force_update.clone();
+                            let session = x.clone();
+                            Callback::from(move |_| {
+                                *confirm.borrow_mut() = Some(Revoke::One(session.clone()));
+                                force_update.force_update();
+                            })
+                        };
+                        html!{
+                            <tr>
+                                <td>
+                                    {x.device.clone().unwrap_or(unknown.clone())}
+                                    if x.is_current {
+                                        {" "}<span class="tag is-success is-light">{i18n.t("session.current")}</span>
+                                    }
+                                </td>
+                                <td>{x.ip.clone().unwrap_or_default()}</td>
+                                <td>{i18n.format_datetime(x.last_seen)}</td>
+                                <td>{i18n.format_datetime(x.created_at)}</td>
+                                <td>
+                                    <button class="button is-small is-light is-danger" onclick={revoke}>{i18n.t("session.sign_out")}</button>
+                                </td>
+                            </tr>
+                        }
+                    }).collect::<Html>()
+                }
+            }
+            </tbody>
+            </table>
+        </div>
+        <button class="button is-light is-danger" disabled={sessions.iter().all(|x| x.is_current)} onclick={revoke_others}>
+            {i18n.t(if has_current {"session.sign_out_others"} else {"session.sign_out_all"})}
+        </button>
+        </>
+    }
+}
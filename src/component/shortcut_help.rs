@@ -0,0 +1,101 @@
+use crate::i18n::use_i18n::use_i18n;
+use crate::shortcut;
+use crate::shortcut::use_shortcut::use_shortcut;
+use gloo::events::EventListener;
+use wasm_bindgen::JsCast;
+use web_sys::KeyboardEvent;
+use yew::prelude::*;
+
+// keys handled inside the palette, not through the registry
+const PALETTE_KEYS: [(&str, &str); 3] = [
+    ("↑ ↓", "shortcut.palette_move"),
+    ("Enter", "shortcut.palette_run"),
+    ("Esc", "shortcut.close"),
+];
+
+#[function_component(ShortcutHelp)]
+pub fn shortcut_help() -> Html {
+    let i18n = use_i18n();
+    let force_update = use_force_update();
+    let open = use_mut_ref(|| false);
+
+    let close = {
+        let open = open.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            *open.borrow_mut() = false;
+            force_update.force_update();
+        })
+    };
+
+    {
+        let open = open.clone();
+        let force_update = force_update.clone();
+        use_shortcut(
+            "shortcut_help",
+            "?",
+            "shortcut.show_help",
+            Callback::from(move |_| {
+                let v = !*open.borrow();
+                *open.borrow_mut() = v;
+                force_update.force_update();
+            }),
+        );
+    }
+
+    {
+        let close = close.clone();
+        let is_open = *open.borrow();
+        use_effect_with(is_open, move |is_open| {
+            let listener = is_open.then(|| {
+                let window = web_sys::window().unwrap();
+                EventListener::new(&window, "keydown", move |e| {
+                    if e.dyn_ref::<KeyboardEvent>()
+                        .is_some_and(|x| x.key() == "Escape")
+                    {
+                        close.emit(());
+                    }
+                })
+            });
+            move || drop(listener)
+        });
+    }
+
+    if !*open.borrow() {
+        return html! {};
+    }
+
+    let rows = shortcut::bindings()
+        .into_iter()
+        .map(|(binding, description)| (binding.display(), i18n.t(description)))
+        .chain(
+            PALETTE_KEYS
+                .iter()
+                .map(|(keys, description)| (keys.to_string(), i18n.t(description))),
+        )
+        .map(|(keys, description)| {
+            html! {
+                <tr>
+                    <td>{keys.split(' ').map(|k| html! { <kbd>{k}</kbd> }).collect::<Html>()}</td>
+                    <td>{description}</td>
+                </tr>
+            }
+        })
+        .collect::<Html>();
+    html! {
+        <div class="modal is-active shortcut-help">
+            <div class="modal-background" onclick={close.reform(|_| ())}></div>
+            <div class="modal-card">
+                <header class="modal-card-head">
+                <p class="modal-card-title">{i18n.t("shortcut.help_title")}</p>
+                <button class="delete" aria-label="close" onclick={close.reform(|_| ())}></button>
+                </header>
+                <section class="modal-card-body">
+                    <table class="table is-fullwidth">
+                        <tbody>{rows}</tbody>
+                    </table>
+                </section>
+            </div>
+        </div>
+    }
+}
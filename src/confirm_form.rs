@@ -1,3 +1,4 @@
+use crate::i18n::use_i18n::use_i18n;
 use crate::util::common;
 use yew::prelude::*;
 use yew::Properties;
@@ -15,6 +16,7 @@ pub struct ConfirmFormProps {
 
 #[function_component(ConfirmForm)]
 pub fn confirm_form(props: &ConfirmFormProps) -> Html {
+    let i18n = use_i18n();
     let close = {
         let onclose = props.onclose.clone();
         Callback::from(move |_| {
@@ -34,7 +36,7 @@ pub fn confirm_form(props: &ConfirmFormProps) -> Html {
             <div class="modal-background"></div>
             <div class="modal-card">
                 <header class="modal-card-head">
-                <p class="modal-card-title">{"Confirm"}</p>
+                <p class="modal-card-title">{i18n.t("common.confirm")}</p>
                 <button class="delete" aria-label="close" onclick={close.clone()}></button>
                 </header>
                 <section class="modal-card-body">
@@ -43,8 +45,8 @@ pub fn confirm_form(props: &ConfirmFormProps) -> Html {
                     }
                 </section>
                 <footer class="modal-card-foot">
-                <button class="button is-danger"  onclick={onconfirm}>{"Confirm"}</button>
-                <button class="button" onclick={close}>{"Cancel"}</button>
+                <button class="button is-danger"  onclick={onconfirm}>{i18n.t("common.confirm")}</button>
+                <button class="button" onclick={close}>{i18n.t("common.cancel")}</button>
                 </footer>
             </div>
         </div>
@@ -0,0 +1,121 @@
+use crate::i18n;
+use crate::util::error_log::{self, ErrorSource};
+use gloo::events::EventListener;
+use js_sys::Reflect;
+use wasm_bindgen::JsValue;
+use web_sys::{Document, Element};
+
+const SCREEN_ID: &str = "crash-screen";
+const DETAILS_ID: &str = "crash-details";
+
+// after a panic the app state can not be trusted, so the screen is plain dom and its
+// buttons are inline scripts that work even when the wasm module has trapped
+const RELOAD_SCRIPT: &str = "location.reload()";
+const COPY_SCRIPT: &str = "navigator.clipboard && navigator.clipboard.writeText(\
+    document.getElementById('crash-details').textContent)";
+
+fn element(document: &Document, tag: &str, class: &str, text: &str) -> Option<Element> {
+    let el = document.create_element(tag).ok()?;
+    el.set_class_name(class);
+    if !text.is_empty() {
+        el.set_text_content(Some(text));
+    }
+    Some(el)
+}
+
+fn show() -> Option<()> {
+    let document = web_sys::window()?.document()?;
+    let report = error_log::report();
+    // a second panic only refreshes the details
+    if let Some(details) = document.get_element_by_id(DETAILS_ID) {
+        details.set_text_content(Some(&report));
+        return Some(());
+    }
+
+    let screen = element(&document, "div", "modal is-active crash-screen", "")?;
+    screen.set_id(SCREEN_ID);
+    screen.set_attribute("role", "alertdialog").ok()?;
+    let card = element(&document, "div", "modal-card", "")?;
+
+    let head = element(&document, "header", "modal-card-head", "")?;
+    head.append_child(&element(
+        &document,
+        "p",
+        "modal-card-title",
+        &i18n::t("crash.title"),
+    )?)
+    .ok()?;
+
+    let body = element(&document, "section", "modal-card-body", "")?;
+    body.append_child(&element(&document, "p", "", &i18n::t("crash.desc"))?)
+        .ok()?;
+    let details = element(&document, "pre", "crash-details", &report)?;
+    details.set_id(DETAILS_ID);
+    body.append_child(&details).ok()?;
+
+    let foot = element(&document, "footer", "modal-card-foot", "")?;
+    let reload = element(
+        &document,
+        "button",
+        "button is-primary",
+        &i18n::t("crash.reload"),
+    )?;
+    reload.set_attribute("onclick", RELOAD_SCRIPT).ok()?;
+    let copy = element(
+        &document,
+        "button",
+        "button",
+        &i18n::t("crash.copy_details"),
+    )?;
+    copy.set_attribute("onclick", COPY_SCRIPT).ok()?;
+    foot.append_child(&reload).ok()?;
+    foot.append_child(&copy).ok()?;
+
+    card.append_child(&head).ok()?;
+    card.append_child(&body).ok()?;
+    card.append_child(&foot).ok()?;
+    screen
+        .append_child(&element(&document, "div", "modal-background", "")?)
+        .ok()?;
+    screen.append_child(&card).ok()?;
+    document.body()?.append_child(&screen).ok()?;
+    Some(())
+}
+
+fn js_message(value: &JsValue) -> String {
+    value
+        .as_string()
+        .or_else(|| {
+            Reflect::get(value, &JsValue::from_str("message"))
+                .ok()
+                .and_then(|x| x.as_string())
+        })
+        .unwrap_or_else(|| format!("{:?}", value))
+}
+
+// replaces the blank page a panic would leave with a screen offering reload and copy details
+pub fn install() {
+    std::panic::set_hook(Box::new(|info| {
+        let message = info.to_string();
+        log::error!("{}", message);
+        error_log::record(ErrorSource::Panic, &message);
+        if show().is_none() {
+            log::error!("render crash screen failed");
+        }
+    }));
+
+    let window = match web_sys::window() {
+        Some(v) => v,
+        None => return,
+    };
+    // script errors and rejected promises are only logged, the page may well keep working
+    EventListener::new(&window, "error", |e| {
+        error_log::record(ErrorSource::Script, &js_message(e.as_ref()));
+    })
+    .forget();
+    EventListener::new(&window, "unhandledrejection", |e| {
+        let reason = Reflect::get(e.as_ref(), &JsValue::from_str("reason")).unwrap_or_default();
+        error_log::record(ErrorSource::Script, &js_message(&reason));
+    })
+    .forget();
+}
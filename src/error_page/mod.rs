@@ -1,2 +1,3 @@
+pub mod crash;
 pub mod request_error;
 pub mod page_not_found;
\ No newline at end of file
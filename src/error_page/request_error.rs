@@ -1,3 +1,4 @@
+use crate::i18n::use_i18n::use_i18n;
 use yew::prelude::*;
 
 #[derive(PartialEq, Properties)]
@@ -7,58 +8,79 @@ pub struct RequestProps {
 
 #[function_component(RequestError)]
 pub fn request_error(props: &RequestProps) -> Html {
+    let i18n = use_i18n();
+    let lang = i18n.locale().code();
     match props.status {
         401 => html! {
-        <html lang="en">
+        <html lang={lang}>
         <head>
             <meta charset="utf-8"/>
-            <title>{"401 Unauthorized"}</title>
+            <title>{i18n.t("request_error.unauthorized_title")}</title>
         </head>
         <body align="center">
             <div role="main" align="center">
-                <h1>{"401: Unauthorized"}</h1>
-                <p>{"The request requires user authentication."}</p>
+                <h1>{i18n.t("request_error.unauthorized_heading")}</h1>
+                <p>{i18n.t("request_error.unauthorized_desc")}</p>
                 // <p>{"uri: "}<b></b></p>
                 <hr/>
             </div>
             <div role="contentinfo" align="center">
-                <span style="margin-top:15px;"><a href="/login">{"Go to login"}</a></span>
+                <span style="margin-top:15px;"><a href="/login">{i18n.t("common.go_to_login")}</a></span>
+            </div>
+        </body>
+        </html>
+            },
+        403 => html! {
+        <html lang={lang}>
+        <head>
+            <meta charset="utf-8"/>
+            <title>{i18n.t("request_error.forbidden_title")}</title>
+        </head>
+        <body align="center">
+            <div role="main" align="center">
+                <h1>{i18n.t("request_error.forbidden_heading")}</h1>
+                <p>{i18n.t("request_error.forbidden_desc")}</p>
+                // <p>{"uri: "}<b></b></p>
+                <hr/>
+            </div>
+            <div role="contentinfo" align="center">
+                <span style="margin-top:15px;"><a href="/login">{i18n.t("common.go_to_login")}</a></span>
             </div>
         </body>
         </html>
             },
         404 => html! {
-        <html lang="en">
+        <html lang={lang}>
         <head>
             <meta charset="utf-8"/>
-            <title>{"401 Unauthorized"}</title>
+            <title>{i18n.t("request_error.not_found_title")}</title>
         </head>
         <body align="center">
             <div role="main" align="center">
-                <h1>{"404: Not Found"}</h1>
-                <p>{"The requested resource could not be found."}</p>
+                <h1>{i18n.t("request_error.not_found_heading")}</h1>
+                <p>{i18n.t("request_error.not_found_desc")}</p>
                 // <p>{"uri: "}<b></b></p>
                 <hr/>
             </div>
             <div role="contentinfo" align="center">
-                <span style="margin-top:15px;"><a href="/login">{"Go to login"}</a></span>
+                <span style="margin-top:15px;"><a href="/login">{i18n.t("common.go_to_login")}</a></span>
             </div>
         </body>
         </html>
             },
         _ => html! {
-        <html lang="en">
+        <html lang={lang}>
         <head>
             <meta charset="utf-8"/>
-            <title>{"request api error"}</title>
+            <title>{i18n.t("request_error.api_error")}</title>
         </head>
         <body align="center">
             <div role="main" align="center">
-                <h1>{"request api error"}</h1>
+                <h1>{i18n.t("request_error.api_error")}</h1>
                 <hr/>
             </div>
             <div role="contentinfo" align="center">
-                <span style="margin-top:15px;"><a href="/login">{"Go to login"}</a></span>
+                <span style="margin-top:15px;"><a href="/login">{i18n.t("common.go_to_login")}</a></span>
             </div>
         </body>
         </html>
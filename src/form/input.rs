@@ -0,0 +1,182 @@
+use super::use_form::UseFormHandle;
+use yew::prelude::*;
+use yew::Properties;
+
+#[derive(Properties, PartialEq)]
+pub struct FormInputProps {
+    pub form: UseFormHandle,
+    pub name: &'static str,
+    #[prop_or_default]
+    pub label: Option<AttrValue>,
+    #[prop_or(AttrValue::from("text"))]
+    pub r#type: AttrValue,
+    #[prop_or_default]
+    pub placeholder: AttrValue,
+    // fontawesome class, e.g. "fa-solid fa-envelope"
+    #[prop_or_default]
+    pub icon: Option<AttrValue>,
+    #[prop_or(AttrValue::from("is-medium is-rounded"))]
+    pub modifier: AttrValue,
+    #[prop_or_default]
+    pub disabled: bool,
+    #[prop_or_default]
+    pub autofocus: bool,
+    #[prop_or_default]
+    pub onenter: Callback<()>,
+    // extra message shown under the input, e.g. a server hint
+    #[prop_or_default]
+    pub hint: Option<AttrValue>,
+    // rendered next to the input, e.g. a code button
+    #[prop_or_default]
+    pub children: Html,
+}
+
+#[function_component(FormInput)]
+pub fn form_input(props: &FormInputProps) -> Html {
+    let input_ref = use_node_ref();
+    {
+        let input_ref = input_ref.clone();
+        let autofocus = props.autofocus;
+        use_effect_with((), move |_| {
+            if autofocus {
+                if let Some(input) = input_ref.cast::<web_sys::HtmlInputElement>() {
+                    input.focus().unwrap_or_default();
+                }
+            }
+        });
+    }
+
+    let status = props.form.status(props.name);
+    let onkeydown = {
+        let onenter = props.onenter.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            if e.key_code() == 13 {
+                onenter.emit(());
+            }
+        })
+    };
+    let class = format!("input {} {}", status.class(), props.modifier);
+    let control_class = if props.icon.is_some() {
+        "control has-icons-left"
+    } else {
+        "control"
+    };
+    let control = html! {
+        <div class={control_class}>
+            <input ref={input_ref} class={class} type={props.r#type.clone()} value={props.form.value(props.name)} disabled={props.disabled}
+             placeholder={props.placeholder.clone()} oninput={props.form.oninput(props.name)} onkeydown={onkeydown}/>
+            if let Some(icon) = &props.icon {
+                <span class="icon is-small is-left">
+                <i class={icon.clone()}></i>
+                </span>
+            }
+        </div>
+    };
+    let message = match status.message() {
+        m if m.is_empty() => props
+            .hint
+            .clone()
+            .map(|x| x.to_string())
+            .unwrap_or_default(),
+        m => m,
+    };
+
+    html! {
+        <div class="field">
+            if let Some(label) = &props.label {
+                <label class="label">{label.clone()}</label>
+            }
+            if props.children == Html::default() {
+                {control}
+            } else {
+                <div class="field is-horizontal">
+                    <div class="field-body">
+                        <div class="field is-expanded">
+                            {control}
+                        </div>
+                        <div class="control">
+                            {props.children.clone()}
+                        </div>
+                    </div>
+                </div>
+            }
+            <p class="help is-danger">
+                {message}
+            </p>
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct SubmitButtonProps {
+    pub text: AttrValue,
+    pub onclick: Callback<()>,
+    #[prop_or_default]
+    pub loading: bool,
+    // form level error, e.g. from the server
+    #[prop_or_default]
+    pub error: String,
+}
+
+#[function_component(SubmitButton)]
+pub fn submit_button(props: &SubmitButtonProps) -> Html {
+    let onclick = {
+        let onclick = props.onclick.clone();
+        Callback::from(move |_| onclick.emit(()))
+    };
+    let class = if props.loading {
+        "button is-block is-fullwidth is-primary is-medium is-rounded is-loading"
+    } else {
+        "button is-block is-fullwidth is-primary is-medium is-rounded"
+    };
+    html! {
+        <div class="field">
+            <p class="control">
+                <button class={class} disabled={props.loading} onclick={onclick}>
+                {props.text.clone()}
+                </button>
+            </p>
+            <p class="help is-danger">
+                {props.error.clone()}
+            </p>
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct CodeButtonProps {
+    pub onclick: Callback<()>,
+    #[prop_or(AttrValue::from("Generate Code"))]
+    pub text: AttrValue,
+    #[prop_or_default]
+    pub disabled: bool,
+    #[prop_or_default]
+    pub loading: bool,
+    // seconds until another code can be sent
+    #[prop_or_default]
+    pub countdown: usize,
+}
+
+#[function_component(CodeButton)]
+pub fn code_button(props: &CodeButtonProps) -> Html {
+    let onclick = {
+        let onclick = props.onclick.clone();
+        Callback::from(move |_| onclick.emit(()))
+    };
+    let class = if props.loading {
+        "button is-block is-fullwidth is-primary is-medium is-rounded is-loading"
+    } else {
+        "button is-block is-fullwidth is-primary is-medium is-rounded"
+    };
+    html! {
+        <button disabled={props.disabled || props.countdown > 0} class={class} onclick={onclick}>
+        {
+            if props.countdown > 0 {
+                props.countdown.to_string()
+            } else {
+                props.text.to_string()
+            }
+        }
+        </button>
+    }
+}
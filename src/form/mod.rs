@@ -0,0 +1,64 @@
+pub mod input;
+pub mod use_countdown;
+pub mod use_form;
+pub mod verification_code;
+
+use crate::util::api_error::ApiError;
+use crate::util::request::RequestOptions;
+use evolve_axum_cli::apis::user_api;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub enum ValidStatus {
+    Valid,
+    InValid(String),
+    #[default]
+    None,
+}
+
+impl ValidStatus {
+    pub fn class(&self) -> &'static str {
+        match self {
+            ValidStatus::Valid => "is-success",
+            ValidStatus::InValid(_) => "is-danger",
+            ValidStatus::None => "is-info",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            ValidStatus::InValid(e) => e.clone(),
+            _ => String::default(),
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        *self == ValidStatus::Valid
+    }
+}
+
+// error returned from a submit handler, mapped onto a field or the whole form
+#[derive(Debug, Clone)]
+pub enum FormError {
+    Field(&'static str, String),
+    Form(String),
+    // field errors from the server land on matching fields, the rest on the form
+    Api(ApiError),
+}
+
+impl From<ApiError> for FormError {
+    fn from(err: ApiError) -> Self {
+        FormError::Api(err)
+    }
+}
+
+pub async fn validate_exist_email(email: String) -> Result<(), String> {
+    RequestOptions::new()
+        .retries(2)
+        .cli("user_api::validate_exist_email", false, |config| {
+            let email = email.clone();
+            async move { user_api::validate_exist_email(&config, &email).await }
+        })
+        .await
+        .map(|_| ())
+        .map_err(|err| err.user_message())
+}
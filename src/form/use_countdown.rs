@@ -0,0 +1,61 @@
+use gloo::timers::callback::Interval;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use yew::prelude::*;
+
+#[derive(Clone)]
+pub struct UseCountdownHandle {
+    remaining: Rc<Cell<usize>>,
+    interval: Rc<RefCell<Option<Interval>>>,
+    force_update: UseForceUpdateHandle,
+}
+
+impl UseCountdownHandle {
+    pub fn remaining(&self) -> usize {
+        self.remaining.get()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.remaining.get() > 0
+    }
+
+    pub fn start(&self, secs: usize) {
+        self.remaining.set(secs);
+        let remaining = self.remaining.clone();
+        let interval = self.interval.clone();
+        let force_update = self.force_update.clone();
+        *self.interval.borrow_mut() = if secs > 0 {
+            Some(Interval::new(1000, move || {
+                let secs = remaining.get().saturating_sub(1);
+                remaining.set(secs);
+                if secs == 0 {
+                    interval.borrow_mut().take();
+                }
+                force_update.force_update();
+            }))
+        } else {
+            None
+        };
+        self.force_update.force_update();
+    }
+}
+
+#[hook]
+pub fn use_countdown() -> UseCountdownHandle {
+    let force_update = use_force_update();
+    let remaining = use_memo((), |_| Cell::new(0));
+    let interval: Rc<RefCell<Option<Interval>>> = use_mut_ref(|| None);
+    {
+        let interval = interval.clone();
+        use_effect_with((), move |_| {
+            move || {
+                interval.borrow_mut().take();
+            }
+        });
+    }
+    UseCountdownHandle {
+        remaining,
+        interval,
+        force_update,
+    }
+}
@@ -0,0 +1,420 @@
+use super::{FormError, ValidStatus};
+use crate::util::common::BasicResult;
+use crate::util::validation::{FieldValues, Validator};
+use futures::future::LocalBoxFuture;
+use futures::FutureExt;
+use gloo::timers::callback::Timeout;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::Future;
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+const DEFAULT_DEBOUNCE_MS: u32 = 300;
+
+type SyncValidator = Rc<dyn Fn(&str, &FormValues) -> BasicResult<()>>;
+type AsyncValidator = Rc<dyn Fn(String) -> LocalBoxFuture<'static, Result<(), String>>>;
+
+#[derive(Clone, Default, Debug)]
+pub struct FormValues(HashMap<&'static str, String>);
+
+impl FormValues {
+    pub fn get(&self, name: &str) -> &str {
+        self.0.get(name).map(|x| x.as_str()).unwrap_or_default()
+    }
+}
+
+impl FieldValues for FormValues {
+    fn field(&self, name: &str) -> &str {
+        self.get(name)
+    }
+}
+
+pub struct FieldConfig {
+    name: &'static str,
+    validators: Vec<SyncValidator>,
+    async_validator: Option<AsyncValidator>,
+    debounce: u32,
+}
+
+impl FieldConfig {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            validators: vec![],
+            async_validator: None,
+            debounce: DEFAULT_DEBOUNCE_MS,
+        }
+    }
+
+    pub fn validate<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str, &FormValues) -> BasicResult<()> + 'static,
+    {
+        self.validators.push(Rc::new(f));
+        self
+    }
+
+    pub fn rule<V>(self, rule: V) -> Self
+    where
+        V: Validator + 'static,
+    {
+        self.validate(move |v, values| Ok(rule.validate(v, values)?))
+    }
+
+    // runs after the sync validators pass, debounced while typing
+    pub fn validate_async<F, Fut>(mut self, f: F) -> Self
+    where
+        F: Fn(String) -> Fut + 'static,
+        Fut: Future<Output = Result<(), String>> + 'static,
+    {
+        self.async_validator = Some(Rc::new(move |v| f(v).boxed_local()));
+        self
+    }
+
+    pub fn debounce(mut self, ms: u32) -> Self {
+        self.debounce = ms;
+        self
+    }
+}
+
+struct Field {
+    config: FieldConfig,
+    value: String,
+    status: ValidStatus,
+    // bumped on every change so stale async results are dropped
+    version: u64,
+    timer: Option<Timeout>,
+}
+
+struct FormState {
+    fields: Vec<Field>,
+    submitting: bool,
+    error: String,
+    version: u64,
+}
+
+impl FormState {
+    fn field(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|x| x.config.name == name)
+    }
+
+    fn field_mut(&mut self, name: &str) -> Option<&mut Field> {
+        self.fields.iter_mut().find(|x| x.config.name == name)
+    }
+
+    fn values(&self) -> FormValues {
+        FormValues(
+            self.fields
+                .iter()
+                .map(|x| (x.config.name, x.value.clone()))
+                .collect(),
+        )
+    }
+
+    fn check_sync(&self, name: &str) -> Result<(), String> {
+        let values = self.values();
+        if let Some(field) = self.field(name) {
+            for validator in field.config.validators.iter() {
+                validator(&field.value, &values).map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct UseFormHandle {
+    state: Rc<RefCell<FormState>>,
+    force_update: UseForceUpdateHandle,
+    version: u64,
+}
+
+impl PartialEq for UseFormHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.state, &other.state) && self.version == other.version
+    }
+}
+
+impl UseFormHandle {
+    fn refresh(&self) {
+        self.state.borrow_mut().version += 1;
+        self.force_update.force_update();
+    }
+
+    pub fn value(&self, name: &str) -> String {
+        self.state
+            .borrow()
+            .field(name)
+            .map(|x| x.value.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn values(&self) -> FormValues {
+        self.state.borrow().values()
+    }
+
+    pub fn status(&self, name: &str) -> ValidStatus {
+        self.state
+            .borrow()
+            .field(name)
+            .map(|x| x.status.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn submitting(&self) -> bool {
+        self.state.borrow().submitting
+    }
+
+    pub fn error(&self) -> String {
+        self.state.borrow().error.clone()
+    }
+
+    pub fn set_error(&self, msg: &str) {
+        self.state.borrow_mut().error = msg.to_string();
+        self.refresh();
+    }
+
+    pub fn set_field_error(&self, name: &str, msg: &str) {
+        if let Some(field) = self.state.borrow_mut().field_mut(name) {
+            field.status = ValidStatus::InValid(msg.to_string());
+        }
+        self.refresh();
+    }
+
+    pub fn reset(&self) {
+        {
+            let mut state = self.state.borrow_mut();
+            for field in state.fields.iter_mut() {
+                field.value = String::default();
+                field.status = ValidStatus::None;
+                field.version += 1;
+                field.timer = None;
+            }
+            state.error = String::default();
+        }
+        self.refresh();
+    }
+
+    pub fn set_value(&self, name: &str, value: String) {
+        let schedule = {
+            let mut state = self.state.borrow_mut();
+            let field = match state.field_mut(name) {
+                Some(v) => v,
+                None => return,
+            };
+            field.value = value;
+            field.version += 1;
+            field.timer = None;
+            let version = field.version;
+            let debounce = field.config.debounce;
+            let has_async = field.config.async_validator.is_some();
+
+            let res = state.check_sync(name);
+            let field = state.field_mut(name).unwrap();
+            match res {
+                Err(e) => {
+                    field.status = ValidStatus::InValid(e);
+                    None
+                }
+                Ok(_) if has_async => Some((version, debounce)),
+                Ok(_) => {
+                    field.status = ValidStatus::Valid;
+                    None
+                }
+            }
+        };
+
+        // fields already touched may depend on this one, e.g. password confirm
+        {
+            let mut state = self.state.borrow_mut();
+            let others = state
+                .fields
+                .iter()
+                .filter(|x| x.config.name != name && x.status != ValidStatus::None)
+                .filter(|x| x.config.async_validator.is_none())
+                .map(|x| x.config.name)
+                .collect::<Vec<_>>();
+            for other in others {
+                let status = match state.check_sync(other) {
+                    Ok(_) => ValidStatus::Valid,
+                    Err(e) => ValidStatus::InValid(e),
+                };
+                state.field_mut(other).unwrap().status = status;
+            }
+        }
+
+        if let Some((version, debounce)) = schedule {
+            let handle = self.clone();
+            let field_name = name.to_string();
+            let timer = Timeout::new(debounce, move || {
+                spawn_local(async move {
+                    handle.run_async(&field_name, version).await;
+                });
+            });
+            if let Some(field) = self.state.borrow_mut().field_mut(name) {
+                field.timer = Some(timer);
+            }
+        }
+        self.refresh();
+    }
+
+    async fn run_async(&self, name: &str, version: u64) -> bool {
+        let (validator, value) = match self.state.borrow().field(name) {
+            Some(field) => match &field.config.async_validator {
+                Some(v) => (v.clone(), field.value.clone()),
+                None => return true,
+            },
+            None => return true,
+        };
+        let res = validator(value).await;
+        let valid = res.is_ok();
+        if let Some(field) = self.state.borrow_mut().field_mut(name) {
+            if field.version != version {
+                return false;
+            }
+            field.status = match res {
+                Ok(_) => ValidStatus::Valid,
+                Err(e) => ValidStatus::InValid(e),
+            };
+        }
+        self.refresh();
+        valid
+    }
+
+    // validates one field right away, skipping the debounce
+    pub async fn validate_field(&self, name: &str) -> bool {
+        let version = {
+            let mut state = self.state.borrow_mut();
+            let res = state.check_sync(name);
+            let field = match state.field_mut(name) {
+                Some(v) => v,
+                None => return true,
+            };
+            field.timer = None;
+            match res {
+                Err(e) => {
+                    field.status = ValidStatus::InValid(e);
+                    None
+                }
+                Ok(_) if field.config.async_validator.is_some() => Some(field.version),
+                Ok(_) => {
+                    field.status = ValidStatus::Valid;
+                    None
+                }
+            }
+        };
+        match version {
+            Some(version) => self.run_async(name, version).await,
+            None => {
+                self.refresh();
+                self.status(name).is_valid()
+            }
+        }
+    }
+
+    pub async fn validate(&self) -> bool {
+        let names = self
+            .state
+            .borrow()
+            .fields
+            .iter()
+            .map(|x| x.config.name)
+            .collect::<Vec<_>>();
+        let mut valid = true;
+        for name in names {
+            valid &= self.validate_field(name).await;
+        }
+        valid
+    }
+
+    pub fn oninput(&self, name: &'static str) -> Callback<InputEvent> {
+        let handle = self.clone();
+        Callback::from(move |e: InputEvent| {
+            let el: HtmlInputElement = e.target_unchecked_into();
+            handle.set_value(name, el.value());
+        })
+    }
+
+    // validates every field, then hands the values to `f`; its error is mapped back onto the form
+    pub fn onsubmit<F, Fut>(&self, f: F) -> Callback<()>
+    where
+        F: Fn(FormValues) -> Fut + 'static,
+        Fut: Future<Output = Result<(), FormError>> + 'static,
+    {
+        let handle = self.clone();
+        let f = Rc::new(f);
+        Callback::from(move |_| {
+            if handle.submitting() {
+                return;
+            }
+            {
+                let mut state = handle.state.borrow_mut();
+                state.submitting = true;
+                state.error = String::default();
+            }
+            handle.refresh();
+            let handle = handle.clone();
+            let f = f.clone();
+            spawn_local(async move {
+                if handle.validate().await {
+                    match f(handle.values()).await {
+                        Ok(_) => {}
+                        Err(FormError::Field(name, msg)) => {
+                            if let Some(field) = handle.state.borrow_mut().field_mut(name) {
+                                field.status = ValidStatus::InValid(msg);
+                            }
+                        }
+                        Err(FormError::Form(msg)) => handle.state.borrow_mut().error = msg,
+                        Err(FormError::Api(err)) => {
+                            let mut state = handle.state.borrow_mut();
+                            let mut matched = false;
+                            for e in err.field_errors.iter() {
+                                if let Some(field) = state.field_mut(&e.field) {
+                                    field.status = ValidStatus::InValid(e.message.clone());
+                                    matched = true;
+                                }
+                            }
+                            if !matched {
+                                state.error = err.user_message();
+                            }
+                        }
+                    }
+                }
+                handle.state.borrow_mut().submitting = false;
+                handle.refresh();
+            });
+        })
+    }
+}
+
+#[hook]
+pub fn use_form<F>(init: F) -> UseFormHandle
+where
+    F: FnOnce() -> Vec<FieldConfig>,
+{
+    let force_update = use_force_update();
+    let state = use_mut_ref(move || FormState {
+        fields: init()
+            .into_iter()
+            .map(|config| Field {
+                config,
+                value: String::default(),
+                status: ValidStatus::None,
+                version: 0,
+                timer: None,
+            })
+            .collect(),
+        submitting: false,
+        error: String::default(),
+        version: 0,
+    });
+    let version = state.borrow().version;
+    UseFormHandle {
+        state,
+        force_update,
+        version,
+    }
+}
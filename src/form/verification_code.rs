@@ -0,0 +1,176 @@
+use super::input::{CodeButton, FormInput};
+use super::use_countdown::use_countdown;
+use super::use_form::UseFormHandle;
+use crate::i18n::use_i18n::use_i18n;
+use crate::storage::keys;
+use futures::future::LocalBoxFuture;
+use futures::FutureExt;
+use std::future::Future;
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+use yew::Properties;
+
+pub enum SendCodeOutcome {
+    // seconds until another code may be requested, as told by the server
+    Sent(usize),
+    // a code is still valid, e.g. status 452; the input unlocks and the message is shown
+    Hint(String),
+    // nothing was sent, e.g. the email failed validation
+    Cancelled,
+}
+
+#[derive(Clone)]
+pub struct CodeSender(Rc<dyn Fn() -> LocalBoxFuture<'static, Result<SendCodeOutcome, String>>>);
+
+impl CodeSender {
+    pub fn new<F, Fut>(f: F) -> Self
+    where
+        F: Fn() -> Fut + 'static,
+        Fut: Future<Output = Result<SendCodeOutcome, String>> + 'static,
+    {
+        Self(Rc::new(move || f().boxed_local()))
+    }
+}
+
+impl PartialEq for CodeSender {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct VerificationCodeInputProps {
+    pub form: UseFormHandle,
+    pub name: &'static str,
+    // requests a code, e.g. by email; left out when the code comes from an authenticator app,
+    // then the input is always open and there is no button
+    #[prop_or_default]
+    pub sender: Option<CodeSender>,
+    // names the storage entry the cooldown expiry is kept in, so it survives reloads
+    #[prop_or_default]
+    pub cooldown_key: AttrValue,
+    #[prop_or_default]
+    pub label: Option<AttrValue>,
+    #[prop_or(AttrValue::from("123456"))]
+    pub placeholder: AttrValue,
+    #[prop_or(AttrValue::from("is-medium is-rounded"))]
+    pub modifier: AttrValue,
+    #[prop_or_default]
+    pub disabled: bool,
+    #[prop_or_default]
+    pub autofocus: bool,
+    #[prop_or_default]
+    pub onenter: Callback<()>,
+}
+
+fn remaining_cooldown(key: &str) -> usize {
+    keys::CODE_COOLDOWN
+        .child(key)
+        .get()
+        .map(|expiry| (expiry - chrono::Utc::now().timestamp()).max(0) as usize)
+        .unwrap_or_default()
+}
+
+#[function_component(VerificationCodeInput)]
+pub fn verification_code_input(props: &VerificationCodeInputProps) -> Html {
+    let i18n = use_i18n();
+    let force_update = use_force_update();
+    let countdown = use_countdown();
+    let sending = use_mut_ref(|| false);
+    let hint: std::rc::Rc<std::cell::RefCell<Option<String>>> = use_mut_ref(|| None);
+    // the input unlocks once a code went out, including one sent before a reload
+    let sent = use_mut_ref(|| false);
+
+    {
+        let countdown = countdown.clone();
+        let sent = sent.clone();
+        let cooldown_key = props.cooldown_key.clone();
+        let sends = props.sender.is_some();
+        use_effect_with((), move |_| {
+            if !sends {
+                return;
+            }
+            let secs = remaining_cooldown(&cooldown_key);
+            if secs > 0 {
+                *sent.borrow_mut() = true;
+                countdown.start(secs);
+            }
+        });
+    }
+
+    let send = {
+        let form = props.form.clone();
+        let name = props.name;
+        let sender = props.sender.clone();
+        let cooldown_key = props.cooldown_key.clone();
+        let countdown = countdown.clone();
+        let sending = sending.clone();
+        let hint = hint.clone();
+        let sent = sent.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            if *sending.borrow() || countdown.is_running() {
+                return;
+            }
+            *sending.borrow_mut() = true;
+            *hint.borrow_mut() = None;
+            force_update.force_update();
+
+            let sender = match &sender {
+                Some(v) => v.clone(),
+                None => return,
+            };
+            let form = form.clone();
+            let cooldown_key = cooldown_key.clone();
+            let countdown = countdown.clone();
+            let sending = sending.clone();
+            let hint = hint.clone();
+            let sent = sent.clone();
+            let force_update = force_update.clone();
+            spawn_local(async move {
+                match (sender.0)().await {
+                    Ok(SendCodeOutcome::Sent(secs)) => {
+                        *sent.borrow_mut() = true;
+                        keys::CODE_COOLDOWN
+                            .child(&cooldown_key)
+                            .set(&(chrono::Utc::now().timestamp() + secs as i64));
+                        countdown.start(secs);
+                    }
+                    Ok(SendCodeOutcome::Hint(msg)) => {
+                        *sent.borrow_mut() = true;
+                        *hint.borrow_mut() = Some(msg);
+                    }
+                    Ok(SendCodeOutcome::Cancelled) => {}
+                    Err(e) => form.set_field_error(name, &e),
+                }
+                *sending.borrow_mut() = false;
+                force_update.force_update();
+            });
+        })
+    };
+
+    let text = i18n.t(if *sent.borrow() {
+        "code.resend"
+    } else {
+        "code.generate"
+    });
+    let label = props
+        .label
+        .clone()
+        .unwrap_or_else(|| AttrValue::from(i18n.t("code.label")));
+    if props.sender.is_none() {
+        return html! {
+            <FormInput form={props.form.clone()} name={props.name} label={label} icon="fa-solid fa-barcode"
+                placeholder={props.placeholder.clone()} modifier={props.modifier.clone()} disabled={props.disabled}
+                autofocus={props.autofocus} onenter={props.onenter.clone()}/>
+        };
+    }
+    html! {
+        <FormInput form={props.form.clone()} name={props.name} label={label} icon="fa-solid fa-barcode"
+            placeholder={props.placeholder.clone()} modifier={props.modifier.clone()} disabled={!*sent.borrow()}
+            autofocus={props.autofocus} onenter={props.onenter.clone()} hint={hint.borrow().clone().map(AttrValue::from)}>
+            <CodeButton onclick={send} text={text} disabled={props.disabled} loading={*sending.borrow()} countdown={countdown.remaining()}/>
+        </FormInput>
+    }
+}
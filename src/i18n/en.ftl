@@ -0,0 +1,247 @@
+# common
+common.confirm = Confirm
+common.cancel = Cancel
+common.return_to_login = Return to login
+common.go_to_login = Go to login
+common.please_select_record = please select a record
+common.undo = Undo
+common.retry = Retry
+common.back = Back
+
+# message titles
+message.success = Success
+message.warning = Warning
+message.info = Info
+message.error = Error
+
+# header
+header.language = Language
+header.unnamed = unnamed
+header.my_account = My account
+header.logout = Logout
+header.theme = Theme
+header.presence = Status
+
+# navigation
+nav.modules = Modules
+nav.welcome = Welcome
+nav.user = User
+nav.user_detail = user #{ $id }
+nav.role = Role
+nav.audit_log = Audit Log
+nav.my_account = My Account
+nav.two_factor = Two-Factor Authentication
+nav.section.user_management = User Management
+nav.section.system = System
+
+# menu
+menu.filter = Filter menu
+menu.collapse = Collapse menu
+menu.expand = Expand menu
+
+# command palette
+palette.placeholder = Search pages, users and actions
+palette.no_results = No results
+palette.group.navigation = Pages
+palette.group.users = Users
+palette.group.actions = Actions
+palette.action.toggle_theme = Switch theme
+palette.action.open_chat = Open chat
+palette.action.logout = Logout
+palette.action.copy_error_report = Copy error report
+palette.error_count = { $count } errors logged
+
+# shortcuts
+shortcut.help_title = Keyboard shortcuts
+shortcut.open_palette = Open the command palette
+shortcut.show_help = Show keyboard shortcuts
+shortcut.palette_move = Move between results
+shortcut.palette_run = Run the selected result
+shortcut.close = Close
+
+# theme
+theme.light = Light
+theme.dark = Dark
+theme.system = System
+
+# presence
+presence.online = Online
+presence.away = Away
+presence.offline = Offline
+presence.typing = Typing: { $names }
+
+# login
+login.login = Login
+login.forgot_password = Forgot Password?
+login.create_account = Create an Account
+login.authentication_code = Authentication Code:
+login.recovery_code = Recovery Code:
+login.verify = Verify
+login.use_authenticator = Use authenticator app
+login.use_recovery_code = Use a recovery code
+
+# two-factor authentication
+two_factor.intro = Protect your account with a time-based one-time password (TOTP) from an authenticator app.
+two_factor.enable = Enable
+two_factor.scan = Scan the QR code with your authenticator app, then type in the 6-digit code it shows.
+two_factor.manual_key = Can't scan it? Enter this key manually:
+two_factor.save_recovery_codes = Save these recovery codes somewhere safe. Each code can be used once to log in when you lose access to your authenticator app, and they will not be shown again.
+two_factor.done = Done
+two_factor.enabled = enabled
+two_factor.manage = Type in a code from your authenticator app to manage two-factor authentication.
+two_factor.regenerate = Regenerate recovery codes
+two_factor.disable = Disable
+two_factor.disabled = two-factor authentication has been disabled
+
+# register and forget password
+register.email = Email:
+register.password = Password:
+register.password_confirm = Re-enter Password:
+register.register = Register
+forget_pwd.new_password = New Password:
+forget_pwd.new_password_confirm = Re-enter New Password:
+forget_pwd.reset_password = Reset Password
+code.label = Code:
+code.generate = Generate Code
+code.resend = Resend
+
+# user list
+user_list.search = Search
+user_list.detail = Detail
+user_list.edit = Edit
+user_list.delete = Delete
+user_list.delete_confirm = Deleted users <b>can not</b> be recovered!!!<br/> are you sure you want to delete it?
+user_list.deleted = { $name } was deleted
+user.type = Type
+user.email = Email
+user.name = Name
+user.mobile = Mobile
+user.laston = Laston
+user.created_at = Created_at
+user.updated_at = Updated_at
+user.status = Status
+
+# user detail
+user_detail.edit_title = User Edit
+user_detail.reset_pwd = Reset Password
+user_detail.reset_pwd_confirm = The user will be signed out and receive a reset password email.<br/> are you sure you want to continue?
+user_detail.reset_pwd_sent = a reset password email has been sent
+user_detail.updated = user updated
+user_detail.deleted_elsewhere = this user has been deleted by someone else
+user_detail.roles = Roles
+user_detail.sessions = Active Sessions
+user_detail.activities = Recent Activities
+
+# my account
+account.profile = Profile
+account.save = Save changes
+account.profile_saved = profile saved
+account.change_password = Change Password
+account.current_password = Current Password
+account.new_password = New Password
+account.new_password_confirm = Re-enter New Password
+account.same_password = new password must be different from the current one
+account.password_changed = password changed
+account.two_factor_desc = Require a code from an authenticator app in addition to your password when logging in.
+account.manage = Manage
+account.sessions = My Sessions
+
+# sessions
+session.device = Device
+session.ip = IP
+session.last_seen = Last seen
+session.unknown_device = unknown
+session.current = current
+session.empty = no active sessions
+session.sign_out = Sign out this session
+session.sign_out_others = Sign out all other sessions
+session.sign_out_all = Sign out all sessions
+session.signed_out = signed out
+session.confirm_current = This is the session you are using now, you will be logged out.<br/> are you sure you want to continue?
+session.confirm_one = Sign out the session on <b>{ $device }</b>?
+session.confirm_others = All sessions except the current one will be signed out.<br/> are you sure you want to continue?
+session.confirm_all = All sessions will be signed out.<br/> are you sure you want to continue?
+
+# audit log
+audit_log.actor = Actor
+audit_log.all_entities = All entities
+audit_log.all_actions = All actions
+audit_log.from = From
+audit_log.to = To
+audit_log.time = Time
+audit_log.action = Action
+audit_log.entity = Entity
+audit_log.changes = Changes
+audit_log.field_count = { $count } field(s)
+audit_log.field = Field
+audit_log.before = Before
+audit_log.after = After
+audit_log.entity.user = User
+audit_log.entity.role = Role
+audit_log.action.create = Create
+audit_log.action.update = Update
+audit_log.action.delete = Delete
+
+# request error
+request_error.unauthorized_title = 401 Unauthorized
+request_error.unauthorized_heading = 401: Unauthorized
+request_error.unauthorized_desc = The request requires user authentication.
+request_error.not_found_title = 404 Not Found
+request_error.not_found_heading = 404: Not Found
+request_error.not_found_desc = The requested resource could not be found.
+request_error.forbidden_title = 403 Forbidden
+request_error.forbidden_heading = 403: Forbidden
+request_error.forbidden_desc = You do not have permission to view this page.
+request_error.api_error = request api error
+
+# crash
+crash.title = Something went wrong
+crash.desc = The application hit an unexpected error. Reloading usually fixes it; if it keeps happening, please copy the details below into a bug report.
+crash.reload = Reload
+crash.copy_details = Copy details
+
+# api error
+api_error.network = Unable to reach the server, please check your connection
+api_error.bad_request = The request was invalid
+api_error.unauthorized = Your session has expired, please log in again
+api_error.forbidden = You do not have permission to do this
+api_error.not_found = The requested resource could not be found
+api_error.timeout = The server took too long to respond
+api_error.too_many_requests = Too many requests, please try again later
+api_error.server = Something went wrong on the server
+api_error.cancelled = The request was cancelled
+api_error.unknown = Unexpected error
+api_error.invalid_response = Unexpected response from the server: { $error }
+api_error.with_request_id = { $message } (request id: { $id })
+api_error.rolled_back = { $message }; the change was undone
+
+# validation
+validation.required = please type in { $field }
+validation.length = { $field } length must be between { $min } and { $max }
+validation.pattern = invalid { $field }
+validation.mismatch = { $field } must as same as { $other }
+validation.email_invalid = invalid email
+validation.code_invalid = invalid code: length=6 and 0-9 is demanded
+validation.pwd_confirm_mismatch = confirm password must as same as password
+validation.password_policy = invalid password, unmet: { $unmet }
+field.email = email
+field.password = password
+field.confirm_password = confirm password
+field.current_password = current password
+field.code = code
+field.recovery_code = recovery code
+
+# password policy
+password.min_length = at least { $n } characters
+password.max_length = at most { $n } characters
+password.lowercase = a lowercase letter
+password.uppercase = an uppercase letter
+password.digit = a digit
+password.symbol = a symbol
+password.no_symbols = letters and digits only
+password.not_common = not a commonly used password
+password.very_weak = Very weak
+password.weak = Weak
+password.fair = Fair
+password.good = Good
+password.strong = Strong
@@ -0,0 +1,140 @@
+pub mod use_i18n;
+
+use crate::storage::keys;
+use lazy_static::lazy_static;
+use std::cell::Cell;
+use std::collections::HashMap;
+
+lazy_static! {
+    // catalogs are compiled in, so switching never waits on the network
+    static ref CATALOGS: HashMap<&'static str, HashMap<&'static str, &'static str>> = {
+        let mut m = HashMap::new();
+        m.insert(Locale::En.code(), parse_catalog(include_str!("en.ftl")));
+        m.insert(Locale::Zh.code(), parse_catalog(include_str!("zh.ftl")));
+        m
+    };
+}
+
+thread_local! {
+    static CURRENT: Cell<Locale> = const { Cell::new(Locale::En) };
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Locale {
+    #[default]
+    En,
+    Zh,
+}
+
+impl Locale {
+    pub fn all() -> [Locale; 2] {
+        [Locale::En, Locale::Zh]
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Zh => "zh",
+        }
+    }
+
+    // shown in the switcher, always in its own language
+    pub fn name(&self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::Zh => "中文",
+        }
+    }
+
+    // accepts tags like "zh-CN" or "en_US"
+    pub fn from_code(code: &str) -> Option<Locale> {
+        let lang = code.split(['-', '_']).next().unwrap_or_default();
+        Locale::all()
+            .into_iter()
+            .find(|x| x.code().eq_ignore_ascii_case(lang))
+    }
+
+    fn datetime_format(&self) -> &'static str {
+        match self {
+            Locale::En => "%b %-d, %Y %H:%M:%S",
+            Locale::Zh => "%Y年%-m月%-d日 %H:%M:%S",
+        }
+    }
+}
+
+// `key = value` lines, `#` comments, `{ $name }` placeholders; a subset of fluent
+fn parse_catalog(src: &'static str) -> HashMap<&'static str, &'static str> {
+    src.lines()
+        .map(|x| x.trim())
+        .filter(|x| !x.is_empty() && !x.starts_with('#'))
+        .filter_map(|x| x.split_once('='))
+        .map(|(k, v)| (k.trim(), v.trim()))
+        .collect()
+}
+
+pub fn current() -> Locale {
+    CURRENT.with(|x| x.get())
+}
+
+// picks the saved preference, falling back to the browser language
+pub fn init() {
+    let locale = keys::LOCALE
+        .get()
+        .and_then(|x| Locale::from_code(&x))
+        .or_else(|| {
+            web_sys::window()
+                .and_then(|x| x.navigator().language())
+                .and_then(|x| Locale::from_code(&x))
+        })
+        .unwrap_or_default();
+    apply(locale);
+}
+
+pub fn set_current(locale: Locale) {
+    keys::LOCALE.set(&locale.code().to_string());
+    apply(locale);
+}
+
+fn apply(locale: Locale) {
+    CURRENT.with(|x| x.set(locale));
+    if let Some(el) = web_sys::window()
+        .and_then(|x| x.document())
+        .and_then(|x| x.document_element())
+    {
+        el.set_attribute("lang", locale.code()).unwrap_or_default();
+    }
+}
+
+pub fn translate(locale: Locale, key: &str, args: &[(&str, &str)]) -> String {
+    let msg = CATALOGS
+        .get(locale.code())
+        .and_then(|x| x.get(key))
+        .or_else(|| CATALOGS.get(Locale::En.code()).and_then(|x| x.get(key)))
+        .map(|x| x.to_string())
+        .unwrap_or_else(|| key.to_string());
+    args.iter().fold(msg, |msg, (name, value)| {
+        msg.replace(&format!("{{ ${} }}", name), value)
+    })
+}
+
+pub fn t(key: &str) -> String {
+    translate(current(), key, &[])
+}
+
+pub fn t_args(key: &str, args: &[(&str, &str)]) -> String {
+    translate(current(), key, args)
+}
+
+pub fn format_datetime(ts: i64) -> String {
+    format_datetime_in(current(), ts)
+}
+
+pub fn format_datetime_in(locale: Locale, ts: i64) -> String {
+    chrono::DateTime::from_timestamp(ts, 0)
+        .map(|x| {
+            x.with_timezone(&chrono::Local)
+                .format(locale.datetime_format())
+                .to_string()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,68 @@
+use super::Locale;
+use yew::prelude::*;
+
+#[derive(Clone, PartialEq)]
+pub struct I18n {
+    locale: Locale,
+    set_locale: Callback<Locale>,
+}
+
+impl I18n {
+    pub fn locale(&self) -> Locale {
+        self.locale
+    }
+
+    pub fn set_locale(&self, locale: Locale) {
+        self.set_locale.emit(locale);
+    }
+
+    pub fn t(&self, key: &str) -> String {
+        super::translate(self.locale, key, &[])
+    }
+
+    pub fn t_args(&self, key: &str, args: &[(&str, &str)]) -> String {
+        super::translate(self.locale, key, args)
+    }
+
+    pub fn format_datetime(&self, ts: i64) -> String {
+        super::format_datetime_in(self.locale, ts)
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct I18nProviderProps {
+    pub children: Html,
+}
+
+#[function_component(I18nProvider)]
+pub fn i18n_provider(props: &I18nProviderProps) -> Html {
+    let locale = use_state(|| {
+        super::init();
+        super::current()
+    });
+    let set_locale = {
+        let locale = locale.clone();
+        Callback::from(move |v: Locale| {
+            super::set_current(v);
+            locale.set(v);
+        })
+    };
+    let ctx = I18n {
+        locale: *locale,
+        set_locale,
+    };
+    html! {
+        <ContextProvider<I18n> context={ctx}>
+            {props.children.clone()}
+        </ContextProvider<I18n>>
+    }
+}
+
+// re-renders the caller whenever the language is switched
+#[hook]
+pub fn use_i18n() -> I18n {
+    use_context::<I18n>().unwrap_or_else(|| I18n {
+        locale: super::current(),
+        set_locale: Callback::from(super::set_current),
+    })
+}
@@ -0,0 +1,247 @@
+# common
+common.confirm = 确认
+common.cancel = 取消
+common.return_to_login = 返回登录
+common.go_to_login = 前往登录
+common.please_select_record = 请选择一条记录
+common.undo = 撤销
+common.retry = 重试
+common.back = 返回
+
+# message titles
+message.success = 成功
+message.warning = 警告
+message.info = 提示
+message.error = 错误
+
+# header
+header.language = 语言
+header.unnamed = 未命名
+header.my_account = 我的账户
+header.logout = 退出登录
+header.theme = 主题
+header.presence = 状态
+
+# navigation
+nav.modules = 模块
+nav.welcome = 欢迎
+nav.user = 用户
+nav.user_detail = 用户 #{ $id }
+nav.role = 角色
+nav.audit_log = 审计日志
+nav.my_account = 我的账户
+nav.two_factor = 两步验证
+nav.section.user_management = 用户管理
+nav.section.system = 系统
+
+# menu
+menu.filter = 筛选菜单
+menu.collapse = 收起菜单
+menu.expand = 展开菜单
+
+# command palette
+palette.placeholder = 搜索页面、用户和操作
+palette.no_results = 没有结果
+palette.group.navigation = 页面
+palette.group.users = 用户
+palette.group.actions = 操作
+palette.action.toggle_theme = 切换主题
+palette.action.open_chat = 打开聊天
+palette.action.logout = 退出登录
+palette.action.copy_error_report = 复制错误报告
+palette.error_count = 已记录 { $count } 个错误
+
+# shortcuts
+shortcut.help_title = 键盘快捷键
+shortcut.open_palette = 打开命令面板
+shortcut.show_help = 显示键盘快捷键
+shortcut.palette_move = 在结果之间移动
+shortcut.palette_run = 执行选中的结果
+shortcut.close = 关闭
+
+# theme
+theme.light = 浅色
+theme.dark = 深色
+theme.system = 跟随系统
+
+# presence
+presence.online = 在线
+presence.away = 离开
+presence.offline = 离线
+presence.typing = 正在输入：{ $names }
+
+# login
+login.login = 登录
+login.forgot_password = 忘记密码？
+login.create_account = 创建账户
+login.authentication_code = 验证码：
+login.recovery_code = 恢复码：
+login.verify = 验证
+login.use_authenticator = 使用身份验证器
+login.use_recovery_code = 使用恢复码
+
+# two-factor authentication
+two_factor.intro = 使用身份验证器应用生成的基于时间的一次性密码（TOTP）保护你的账户。
+two_factor.enable = 启用
+two_factor.scan = 用身份验证器应用扫描二维码，然后输入它显示的 6 位验证码。
+two_factor.manual_key = 无法扫描？请手动输入此密钥：
+two_factor.save_recovery_codes = 请把这些恢复码保存在安全的地方。无法使用身份验证器时，每个恢复码可用于登录一次，它们不会再次显示。
+two_factor.done = 完成
+two_factor.enabled = 已启用
+two_factor.manage = 输入身份验证器应用中的验证码以管理两步验证。
+two_factor.regenerate = 重新生成恢复码
+two_factor.disable = 停用
+two_factor.disabled = 两步验证已停用
+
+# register and forget password
+register.email = 邮箱：
+register.password = 密码：
+register.password_confirm = 确认密码：
+register.register = 注册
+forget_pwd.new_password = 新密码：
+forget_pwd.new_password_confirm = 确认新密码：
+forget_pwd.reset_password = 重置密码
+code.label = 验证码：
+code.generate = 获取验证码
+code.resend = 重新发送
+
+# user list
+user_list.search = 搜索
+user_list.detail = 详情
+user_list.edit = 编辑
+user_list.delete = 删除
+user_list.delete_confirm = 删除的用户<b>无法</b>恢复！！！<br/> 确定要删除吗？
+user_list.deleted = 已删除 { $name }
+user.type = 类型
+user.email = 邮箱
+user.name = 姓名
+user.mobile = 手机
+user.laston = 最近登录
+user.created_at = 创建时间
+user.updated_at = 更新时间
+user.status = 状态
+
+# user detail
+user_detail.edit_title = 编辑用户
+user_detail.reset_pwd = 重置密码
+user_detail.reset_pwd_confirm = 该用户将被登出并收到重置密码邮件。<br/> 确定要继续吗？
+user_detail.reset_pwd_sent = 重置密码邮件已发送
+user_detail.updated = 用户已更新
+user_detail.deleted_elsewhere = 该用户已被其他人删除
+user_detail.roles = 角色
+user_detail.sessions = 活跃会话
+user_detail.activities = 最近活动
+
+# my account
+account.profile = 个人资料
+account.save = 保存修改
+account.profile_saved = 个人资料已保存
+account.change_password = 修改密码
+account.current_password = 当前密码
+account.new_password = 新密码
+account.new_password_confirm = 确认新密码
+account.same_password = 新密码不能与当前密码相同
+account.password_changed = 密码已修改
+account.two_factor_desc = 登录时除密码外还需要输入身份验证器应用中的验证码。
+account.manage = 管理
+account.sessions = 我的会话
+
+# sessions
+session.device = 设备
+session.ip = IP
+session.last_seen = 最近活动
+session.unknown_device = 未知设备
+session.current = 当前
+session.empty = 没有活跃的会话
+session.sign_out = 登出此会话
+session.sign_out_others = 登出其他所有会话
+session.sign_out_all = 登出所有会话
+session.signed_out = 已登出
+session.confirm_current = 这是你正在使用的会话，你将被登出。<br/> 确定要继续吗？
+session.confirm_one = 登出 <b>{ $device }</b> 上的会话？
+session.confirm_others = 除当前会话外的所有会话都将被登出。<br/> 确定要继续吗？
+session.confirm_all = 所有会话都将被登出。<br/> 确定要继续吗？
+
+# audit log
+audit_log.actor = 操作人
+audit_log.all_entities = 全部对象
+audit_log.all_actions = 全部操作
+audit_log.from = 开始日期
+audit_log.to = 结束日期
+audit_log.time = 时间
+audit_log.action = 操作
+audit_log.entity = 对象
+audit_log.changes = 变更
+audit_log.field_count = { $count } 个字段
+audit_log.field = 字段
+audit_log.before = 修改前
+audit_log.after = 修改后
+audit_log.entity.user = 用户
+audit_log.entity.role = 角色
+audit_log.action.create = 创建
+audit_log.action.update = 更新
+audit_log.action.delete = 删除
+
+# request error
+request_error.unauthorized_title = 401 未授权
+request_error.unauthorized_heading = 401：未授权
+request_error.unauthorized_desc = 该请求需要用户认证。
+request_error.not_found_title = 404 未找到
+request_error.not_found_heading = 404：未找到
+request_error.not_found_desc = 请求的资源不存在。
+request_error.forbidden_title = 403 禁止访问
+request_error.forbidden_heading = 403：禁止访问
+request_error.forbidden_desc = 你没有权限查看此页面。
+request_error.api_error = 接口请求错误
+
+# crash
+crash.title = 出错了
+crash.desc = 应用遇到了意外错误，重新加载通常可以解决。如果问题反复出现，请复制下面的详细信息并提交问题反馈。
+crash.reload = 重新加载
+crash.copy_details = 复制详细信息
+
+# api error
+api_error.network = 无法连接服务器，请检查网络
+api_error.bad_request = 请求无效
+api_error.unauthorized = 登录已过期，请重新登录
+api_error.forbidden = 你没有权限执行此操作
+api_error.not_found = 请求的资源不存在
+api_error.timeout = 服务器响应超时
+api_error.too_many_requests = 请求过于频繁，请稍后再试
+api_error.server = 服务器出错了
+api_error.cancelled = 请求已取消
+api_error.unknown = 未知错误
+api_error.invalid_response = 服务器返回了无法识别的数据：{ $error }
+api_error.with_request_id = { $message }（请求编号：{ $id }）
+api_error.rolled_back = { $message }，修改已撤回
+
+# validation
+validation.required = 请输入{ $field }
+validation.length = { $field }长度必须在 { $min } 到 { $max } 之间
+validation.pattern = { $field }格式不正确
+validation.mismatch = { $field }必须与{ $other }一致
+validation.email_invalid = 邮箱格式不正确
+validation.code_invalid = 验证码格式不正确：需为 6 位数字
+validation.pwd_confirm_mismatch = 两次输入的密码不一致
+validation.password_policy = 密码不符合要求：{ $unmet }
+field.email = 邮箱
+field.password = 密码
+field.confirm_password = 确认密码
+field.current_password = 当前密码
+field.code = 验证码
+field.recovery_code = 恢复码
+
+# password policy
+password.min_length = 至少 { $n } 个字符
+password.max_length = 最多 { $n } 个字符
+password.lowercase = 包含小写字母
+password.uppercase = 包含大写字母
+password.digit = 包含数字
+password.symbol = 包含符号
+password.no_symbols = 仅限字母和数字
+password.not_common = 不能是常用密码
+password.very_weak = 非常弱
+password.weak = 弱
+password.fair = 一般
+password.good = 良好
+password.strong = 强
@@ -0,0 +1,68 @@
+use crate::i18n::use_i18n::use_i18n;
+use crate::layout::nav::Matched;
+use crate::util::common;
+use yew::prelude::*;
+
+#[derive(PartialEq, Properties)]
+pub struct BreadcrumbProps {
+    pub matched: Matched,
+}
+
+#[function_component(Breadcrumb)]
+pub fn breadcrumb(props: &BreadcrumbProps) -> Html {
+    let i18n = use_i18n();
+    let crumbs = props.matched.crumbs();
+    let last = crumbs.len().saturating_sub(1);
+    html! {
+        <nav class="breadcrumb breadcrumb-container" aria-label="breadcrumbs">
+            <ul>
+            {
+                crumbs.into_iter().enumerate().map(|(i, entry)| {
+                    let label = props.matched.label(entry, &i18n);
+                    let icon = match entry.icon {
+                        Some(v) => html! { <span class="icon is-small"><i class={v} aria-hidden="true"></i></span> },
+                        None => html! {},
+                    };
+                    match entry.path {
+                        _ if i == last => html! {
+                            <li class="is-active"><a href={String::from("javascript:void(0)")} aria-current="page">{icon}<span>{label}</span></a></li>
+                        },
+                        Some(path) => {
+                            let onclick = Callback::from(move |_| common::redirect(path));
+                            html! {
+                                <li><a href={String::from("javascript:void(0)")} onclick={onclick}>{icon}<span>{label}</span></a></li>
+                            }
+                        }
+                        // groups have no page of their own
+                        None => html! {
+                            <li><span class="breadcrumb-group">{label}</span></li>
+                        },
+                    }
+                }).collect::<Html>()
+            }
+            </ul>
+        </nav>
+    }
+}
+
+// `<title>` from index.html, appended to every page title
+fn app_title() -> String {
+    thread_local! {
+        static APP_TITLE: String = web_sys::window()
+            .and_then(|x| x.document())
+            .map(|x| x.title())
+            .unwrap_or_default();
+    }
+    APP_TITLE.with(|x| x.clone())
+}
+
+pub fn set_document_title(title: Option<&str>) {
+    let app = app_title();
+    if let Some(doc) = web_sys::window().and_then(|x| x.document()) {
+        match title {
+            Some(v) if !app.is_empty() => doc.set_title(&format!("{} | {}", v, app)),
+            Some(v) => doc.set_title(v),
+            None => doc.set_title(&app),
+        }
+    }
+}
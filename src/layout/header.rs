@@ -1,17 +1,25 @@
+use crate::i18n::use_i18n::use_i18n;
+use crate::i18n::Locale;
 use crate::layout::navbar::Navbar;
+use crate::realtime::presence::{self, Presence};
+use crate::realtime::use_presence::use_presence;
+use crate::theme::use_theme::use_theme;
+use crate::theme::ThemeMode;
 use crate::util::common;
 use yew::prelude::*;
 
 #[derive(Properties, PartialEq)]
 pub struct HeaderProps {
+    // registry id of the current page
     #[prop_or_default]
-    pub selected_navbar_name: Option<String>,
-    #[prop_or_default]
-    pub selected_navbar_parent_name: Option<String>,
+    pub active: Option<&'static str>,
 }
 
 #[function_component(Header)]
 pub fn header(props: &HeaderProps) -> Html {
+    let i18n = use_i18n();
+    let theme = use_theme();
+    let own_presence = use_presence();
     let mut user = None;
     match common::get_current_user() {
         Ok(v) => user = Some(v),
@@ -30,14 +38,59 @@ pub fn header(props: &HeaderProps) -> Html {
             navbar_active.set(!*navbar_active);
         })
     };
-    let logout = {
-        Callback::from(move |_| {
-            common::delete_current_user().unwrap_or_else(|x| {
-                log::error!("{:?}", x);
-            });
-            common::redirect("/login");
+    let logout = Callback::from(move |_| common::logout());
+    let languages = Locale::all()
+        .into_iter()
+        .map(|locale| {
+            let i18n = i18n.clone();
+            let onclick = Callback::from(move |_| i18n.set_locale(locale));
+            let class = if locale == i18n.locale() {
+                "navbar-item is-active"
+            } else {
+                "navbar-item"
+            };
+            html! {
+                <a href={String::from("javascript:void(0)")} class={class} onclick={onclick}>
+                    {locale.name()}
+                </a>
+            }
         })
-    };
+        .collect::<Html>();
+    let themes = ThemeMode::all()
+        .into_iter()
+        .map(|mode| {
+            let theme = theme.clone();
+            let onclick = Callback::from(move |_| theme.set_mode(mode));
+            let class = if mode == theme.mode() {
+                "navbar-item is-active"
+            } else {
+                "navbar-item"
+            };
+            html! {
+                <a href={String::from("javascript:void(0)")} class={class} onclick={onclick}>
+                    <span class="icon"><i class={mode.icon()}></i></span>
+                    <span>{i18n.t(mode.label())}</span>
+                </a>
+            }
+        })
+        .collect::<Html>();
+    let presences = Presence::all()
+        .into_iter()
+        .map(|x| {
+            let onclick = Callback::from(move |_| presence::set(x));
+            let class = if x == presence::chosen() {
+                "navbar-item is-active"
+            } else {
+                "navbar-item"
+            };
+            html! {
+                <a href={String::from("javascript:void(0)")} class={class} onclick={onclick}>
+                    <span class="icon"><i class={x.icon()}></i></span>
+                    <span>{i18n.t(x.label())}</span>
+                </a>
+            }
+        })
+        .collect::<Html>();
     html! {
         <div class="header-container">
             <nav class="navbar is-light" role="navigation" aria-label="main navigation">
@@ -54,11 +107,20 @@ pub fn header(props: &HeaderProps) -> Html {
                 </div>
 
                 <div id="navbarBasicExample" class={format!("navbar-menu {navbar_active_class}")}>
-                    <Navbar selected_navbar_name={props.selected_navbar_name.clone()} selected_navbar_parent_name={props.selected_navbar_parent_name.clone()}/>
+                    <Navbar active={props.active}/>
                     <div class="navbar-end">
                         <div class="navbar-item has-dropdown is-hoverable">
-                            <a href={String::from("javascript:void(0)")} class="navbar-link" style="color:#000000">
-                                { user.name.unwrap_or("unnamed".to_string())}
+                            <a href={String::from("javascript:void(0)")} class="navbar-link" title={i18n.t("header.language")}>
+                                <span class="icon"><i class="fa-solid fa-language"></i></span>
+                            </a>
+                            <div class="navbar-dropdown is-right">
+                                {languages}
+                            </div>
+                        </div>
+                        <div class="navbar-item has-dropdown is-hoverable">
+                            <a href={String::from("javascript:void(0)")} class="navbar-link header-user" title={i18n.t(own_presence.label())}>
+                                <span class="icon"><i class={own_presence.icon()}></i></span>
+                                { user.name.unwrap_or(i18n.t("header.unnamed"))}
                             </a>
 
                             <div class="navbar-dropdown is-right">
@@ -69,8 +131,21 @@ pub fn header(props: &HeaderProps) -> Html {
                                 {user.email}
                             </a>
                             <hr class="navbar-divider"/>
+                            <div class="navbar-item header-dropdown-label">
+                                {i18n.t("header.theme")}
+                            </div>
+                            {themes}
+                            <hr class="navbar-divider"/>
+                            <div class="navbar-item header-dropdown-label">
+                                {i18n.t("header.presence")}
+                            </div>
+                            {presences}
+                            <hr class="navbar-divider"/>
+                            <a href="/main/account" class="navbar-item">
+                                {i18n.t("header.my_account")}
+                            </a>
                             <a href={String::from("javascript:void(0)")} onclick={logout} class="navbar-item">
-                                {"Logout"}
+                                {i18n.t("header.logout")}
                             </a>
                             </div>
                         </div>
@@ -1,6 +1,15 @@
-use crate::component::menu::{Menu, MenuLabel};
+use crate::component::command_palette::CommandPalette;
+use crate::component::menu::{Menu, MenuLabel, MenuNode};
+use crate::component::shortcut_help::ShortcutHelp;
+use crate::error_page::request_error::RequestError;
+use crate::i18n::use_i18n::{use_i18n, I18n};
+use crate::layout::breadcrumb::{self, Breadcrumb};
 use crate::layout::header::Header;
+use crate::layout::nav::{self, Chrome, NavEntry};
+use crate::storage::keys;
+use crate::storage::use_storage::use_storage;
 use crate::util::common;
+use crate::util::common::CurrentUser;
 use yew::prelude::*;
 use yew::virtual_dom::VNode;
 
@@ -8,78 +17,105 @@ use yew::virtual_dom::VNode;
 pub struct BodyProps {
     #[prop_or_default]
     pub content: VNode,
-    #[prop_or_default]
-    pub menus: Vec<MenuLabel>,
-}
-
-pub struct Item<'a> {
-    pub path: &'a str,
-    pub navbar_name: Option<&'a str>,
-    pub navbar_parent_name: Option<&'a str>,
-    pub left_menu_name: Option<&'a str>,
-}
-
-pub fn gen_items<'a>() -> Vec<Item<'a>> {
-    vec![
-        Item {
-            path: "/main/user",
-            navbar_name: Some("User"),
-            navbar_parent_name: Some("Modules"),
-            left_menu_name: Some("User"),
-        },
-        Item {
-            path: "/main/role",
-            navbar_name: Some("User"),
-            navbar_parent_name: Some("Modules"),
-            left_menu_name: Some("Role"),
-        },
-        Item {
-            path: "/",
-            navbar_name: Some("Welcome"),
-            navbar_parent_name: Some("Modules"),
-            left_menu_name: None,
-        },
-    ]
 }
 
-pub fn get_selected_navbar_and_menu() -> (Option<String>, Option<String>, Option<String>) {
-    let mut res = (None, None, None);
-    let path = web_sys::window().unwrap().location().pathname().unwrap();
-    let items = gen_items();
-    let items = items.iter().find(|x| x.path == &path);
-    if let Some(v) = items {
-        res.0 = v.navbar_name.map(|x| x.to_string());
-        res.1 = v.navbar_parent_name.map(|x| x.to_string());
-        res.2 = v.left_menu_name.map(|x| x.to_string());
+fn menu_node(entry: &NavEntry, user: Option<&CurrentUser>, i18n: &I18n) -> MenuNode {
+    MenuNode {
+        name: i18n.t(entry.title),
+        key: Some(entry.id.to_string()),
+        icon: entry.icon.map(|x| x.to_string()),
+        children: nav::menu_children(entry, user)
+            .into_iter()
+            .map(|x| menu_node(x, user, i18n))
+            .collect(),
     }
-
-    res
 }
 
+// header, navbar and side menu around `content`, as the registry entry of the current path asks
 #[function_component(Layout)]
 pub fn body(props: &BodyProps) -> Html {
-    let (selected_navbar_name, selected_navbar_parent_name, selected_name) =
-        get_selected_navbar_and_menu();
-    let labels = props.menus.clone();
-    let on_select_menu = Callback::from(move |name: String| {
-        if let Some(item) = gen_items()
-            .iter()
-            .find(|x| x.left_menu_name.is_some_and(|x| x == &name))
-        {
-            common::redirect(item.path);
+    let i18n = use_i18n();
+    let force_update = use_force_update();
+    let collapsed = use_mut_ref(|| keys::MENU_COLLAPSED.get().unwrap_or_default());
+    let user = use_storage(&keys::CURRENT_USER);
+    // signing out in another tab signs out this one too
+    use_effect_with((), |_| {
+        let subscription = keys::CURRENT_USER.subscribe(|user| {
+            let bare = nav::current().is_none_or(|x| x.entry.chrome == Chrome::Bare);
+            if user.is_none() && !bare {
+                common::redirect("/login");
+            }
+        });
+        move || drop(subscription)
+    });
+    let matched = nav::current();
+    let title = matched.as_ref().map(|x| x.document_title(&i18n));
+    use_effect_with(title, |title| {
+        breadcrumb::set_document_title(title.as_deref())
+    });
+    let matched = match matched {
+        Some(v) if v.entry.chrome != Chrome::Bare => v,
+        _ => return props.content.clone(),
+    };
+    if !matched.entry.is_allowed(user.as_ref()) {
+        return html! { <RequestError status={403} /> };
+    }
+
+    let labels = nav::menu_sections(user.as_ref())
+        .into_iter()
+        .map(|(section, entries)| MenuLabel {
+            label: Some(i18n.t(section)),
+            key: Some(section.to_string()),
+            nodes: entries
+                .into_iter()
+                .map(|x| menu_node(x, user.as_ref(), &i18n))
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+    let selected_name = matched.entry.menu_entry().map(|x| x.id.to_string());
+    let on_select_menu = Callback::from(move |id: String| {
+        if let Some(path) = nav::get(&id).and_then(|x| x.path) {
+            common::redirect(path);
         }
     });
+    let toggle_collapsed = {
+        let collapsed = collapsed.clone();
+        Callback::from(move |_| {
+            let v = !*collapsed.borrow();
+            *collapsed.borrow_mut() = v;
+            keys::MENU_COLLAPSED.set(&v);
+            force_update.force_update();
+        })
+    };
+    let collapsed = *collapsed.borrow();
+    let (left_class, content_class) = if collapsed {
+        ("column is-narrow left-container is-collapsed", "column")
+    } else {
+        ("column is-2 left-container", "column is-10")
+    };
+    let (toggle_icon, toggle_title) = if collapsed {
+        ("fa-solid fa-angles-right", i18n.t("menu.expand"))
+    } else {
+        ("fa-solid fa-angles-left", i18n.t("menu.collapse"))
+    };
     html! {
         <>
-             <Header selected_navbar_name={selected_navbar_name} selected_navbar_parent_name={selected_navbar_parent_name} />
+             <Header active={matched.entry.id} />
+             <Breadcrumb matched={matched.clone()} />
+             <CommandPalette />
+             <ShortcutHelp />
              {
-                if !props.menus.is_empty() {
+                if matched.entry.chrome == Chrome::Menu && !labels.is_empty() {
                     html!{
                         <div class="columns is-gapless">
-                            <div class="column is-2 left-container">
-                                <Menu onselect={on_select_menu} selected_name = {selected_name} labels = { labels }/>
+                            <div class={left_class}>
+                                <a href={String::from("javascript:void(0)")} class="menu-collapse-toggle" onclick={toggle_collapsed} title={toggle_title}>
+                                    <span class="icon"><i class={toggle_icon}></i></span>
+                                </a>
+                                <Menu onselect={on_select_menu} selected_name = {selected_name} labels = { labels }
+                                    collapsed={collapsed} filterable={true} storage_key={keys::MENU_EXPANDED.clone()}/>
                             </div>
-                            <div class="column is-10">
+                            <div class={content_class}>
                                 { props.content.clone() }
                             </div>
                         </div>
@@ -1,3 +1,5 @@
+pub mod breadcrumb;
 pub mod header;
+pub mod nav;
 pub mod navbar;
 pub mod layout;
@@ -0,0 +1,380 @@
+use crate::i18n::use_i18n::I18n;
+use crate::util::common::CurrentUser;
+
+// everything the app knows about its pages; the navbar, the side menu and the layout chrome
+// are all derived from `ENTRIES`, so adding a page means adding one entry (plus its `Route`,
+// which a test in main.rs checks)
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Chrome {
+    // the page renders itself, e.g. login
+    Bare,
+    // header and navbar only
+    Header,
+    // header, navbar and the side menu
+    Menu,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct NavEntry {
+    pub id: &'static str,
+    pub parent: Option<&'static str>,
+    // `:name` segments match anything, e.g. "/main/user/:id"; groups have no path
+    pub path: Option<&'static str>,
+    // catalog key
+    pub title: &'static str,
+    pub icon: Option<&'static str>,
+    // catalog key of the side menu label; entries without one stay out of the menu
+    pub section: Option<&'static str>,
+    pub navbar: bool,
+    // a divider follows the entry in the navbar dropdown
+    pub divider: bool,
+    pub chrome: Chrome,
+    pub permission: Option<&'static str>,
+}
+
+impl NavEntry {
+    const fn new(id: &'static str, title: &'static str) -> Self {
+        Self {
+            id,
+            parent: None,
+            path: None,
+            title,
+            icon: None,
+            section: None,
+            navbar: false,
+            divider: false,
+            chrome: Chrome::Header,
+            permission: None,
+        }
+    }
+
+    const fn parent(mut self, parent: &'static str) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    const fn path(mut self, path: &'static str) -> Self {
+        self.path = Some(path);
+        self
+    }
+
+    const fn icon(mut self, icon: &'static str) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    const fn menu(mut self, section: &'static str) -> Self {
+        self.section = Some(section);
+        self.chrome = Chrome::Menu;
+        self
+    }
+
+    const fn navbar(mut self) -> Self {
+        self.navbar = true;
+        self
+    }
+
+    const fn divider(mut self) -> Self {
+        self.divider = true;
+        self
+    }
+
+    const fn chrome(mut self, chrome: Chrome) -> Self {
+        self.chrome = chrome;
+        self
+    }
+
+    const fn permission(mut self, permission: &'static str) -> Self {
+        self.permission = Some(permission);
+        self
+    }
+
+    pub fn is_allowed(&self, user: Option<&CurrentUser>) -> bool {
+        match self.permission {
+            Some(v) => user.is_some_and(|x| x.can(v)),
+            None => true,
+        }
+    }
+
+    pub fn parent_entry(&self) -> Option<&'static NavEntry> {
+        self.parent.and_then(get)
+    }
+
+    pub fn children(&self) -> impl Iterator<Item = &'static NavEntry> + '_ {
+        ENTRIES.iter().filter(|x| x.parent == Some(self.id))
+    }
+
+    // the entry itself followed by its ancestors
+    pub fn trail(&'static self) -> impl Iterator<Item = &'static NavEntry> {
+        std::iter::successors(Some(self), |x| x.parent_entry())
+    }
+
+    // pages that are not in the menu, e.g. a user detail, highlight their closest ancestor
+    pub fn menu_entry(&'static self) -> Option<&'static NavEntry> {
+        self.trail().find(|x| x.section.is_some())
+    }
+
+    pub fn navbar_entry(&'static self) -> Option<&'static NavEntry> {
+        self.trail().find(|x| x.navbar && x.path.is_some())
+    }
+}
+
+const USER_MANAGEMENT: &str = "nav.section.user_management";
+const SYSTEM: &str = "nav.section.system";
+
+static ENTRIES: [NavEntry; 9] = [
+    NavEntry::new("login", "login.login")
+        .path("/login")
+        .chrome(Chrome::Bare),
+    NavEntry::new("modules", "nav.modules").navbar(),
+    NavEntry::new("welcome", "nav.welcome")
+        .parent("modules")
+        .path("/")
+        .icon("fa-solid fa-house")
+        .navbar()
+        .divider(),
+    NavEntry::new("user", "nav.user")
+        .parent("modules")
+        .path("/main/user")
+        .icon("fa-solid fa-user")
+        .navbar()
+        .menu(USER_MANAGEMENT)
+        .permission("user:read"),
+    NavEntry::new("user_detail", "nav.user_detail")
+        .parent("user")
+        .path("/main/user/:id")
+        .chrome(Chrome::Menu)
+        .permission("user:read"),
+    NavEntry::new("role", "nav.role")
+        .parent("modules")
+        .path("/main/role")
+        .icon("fa-solid fa-user-shield")
+        .menu(USER_MANAGEMENT)
+        .permission("role:read"),
+    NavEntry::new("audit_log", "nav.audit_log")
+        .parent("modules")
+        .path("/main/audit_log")
+        .icon("fa-solid fa-clock-rotate-left")
+        .navbar()
+        .menu(SYSTEM)
+        .permission("audit_log:read"),
+    NavEntry::new("account", "nav.my_account")
+        .path("/main/account")
+        .icon("fa-solid fa-user-gear"),
+    NavEntry::new("two_factor", "nav.two_factor")
+        .parent("account")
+        .path("/main/account/two_factor")
+        .icon("fa-solid fa-shield-halved"),
+];
+
+pub fn entries() -> &'static [NavEntry] {
+    &ENTRIES
+}
+
+pub fn get(id: &str) -> Option<&'static NavEntry> {
+    ENTRIES.iter().find(|x| x.id == id)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matched {
+    pub entry: &'static NavEntry,
+    // values of the `:name` segments
+    pub params: Vec<(&'static str, String)>,
+}
+
+impl Matched {
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    // titles may use the path params, e.g. "user #{ $id }"
+    pub fn label(&self, entry: &NavEntry, i18n: &I18n) -> String {
+        let args = self
+            .params
+            .iter()
+            .map(|(k, v)| (*k, v.as_str()))
+            .collect::<Vec<_>>();
+        i18n.t_args(entry.title, &args)
+    }
+
+    // root first, ending with the matched entry
+    pub fn crumbs(&self) -> Vec<&'static NavEntry> {
+        let mut res = self.entry.trail().collect::<Vec<_>>();
+        res.reverse();
+        res
+    }
+
+    // most specific first, so it survives tab truncation
+    pub fn document_title(&self, i18n: &I18n) -> String {
+        self.entry
+            .trail()
+            .map(|x| self.label(x, i18n))
+            .collect::<Vec<_>>()
+            .join(" - ")
+    }
+}
+
+fn match_path(pattern: &'static str, path: &str) -> Option<Vec<(&'static str, String)>> {
+    let pattern = pattern
+        .split('/')
+        .filter(|x| !x.is_empty())
+        .collect::<Vec<_>>();
+    let path = path
+        .split('/')
+        .filter(|x| !x.is_empty())
+        .collect::<Vec<_>>();
+    if pattern.len() != path.len() {
+        return None;
+    }
+    let mut params = vec![];
+    for (p, v) in pattern.into_iter().zip(path) {
+        match p.strip_prefix(':') {
+            Some(name) => params.push((name, v.to_string())),
+            None if p == v => {}
+            None => return None,
+        }
+    }
+    Some(params)
+}
+
+pub fn find(path: &str) -> Option<Matched> {
+    ENTRIES.iter().find_map(|entry| {
+        let params = match_path(entry.path?, path)?;
+        Some(Matched { entry, params })
+    })
+}
+
+pub fn current() -> Option<Matched> {
+    let path = web_sys::window()?.location().pathname().ok()?;
+    find(&path)
+}
+
+// side menu sections in registry order, with the top level entries the user may open;
+// menu entries whose parent is in the menu too nest under it, see `menu_children`
+pub fn menu_sections(user: Option<&CurrentUser>) -> Vec<(&'static str, Vec<&'static NavEntry>)> {
+    let mut sections: Vec<(&'static str, Vec<&'static NavEntry>)> = vec![];
+    for entry in ENTRIES.iter().filter(|x| x.is_allowed(user)) {
+        let section = match entry.section {
+            Some(v) if entry.parent_entry().is_none_or(|x| x.section.is_none()) => v,
+            _ => continue,
+        };
+        match sections.iter_mut().find(|(x, _)| *x == section) {
+            Some((_, items)) => items.push(entry),
+            None => sections.push((section, vec![entry])),
+        }
+    }
+    sections
+}
+
+pub fn menu_children(entry: &NavEntry, user: Option<&CurrentUser>) -> Vec<&'static NavEntry> {
+    entry
+        .children()
+        .filter(|x| x.section.is_some() && x.is_allowed(user))
+        .collect()
+}
+
+// top level navbar entries; groups without a visible child are dropped
+pub fn navbar_entries(
+    user: Option<&CurrentUser>,
+) -> Vec<(&'static NavEntry, Vec<&'static NavEntry>)> {
+    ENTRIES
+        .iter()
+        .filter(|x| x.parent.is_none() && x.navbar && x.is_allowed(user))
+        .map(|x| {
+            let children = x
+                .children()
+                .filter(|c| c.navbar && c.is_allowed(user))
+                .collect::<Vec<_>>();
+            (x, children)
+        })
+        .filter(|(x, children)| x.path.is_some() || !children.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(permissions: Option<&[&str]>) -> CurrentUser {
+        CurrentUser {
+            id: 1,
+            r#type: "user".to_string(),
+            email: "hello@example.com".to_string(),
+            name: None,
+            mobile: None,
+            laston: None,
+            created_at: 0,
+            updated_at: None,
+            permissions: permissions.map(|x| x.iter().map(|p| p.to_string()).collect()),
+        }
+    }
+
+    fn ids(entries: &[&NavEntry]) -> Vec<&'static str> {
+        entries.iter().map(|x| x.id).collect()
+    }
+
+    #[test]
+    fn match_path_static() {
+        assert_eq!(match_path("/main/user", "/main/user"), Some(vec![]));
+        assert_eq!(match_path("/main/user", "/main/user/"), Some(vec![]));
+        assert_eq!(match_path("/", "/"), Some(vec![]));
+        assert_eq!(match_path("/main/user", "/main/role"), None);
+        assert_eq!(match_path("/main/user", "/main"), None);
+        assert_eq!(match_path("/", "/main"), None);
+    }
+
+    #[test]
+    fn match_path_params() {
+        assert_eq!(
+            match_path("/main/user/:id", "/main/user/42"),
+            Some(vec![("id", "42".to_string())])
+        );
+        assert_eq!(match_path("/main/user/:id", "/main/user"), None);
+        assert_eq!(match_path("/main/user/:id", "/main/user/42/edit"), None);
+    }
+
+    #[test]
+    fn find_prefers_the_exact_entry() {
+        assert_eq!(find("/main/user").map(|x| x.entry.id), Some("user"));
+        let matched = find("/main/user/7").unwrap();
+        assert_eq!(matched.entry.id, "user_detail");
+        assert_eq!(matched.param("id"), Some("7"));
+        assert_eq!(
+            ids(&matched.crumbs()),
+            vec!["modules", "user", "user_detail"]
+        );
+        assert!(find("/nowhere").is_none());
+    }
+
+    #[test]
+    fn menu_sections_everything_allowed() {
+        let sections = menu_sections(Some(&user(None)));
+        let sections = sections
+            .iter()
+            .map(|(section, entries)| (*section, ids(entries)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sections,
+            vec![
+                (USER_MANAGEMENT, vec!["user", "role"]),
+                (SYSTEM, vec!["audit_log"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn menu_sections_by_permission() {
+        let sections = menu_sections(Some(&user(Some(&["role:read"]))));
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].0, USER_MANAGEMENT);
+        assert_eq!(ids(&sections[0].1), vec!["role"]);
+
+        assert_eq!(menu_sections(Some(&user(Some(&["*"])))).len(), 2);
+        assert!(menu_sections(Some(&user(Some(&[])))).is_empty());
+        assert!(menu_sections(None).is_empty());
+    }
+}
@@ -1,109 +1,76 @@
+use crate::i18n::use_i18n::use_i18n;
+use crate::layout::nav::{self, NavEntry};
 use crate::util::common;
-use serde::{Deserialize, Serialize};
 use yew::prelude::*;
 
 #[derive(PartialEq, Properties)]
 pub struct NavbarProps {
+    // registry id of the current page
     #[prop_or_default]
-    pub selected_navbar_name: Option<String>,
-    #[prop_or_default]
-    pub selected_navbar_parent_name: Option<String>,
-}
-
-#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
-pub struct NavbarNode {
-    name: String,
-    children: Vec<NavbarNode>,
-    path: Option<String>,
-    divider: bool,
+    pub active: Option<&'static str>,
 }
 
-impl NavbarNode {
-    fn render(&self, props: &NavbarProps) -> Html {
-        let selected_navbar_name = props.selected_navbar_name.clone();
-        let selected_navbar_parent_name = props.selected_navbar_parent_name.clone();
-        if self.children.is_empty() {
-            let onselect = {
-                let path = self.path.clone().unwrap();
-                Callback::from(move |_| {
-                    common::redirect(&path);
-                })
-            };
-            html! {
-                <a href={String::from("javascript:void(0)")} onclick = {onselect} class={if selected_navbar_name.is_some() && &self.name == &selected_navbar_name.clone().unwrap()  {"navbar-item is-active"} else {"navbar-item"}}>
-                    {self.name.clone()}
-                </a>
-            }
-        } else {
-            html! {
-            <div class="navbar-item has-dropdown is-hoverable">
-                    <a href={String::from("javascript:void(0)")} class={if selected_navbar_parent_name.is_some() && &self.name == &selected_navbar_parent_name.clone().unwrap() {"navbar-link is-active"} else {"navbar-link"}}>
-                        {self.name.clone()}
-                    </a>
-                    <div class="navbar-dropdown">
-                        {
-                            self.children.iter().map(|child_item|{
-                                let class = {if selected_navbar_name.is_some() && &child_item.name == &selected_navbar_name.clone().unwrap() {"navbar-item is-active"} else {"navbar-item"}};
-                                let onselect = {
-                                    let path = child_item.path.clone().unwrap();
-                                    Callback::from(move |_| {
-                                        common::redirect(&path);
-                                    })
-                                };
-                                html!{
-                                    <>
-                                    <a href={String::from("javascript:void(0)")} onclick = {onselect} class={class} >
-                                        {child_item.name.clone()}
-                                    </a>
-                                    {
-                                        if child_item.divider{
-                                        html!{ <hr class="navbar-divider"/> }
-                                        }else{
-                                        html!{}
-                                        }
-                                    }
-                                    </>
-                                }
-                            }).collect::<Html>()
-                        }
-                    </div>
-            </div>
-            }
-        }
-    }
+fn redirect_to(entry: &NavEntry) -> Callback<MouseEvent> {
+    let path = entry.path.unwrap_or("/");
+    Callback::from(move |_| common::redirect(path))
 }
 
 #[function_component(Navbar)]
 pub fn navbar(props: &NavbarProps) -> Html {
-    let data = vec![NavbarNode {
-        name: "Modules".to_string(),
-        path: None,
-        divider: false,
-        children: vec![
-            NavbarNode {
-                name: "Welcome".to_string(),
-                path: Some("/".to_string()),
-                divider: true,
-                children: vec![],
-            },
-            NavbarNode {
-                name: "User".to_string(),
-                path: Some("/main/user".to_string()),
-                divider: false,
-                children: vec![],
-            },
-        ],
-    }];
+    let i18n = use_i18n();
+    let user = common::get_current_user().ok();
+    let selected = props
+        .active
+        .and_then(nav::get)
+        .and_then(|x| x.navbar_entry());
+    let is_selected = |entry: &NavEntry| selected.is_some_and(|x| x.id == entry.id);
+    let is_selected_parent =
+        |entry: &NavEntry| selected.is_some_and(|x| x.parent == Some(entry.id));
     html! {
         <div class="navbar-start">
         {
-            data
-            .iter()
-            .map(|item| {
-                item.render(props)
+            nav::navbar_entries(user.as_ref())
+            .into_iter()
+            .map(|(item, children)| {
+                if children.is_empty() {
+                    html! {
+                        <a href={String::from("javascript:void(0)")} onclick = {redirect_to(item)} class={if is_selected(item) {"navbar-item is-active"} else {"navbar-item"}}>
+                            {i18n.t(item.title)}
+                        </a>
+                    }
+                } else {
+                    html! {
+                    <div class="navbar-item has-dropdown is-hoverable">
+                            <a href={String::from("javascript:void(0)")} class={if is_selected_parent(item) {"navbar-link is-active"} else {"navbar-link"}}>
+                                {i18n.t(item.title)}
+                            </a>
+                            <div class="navbar-dropdown">
+                                {
+                                    children.into_iter().map(|child_item|{
+                                        let class = if is_selected(child_item) {"navbar-item is-active"} else {"navbar-item"};
+                                        html!{
+                                            <>
+                                            <a href={String::from("javascript:void(0)")} onclick = {redirect_to(child_item)} class={class} >
+                                                {i18n.t(child_item.title)}
+                                            </a>
+                                            {
+                                                if child_item.divider{
+                                                html!{ <hr class="navbar-divider"/> }
+                                                }else{
+                                                html!{}
+                                                }
+                                            }
+                                            </>
+                                        }
+                                    }).collect::<Html>()
+                                }
+                            </div>
+                    </div>
+                    }
+                }
             })
             .collect::<Html>()
         }
         </div>
     }
-}
\ No newline at end of file
+}
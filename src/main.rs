@@ -1,54 +1,70 @@
 #![feature(linked_list_remove)]
-// mod component;
-// mod confirm_form;
+mod audit_log;
+mod change_pwd;
+mod component;
+mod confirm_form;
 mod error_page;
-// mod forget_pwd;
-// mod layout;
+mod forget_pwd;
+mod form;
+mod i18n;
+mod layout;
 mod login;
-// mod register;
-// mod role_list;
-// mod user_form;
-// mod user_list;
+mod query;
+mod realtime;
+mod register;
+mod role_list;
+mod shortcut;
+mod storage;
+mod theme;
+mod two_factor;
+mod user_detail;
+mod user_form;
+mod user_list;
 mod util;
 
-// use component::menu::{MenuLabel, MenuNode};
-// use component::welcome::Welcome;
+use audit_log::AuditLog;
+use change_pwd::MyAccount;
+use component::welcome::Welcome;
 use error_page::{page_not_found::PageNotFound, request_error::RequestError};
-// use forget_pwd::ForgetPwd;
-// use layout::layout::Layout;
+use forget_pwd::ForgetPwd;
+use i18n::use_i18n::I18nProvider;
+use layout::layout::Layout;
 use login::Login;
-// use register::Register;
-// use role_list::RoleList;
-// use user_list::UserList;
+use register::Register;
+use role_list::RoleList;
+use theme::use_theme::ThemeProvider;
+use two_factor::TwoFactorSetup;
+use user_detail::UserDetail;
+use user_list::UserList;
 use yew::prelude::*;
-// use yew::virtual_dom::VNode;
 use yew_router::prelude::*;
 use yew_router::BrowserRouter;
 
-#[derive(Clone, Routable, PartialEq)]
-enum RouteBody {
-    #[not_found]
-    #[at("/page_not_found")]
-    PageNotFound,
-    #[at("/main/user")]
-    User, // Hello,
-    #[at("/main/role")]
-    Role, // Hello,
-}
-
 #[derive(Clone, Routable, PartialEq)]
 enum Route {
     #[not_found]
     #[at("/page_not_found")]
     PageNotFound,
-    // #[at("/")]
-    // Welcome,
+    #[at("/")]
+    Welcome,
     #[at("/login")]
     Login,
-    // #[at("/forget_pwd")]
-    // ForgetPwd,
-    // #[at("/register")]
-    // Register,
+    #[at("/forget_pwd")]
+    ForgetPwd,
+    #[at("/register")]
+    Register,
+    #[at("/main/user")]
+    User,
+    #[at("/main/user/:id")]
+    UserDetail { id: i64 },
+    #[at("/main/role")]
+    Role,
+    #[at("/main/audit_log")]
+    AuditLog,
+    #[at("/main/account")]
+    MyAccount,
+    #[at("/main/account/two_factor")]
+    TwoFactor,
     // #[at("/main/:?")]
     // Body,
     #[at("/401")]
@@ -57,67 +73,59 @@ enum Route {
     NotFound,
 }
 
-// fn switch_body(route: RouteBody) -> VNode {
-//     match route {
-//         RouteBody::PageNotFound => {
-//             html! {
-//                 <PageNotFound />
-//             }
-//         }
-//         RouteBody::User => {
-//             html! {
-//                 <UserList />
-//             }
-//         }
-
-//         RouteBody::Role => {
-//             html! {
-//                 <RoleList />
-//             }
-//         }
-//     }
-// }
-
+// pages only; `Layout` adds the header and menus its registry entry asks for
 fn switch(route: Route) -> Html {
-    match route {
+    let content = match route {
         Route::Login => {
             html! {
                 <Login />
             }
         }
-        // Route::Register => {
-        //     html! {
-        //         <Register />
-        //     }
-        // }
-        // Route::ForgetPwd => {
-        //     html! {
-        //         <ForgetPwd />
-        //     }
-        // }
-        // Route::Welcome => {
-        //     html! {
-        //         <Layout content={html!{<Welcome greeting={"Welcome to Pied Piper!"} />}}/>
-        //     }
-        // }
-        // Route::Body => {
-        //     let menus = vec![MenuLabel {
-        //         label: Some(String::from("User Management")),
-        //         nodes: vec![
-        //             MenuNode {
-        //                 name: String::from("User"),
-        //                 children: Default::default(),
-        //             },
-        //             MenuNode {
-        //                 name: String::from("Role"),
-        //                 children: Default::default(),
-        //             },
-        //         ],
-        //     }];
-        //     html! {
-        //         <Layout menus = {menus} content={html!{<Switch<RouteBody> render={switch_body} />}}/>
-        //     }
-        // }
+        Route::Register => {
+            html! {
+                <Register />
+            }
+        }
+        Route::ForgetPwd => {
+            html! {
+                <ForgetPwd />
+            }
+        }
+        Route::Welcome => {
+            html! {
+                <Welcome greeting={"Welcome to Pied Piper!"} />
+            }
+        }
+        Route::User => {
+            html! {
+                <UserList />
+            }
+        }
+        Route::UserDetail { id } => {
+            html! {
+                <UserDetail id={id} />
+            }
+        }
+        Route::Role => {
+            html! {
+                <RoleList />
+            }
+        }
+        Route::AuditLog => {
+            html! {
+                <AuditLog />
+            }
+        }
+        Route::MyAccount => {
+            html! {
+                <MyAccount />
+            }
+        }
+        Route::TwoFactor => {
+            html! {
+                <TwoFactorSetup />
+            }
+        }
         Route::Unauthorized => {
             html! {
                 <RequestError status={401} />
@@ -135,19 +143,58 @@ fn switch(route: Route) -> Html {
                 <PageNotFound />
             }
         }
+    };
+    html! {
+        <Layout content={content} />
     }
 }
 
 #[function_component(Main)]
 fn app() -> Html {
     html! {
-        <BrowserRouter>
-            <Switch<Route> render={switch} />
-        </BrowserRouter>
+        <I18nProvider>
+            <ThemeProvider>
+                <BrowserRouter>
+                    <Switch<Route> render={switch} />
+                </BrowserRouter>
+            </ThemeProvider>
+        </I18nProvider>
     }
 }
 
 fn main() {
     wasm_logger::init(wasm_logger::Config::default());
+    error_page::crash::install();
+    storage::init();
+    realtime::init();
     yew::Renderer::<Main>::new().render();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use layout::nav;
+
+    // every page of the registry must have a `Route`, otherwise its links end up on the 404 page
+    #[test]
+    fn registry_paths_have_routes() {
+        for entry in nav::entries() {
+            let path = match entry.path {
+                Some(v) => v,
+                None => continue,
+            };
+            let path = path
+                .split('/')
+                .map(|x| if x.starts_with(':') { "1" } else { x })
+                .collect::<Vec<_>>()
+                .join("/");
+            let route = Route::recognize(&path);
+            assert!(
+                route.is_some_and(|x| x != Route::PageNotFound),
+                "no route for {} ({})",
+                entry.id,
+                path
+            );
+        }
+    }
+}
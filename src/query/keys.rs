@@ -0,0 +1,32 @@
+use super::QueryKey;
+
+// every cached query; invalidating a parent refetches all of its children
+
+pub fn users() -> QueryKey {
+    QueryKey::new("user")
+}
+
+// every page of every search
+pub fn user_searches() -> QueryKey {
+    users().with("search")
+}
+
+pub fn user_search(key_word: &str, index: i64, size: usize) -> QueryKey {
+    user_searches().with(key_word).with(index).with(size)
+}
+
+pub fn user_detail(id: i64) -> QueryKey {
+    users().with("detail").with(id)
+}
+
+pub fn sessions() -> QueryKey {
+    QueryKey::new("session")
+}
+
+// `None` for the sessions of the current user
+pub fn user_sessions(user_id: Option<i64>) -> QueryKey {
+    match user_id {
+        Some(id) => sessions().with(id),
+        None => sessions().with("me"),
+    }
+}
@@ -0,0 +1,565 @@
+pub mod keys;
+pub mod use_mutation;
+pub mod use_query;
+
+use crate::util::api_error::{ApiError, ApiResult};
+use crate::util::middleware::CancelToken;
+use crate::util::request::RequestOptions;
+use futures::future::LocalBoxFuture;
+use gloo::events::EventListener;
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+
+const DEFAULT_STALE_MS: u32 = 30_000;
+const DEFAULT_CACHE_MS: u32 = 5 * 60_000;
+
+thread_local! {
+    static CACHE: RefCell<HashMap<QueryKey, Entry>> = RefCell::new(HashMap::new());
+    static LISTENER: RefCell<Option<EventListener>> = const { RefCell::new(None) };
+    static NEXT_ID: Cell<usize> = const { Cell::new(0) };
+}
+
+// names a cached result, e.g. `user/detail/12`; see `keys` for the ones in use
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QueryKey(Vec<String>);
+
+impl QueryKey {
+    pub fn new(name: &str) -> Self {
+        QueryKey(vec![name.to_string()])
+    }
+
+    pub fn with(mut self, part: impl ToString) -> Self {
+        self.0.push(part.to_string());
+        self
+    }
+
+    // `prefix` itself or one of its children, so `user` covers `user/detail/12`
+    pub fn starts_with(&self, prefix: &QueryKey) -> bool {
+        self.0.starts_with(&prefix.0)
+    }
+}
+
+impl std::fmt::Display for QueryKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0.join("/"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QueryOptions {
+    // a result younger than this is served without asking the server again
+    stale_ms: u32,
+    // how long a result nobody uses is kept around
+    cache_ms: u32,
+}
+
+impl Default for QueryOptions {
+    fn default() -> Self {
+        QueryOptions {
+            stale_ms: DEFAULT_STALE_MS,
+            cache_ms: DEFAULT_CACHE_MS,
+        }
+    }
+}
+
+impl QueryOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stale(mut self, ms: u32) -> Self {
+        self.stale_ms = ms;
+        self
+    }
+
+    pub fn cache(mut self, ms: u32) -> Self {
+        self.cache_ms = ms;
+        self
+    }
+}
+
+pub(crate) type Fetch =
+    Rc<dyn Fn(RequestOptions) -> LocalBoxFuture<'static, ApiResult<Rc<dyn Any>>>>;
+
+struct Entry {
+    data: Option<Rc<dyn Any>>,
+    error: Option<ApiError>,
+    // ms since the epoch; 0 until the first result and after an invalidation
+    updated_at: f64,
+    // the token of the request in flight, compared to drop answers that were overtaken
+    fetching: Option<CancelToken>,
+    // the fetcher of the latest subscriber, used for every refetch
+    fetch: Option<Fetch>,
+    options: QueryOptions,
+    subscribers: Vec<(usize, Rc<dyn Fn()>)>,
+    // when the last subscriber left
+    unused_since: Option<f64>,
+}
+
+impl Entry {
+    fn new(options: QueryOptions) -> Self {
+        Entry {
+            data: None,
+            error: None,
+            updated_at: 0.0,
+            fetching: None,
+            fetch: None,
+            options,
+            subscribers: vec![],
+            unused_since: None,
+        }
+    }
+
+    fn is_stale(&self, now: f64) -> bool {
+        self.updated_at + self.options.stale_ms as f64 <= now
+    }
+
+    // nobody used it for longer than its `cache_ms`
+    fn is_expired(&self, now: f64) -> bool {
+        self.unused_since
+            .is_some_and(|since| since + self.options.cache_ms as f64 <= now)
+    }
+
+    // claims the entry for a new request; `None` while one is in flight, the caller joins that
+    fn begin(&mut self) -> Option<(Fetch, CancelToken)> {
+        if self.fetching.is_some() {
+            return None;
+        }
+        let fetch = self.fetch.clone()?;
+        let token = CancelToken::new();
+        self.fetching = Some(token.clone());
+        Some((fetch, token))
+    }
+}
+
+// what a component sees of an entry
+pub(crate) struct Snapshot<T> {
+    pub data: Option<Rc<T>>,
+    pub error: Option<ApiError>,
+    pub fetching: bool,
+}
+
+// keeps the entry alive and refetched until dropped
+pub(crate) struct QuerySubscription {
+    key: QueryKey,
+    id: usize,
+}
+
+impl Drop for QuerySubscription {
+    fn drop(&mut self) {
+        CACHE.with(|x| {
+            if let Some(entry) = x.borrow_mut().get_mut(&self.key) {
+                entry.subscribers.retain(|(id, _)| *id != self.id);
+                if entry.subscribers.is_empty() {
+                    entry.unused_since = Some(js_sys::Date::now());
+                    // nobody is waiting for the answer any more
+                    if let Some(token) = entry.fetching.take() {
+                        token.cancel();
+                    }
+                }
+            }
+        });
+    }
+}
+
+// `on_change` runs after every change of the entry; fetches when there is no fresh result
+pub(crate) fn subscribe(
+    key: &QueryKey,
+    options: QueryOptions,
+    fetch: Fetch,
+    on_change: Rc<dyn Fn()>,
+) -> QuerySubscription {
+    gc();
+    ensure_listener();
+    let id = NEXT_ID.with(|x| {
+        x.set(x.get() + 1);
+        x.get()
+    });
+    let stale = CACHE.with(|x| {
+        let mut cache = x.borrow_mut();
+        let entry = cache
+            .entry(key.clone())
+            .or_insert_with(|| Entry::new(options));
+        entry.options = options;
+        entry.fetch = Some(fetch);
+        entry.unused_since = None;
+        entry.subscribers.push((id, on_change));
+        entry.is_stale(js_sys::Date::now())
+    });
+    if stale {
+        start(key);
+    }
+    QuerySubscription {
+        key: key.clone(),
+        id,
+    }
+}
+
+pub(crate) fn snapshot<T: 'static>(key: &QueryKey) -> Snapshot<T> {
+    CACHE.with(|x| match x.borrow().get(key) {
+        Some(entry) => Snapshot {
+            data: entry.data.clone().and_then(|v| v.downcast::<T>().ok()),
+            error: entry.error.clone(),
+            fetching: entry.fetching.is_some(),
+        },
+        None => Snapshot {
+            data: None,
+            error: None,
+            fetching: false,
+        },
+    })
+}
+
+// requests shared by every subscriber: a second caller joins the one in flight
+fn start(key: &QueryKey) {
+    let started = CACHE.with(|x| x.borrow_mut().get_mut(key)?.begin());
+    let (fetch, token) = match started {
+        Some(v) => v,
+        None => return,
+    };
+    notify(key);
+    let key = key.clone();
+    spawn_local(async move {
+        let res = fetch(RequestOptions::new().cancel(&token)).await;
+        finish(&key, &token, res);
+    });
+}
+
+fn finish(key: &QueryKey, token: &CancelToken, res: ApiResult<Rc<dyn Any>>) {
+    let current = CACHE.with(|x| {
+        let mut cache = x.borrow_mut();
+        let entry = match cache.get_mut(key) {
+            Some(v) if v.fetching.as_ref() == Some(token) => v,
+            // cancelled, invalidated or cleared in the meantime
+            _ => return false,
+        };
+        entry.fetching = None;
+        match res {
+            Ok(data) => {
+                entry.data = Some(data);
+                entry.error = None;
+                entry.updated_at = js_sys::Date::now();
+            }
+            Err(err) if err.cancelled => {}
+            // the last good result stays next to the error
+            Err(err) => entry.error = Some(err),
+        }
+        true
+    });
+    if current {
+        notify(key);
+    }
+}
+
+// asks the server again for `key`, even when the result is fresh
+pub fn refetch(key: &QueryKey) {
+    invalidate_matching(|k| k == key);
+}
+
+// marks every entry under `prefix` stale and refetches the ones on screen, e.g. after an update
+pub fn invalidate(prefix: &QueryKey) {
+    invalidate_matching(|k| k.starts_with(prefix));
+}
+
+fn invalidate_matching(matches: impl Fn(&QueryKey) -> bool) {
+    let keys = CACHE.with(|x| mark_stale(&mut x.borrow_mut(), matches));
+    for key in keys {
+        start(&key);
+    }
+}
+
+// the keys of the marked entries that are on screen, to refetch
+fn mark_stale(
+    cache: &mut HashMap<QueryKey, Entry>,
+    matches: impl Fn(&QueryKey) -> bool,
+) -> Vec<QueryKey> {
+    let mut keys = vec![];
+    for (key, entry) in cache.iter_mut().filter(|(k, _)| matches(k)) {
+        entry.updated_at = 0.0;
+        // whatever is in flight may predate the change
+        if let Some(token) = entry.fetching.take() {
+            token.cancel();
+        }
+        if !entry.subscribers.is_empty() {
+            keys.push(key.clone());
+        }
+    }
+    keys
+}
+
+// cached results as they were before `patch`, to put back when the change is refused
+#[must_use]
+pub struct Optimistic(Vec<(QueryKey, Option<Rc<dyn Any>>)>);
+
+impl Optimistic {
+    // restores the results and refetches them, the server has the final say
+    pub fn rollback(self) {
+        let keys = CACHE.with(|x| restore(&mut x.borrow_mut(), self.0));
+        for key in &keys {
+            notify(key);
+        }
+        invalidate_matching(|k| keys.contains(k));
+    }
+
+    // keeps the patched results, e.g. when they came from the server in the first place
+    pub fn commit(self) {}
+}
+
+// rewrites every cached `T` under `prefix` at once, before the server confirmed the change;
+// answers in flight are dropped so they can not bring the old value back
+pub fn patch<T: 'static>(prefix: &QueryKey, f: impl Fn(&T) -> T) -> Optimistic {
+    let saved = CACHE.with(|x| rewrite(&mut x.borrow_mut(), prefix, f));
+    for (key, _) in &saved {
+        notify(key);
+    }
+    Optimistic(saved)
+}
+
+// `patch` for a change the server already made, e.g. one pushed over the socket
+pub fn set_data<T: 'static>(prefix: &QueryKey, f: impl Fn(&T) -> T) {
+    patch(prefix, f).commit();
+}
+
+// the results as they were before
+fn rewrite<T: 'static>(
+    cache: &mut HashMap<QueryKey, Entry>,
+    prefix: &QueryKey,
+    f: impl Fn(&T) -> T,
+) -> Vec<(QueryKey, Option<Rc<dyn Any>>)> {
+    let mut saved = vec![];
+    for (key, entry) in cache.iter_mut().filter(|(k, _)| k.starts_with(prefix)) {
+        let value = match entry.data.clone().and_then(|v| v.downcast::<T>().ok()) {
+            Some(v) => v,
+            None => continue,
+        };
+        if let Some(token) = entry.fetching.take() {
+            token.cancel();
+        }
+        saved.push((key.clone(), entry.data.replace(Rc::new(f(&value)))));
+    }
+    saved
+}
+
+// the keys that were put back; entries cleared in the meantime stay cleared
+fn restore(
+    cache: &mut HashMap<QueryKey, Entry>,
+    saved: Vec<(QueryKey, Option<Rc<dyn Any>>)>,
+) -> Vec<QueryKey> {
+    let mut keys = vec![];
+    for (key, data) in saved {
+        if let Some(entry) = cache.get_mut(&key) {
+            entry.data = data;
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+// forgets every result, e.g. when the user signs out
+pub fn clear() {
+    let keys = CACHE.with(|x| {
+        let mut cache = x.borrow_mut();
+        cache.retain(|_, entry| {
+            if let Some(token) = entry.fetching.take() {
+                token.cancel();
+            }
+            !entry.subscribers.is_empty()
+        });
+        for entry in cache.values_mut() {
+            entry.data = None;
+            entry.error = None;
+            entry.updated_at = 0.0;
+        }
+        cache.keys().cloned().collect::<Vec<_>>()
+    });
+    for key in keys {
+        notify(&key);
+    }
+}
+
+fn notify(key: &QueryKey) {
+    // handlers run outside the borrow, they re-render and read the cache
+    let handlers = CACHE.with(|x| {
+        x.borrow()
+            .get(key)
+            .map(|entry| {
+                entry
+                    .subscribers
+                    .iter()
+                    .map(|(_, f)| f.clone())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    });
+    for handler in handlers {
+        handler();
+    }
+}
+
+// drops results nobody used for longer than their `cache_ms`
+fn gc() {
+    let now = js_sys::Date::now();
+    CACHE.with(|x| x.borrow_mut().retain(|_, entry| !entry.is_expired(now)));
+}
+
+// coming back to the tab refetches the stale results on screen
+fn ensure_listener() {
+    LISTENER.with(|x| {
+        if x.borrow().is_some() {
+            return;
+        }
+        let window = match web_sys::window() {
+            Some(v) => v,
+            None => return,
+        };
+        let listener = EventListener::new(&window, "focus", |_| {
+            let now = js_sys::Date::now();
+            let keys = CACHE.with(|x| {
+                x.borrow()
+                    .iter()
+                    .filter(|(_, e)| !e.subscribers.is_empty() && e.is_stale(now))
+                    .map(|(k, _)| k.clone())
+                    .collect::<Vec<_>>()
+            });
+            for key in keys {
+                start(&key);
+            }
+        });
+        *x.borrow_mut() = Some(listener);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(path: &str) -> QueryKey {
+        let mut parts = path.split('/');
+        let first = QueryKey::new(parts.next().unwrap());
+        parts.fold(first, |key, part| key.with(part))
+    }
+
+    fn entry<T: 'static>(data: T, on_screen: bool) -> Entry {
+        let mut entry = Entry::new(QueryOptions::default());
+        entry.data = Some(Rc::new(data));
+        entry.updated_at = 1_000.0;
+        if on_screen {
+            entry.subscribers.push((1, Rc::new(|| {})));
+        }
+        entry
+    }
+
+    fn value(cache: &HashMap<QueryKey, Entry>, path: &str) -> Option<i64> {
+        let data = cache[&key(path)].data.clone()?;
+        data.downcast::<i64>().ok().map(|x| *x)
+    }
+
+    #[test]
+    fn key_prefix() {
+        let detail = key("user/detail/12");
+        assert!(detail.starts_with(&key("user")));
+        assert!(detail.starts_with(&key("user/detail")));
+        assert!(detail.starts_with(&detail));
+        // whole parts only
+        assert!(!detail.starts_with(&key("user/detail/1")));
+        assert!(!detail.starts_with(&key("users")));
+        assert!(!key("user").starts_with(&detail));
+        assert_eq!(detail.to_string(), "user/detail/12");
+    }
+
+    #[test]
+    fn invalidate_by_prefix() {
+        let mut cache = HashMap::new();
+        let token = CancelToken::new();
+        let mut detail = entry(1_i64, true);
+        detail.fetching = Some(token.clone());
+        cache.insert(key("user/detail/1"), detail);
+        cache.insert(key("user/list"), entry(2_i64, false));
+        cache.insert(key("role/list"), entry(3_i64, true));
+
+        let prefix = key("user");
+        let refetch = mark_stale(&mut cache, |k| k.starts_with(&prefix));
+        // only what is on screen is asked for again, the rest once it is used
+        assert_eq!(refetch, vec![key("user/detail/1")]);
+        assert_eq!(cache[&key("user/detail/1")].updated_at, 0.0);
+        assert_eq!(cache[&key("user/list")].updated_at, 0.0);
+        assert_eq!(cache[&key("role/list")].updated_at, 1_000.0);
+        // an answer in flight may predate the change
+        assert!(token.is_cancelled());
+        assert!(cache[&key("user/detail/1")].fetching.is_none());
+        // the old result stays on screen until the new one is in
+        assert_eq!(value(&cache, "user/detail/1"), Some(1));
+    }
+
+    #[test]
+    fn fetches_in_flight_are_shared() {
+        let mut entry = Entry::new(QueryOptions::default());
+        assert!(entry.begin().is_none(), "nothing to fetch with yet");
+
+        let fetch: Fetch = Rc::new(
+            |_: RequestOptions| -> LocalBoxFuture<'static, ApiResult<Rc<dyn Any>>> {
+                Box::pin(async { Ok(Rc::new(()) as Rc<dyn Any>) })
+            },
+        );
+        entry.fetch = Some(fetch);
+        let (_, token) = entry.begin().unwrap();
+        assert!(entry.begin().is_none());
+        assert!(entry.begin().is_none());
+        assert!(entry.fetching.as_ref() == Some(&token));
+
+        // once answered, the next one starts a new request
+        entry.fetching = None;
+        let (_, next) = entry.begin().unwrap();
+        assert!(next != token);
+    }
+
+    #[test]
+    fn stale_and_expired() {
+        let mut entry = Entry::new(QueryOptions::new().stale(100).cache(50));
+        entry.updated_at = 1_000.0;
+        assert!(!entry.is_stale(1_099.0));
+        assert!(entry.is_stale(1_100.0));
+
+        assert!(!entry.is_expired(f64::MAX), "still in use");
+        entry.unused_since = Some(2_000.0);
+        assert!(!entry.is_expired(2_049.0));
+        assert!(entry.is_expired(2_050.0));
+    }
+
+    #[test]
+    fn patch_and_rollback() {
+        let mut cache = HashMap::new();
+        let token = CancelToken::new();
+        let mut first = entry(1_i64, true);
+        first.fetching = Some(token.clone());
+        cache.insert(key("user/list/1"), first);
+        cache.insert(key("user/list/2"), entry(2_i64, true));
+        cache.insert(key("user/detail/1"), entry("not a page", true));
+        cache.insert(key("role/list"), entry(5_i64, true));
+
+        let saved = rewrite(&mut cache, &key("user"), |x: &i64| x * 10);
+        assert_eq!(saved.len(), 2);
+        assert_eq!(value(&cache, "user/list/1"), Some(10));
+        assert_eq!(value(&cache, "user/list/2"), Some(20));
+        assert_eq!(value(&cache, "role/list"), Some(5));
+        // other types under the prefix are left alone
+        assert!(cache[&key("user/detail/1")]
+            .data
+            .clone()
+            .unwrap()
+            .downcast::<&str>()
+            .is_ok());
+        // so the old answer can not overwrite the patch
+        assert!(token.is_cancelled());
+
+        // an entry forgotten in the meantime is not brought back
+        cache.remove(&key("user/list/2"));
+        let restored = restore(&mut cache, saved);
+        assert_eq!(restored, vec![key("user/list/1")]);
+        assert_eq!(value(&cache, "user/list/1"), Some(1));
+        assert!(!cache.contains_key(&key("user/list/2")));
+    }
+}
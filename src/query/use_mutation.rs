@@ -0,0 +1,178 @@
+use super::{Optimistic, QueryKey};
+use crate::util::api_error::{ApiError, ApiResult};
+use crate::util::middleware::{self, CancelToken};
+use futures::future::{self, Either, LocalBoxFuture};
+use gloo::events::{EventListener, EventListenerOptions};
+use std::cell::RefCell;
+use std::future::Future;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::BeforeUnloadEvent;
+use yew::prelude::*;
+
+thread_local! {
+    // one token per deferred run still in its undo window, cancelled to send it at once
+    static DEFERRED: RefCell<Vec<CancelToken>> = const { RefCell::new(Vec::new()) };
+    static LISTENER: RefCell<Option<EventListener>> = const { RefCell::new(None) };
+}
+
+pub struct UseMutationHandle<A, T> {
+    mutate: Rc<dyn Fn(A) -> LocalBoxFuture<'static, ApiResult<T>>>,
+    invalidates: Rc<Vec<QueryKey>>,
+    pending: Rc<RefCell<usize>>,
+    force_update: UseForceUpdateHandle,
+}
+
+impl<A, T> Clone for UseMutationHandle<A, T> {
+    fn clone(&self) -> Self {
+        UseMutationHandle {
+            mutate: self.mutate.clone(),
+            invalidates: self.invalidates.clone(),
+            pending: self.pending.clone(),
+            force_update: self.force_update.clone(),
+        }
+    }
+}
+
+impl<A: 'static, T: 'static> UseMutationHandle<A, T> {
+    // sends the change, invalidates the keys on success, then hands the result to `done`
+    pub fn run(&self, arg: A, done: impl FnOnce(ApiResult<T>) + 'static) {
+        self.spawn(arg, None, None, done);
+    }
+
+    // like `run`, with the cache already showing the change; rolled back if it fails
+    pub fn run_optimistic(
+        &self,
+        arg: A,
+        optimistic: Optimistic,
+        done: impl FnOnce(ApiResult<T>) + 'static,
+    ) {
+        self.spawn(arg, Some(optimistic), None, done);
+    }
+
+    // like `run_optimistic`, but only sent after `ms`; cancelling the returned token
+    // before that rolls the change back without ever sending it, and `done` gets a cancelled error.
+    // leaving the page within the window sends it at once, see `flush_on_unload`
+    pub fn run_deferred(
+        &self,
+        arg: A,
+        optimistic: Optimistic,
+        ms: u32,
+        done: impl FnOnce(ApiResult<T>) + 'static,
+    ) -> CancelToken {
+        let undo = CancelToken::new();
+        self.spawn(arg, Some(optimistic), Some((ms, undo.clone())), done);
+        undo
+    }
+
+    fn spawn(
+        &self,
+        arg: A,
+        optimistic: Option<Optimistic>,
+        deferred: Option<(u32, CancelToken)>,
+        done: impl FnOnce(ApiResult<T>) + 'static,
+    ) {
+        let mutate = self.mutate.clone();
+        let invalidates = self.invalidates.clone();
+        let pending = self.pending.clone();
+        let force_update = self.force_update.clone();
+        *pending.borrow_mut() += 1;
+        force_update.force_update();
+        spawn_local(async move {
+            let undone = match deferred {
+                Some((ms, undo)) => {
+                    let flush = CancelToken::new();
+                    flush_on_unload(&flush);
+                    let wait = future::select(
+                        Box::pin(undo.cancelled()),
+                        future::select(
+                            Box::pin(middleware::sleep(ms)),
+                            Box::pin(flush.cancelled()),
+                        ),
+                    );
+                    let undone = matches!(wait.await, Either::Left(_));
+                    DEFERRED.with(|x| x.borrow_mut().retain(|t| *t != flush));
+                    undone
+                }
+                None => false,
+            };
+            let res = if undone {
+                Err(ApiError::cancelled())
+            } else {
+                mutate(arg).await
+            };
+            *pending.borrow_mut() -= 1;
+            match &res {
+                Ok(_) => {
+                    for key in invalidates.iter() {
+                        super::invalidate(key);
+                    }
+                }
+                Err(_) => {
+                    if let Some(optimistic) = optimistic {
+                        optimistic.rollback();
+                    }
+                }
+            }
+            done(res);
+            force_update.force_update();
+        });
+    }
+
+    // a run is in flight, or waiting out its undo window
+    pub fn pending(&self) -> bool {
+        *self.pending.borrow() > 0
+    }
+}
+
+// a change on the server; the queries under `invalidates` are refetched once it went through.
+// it is not cancelled on unmount, a change that was sent should still reach the cache
+#[hook]
+pub fn use_mutation<A, T, F, Fut>(invalidates: Vec<QueryKey>, mutate: F) -> UseMutationHandle<A, T>
+where
+    A: 'static,
+    T: 'static,
+    F: Fn(A) -> Fut + 'static,
+    Fut: Future<Output = ApiResult<T>> + 'static,
+{
+    let pending = use_mut_ref(|| 0);
+    let force_update = use_force_update();
+    UseMutationHandle {
+        mutate: Rc::new(move |arg| Box::pin(mutate(arg))),
+        invalidates: Rc::new(invalidates),
+        pending,
+        force_update,
+    }
+}
+
+// a deferred change the page already shows as done must not be lost with the page: leaving
+// sends it right away and asks to stay, which gives the request the time to go out
+fn flush_on_unload(flush: &CancelToken) {
+    DEFERRED.with(|x| x.borrow_mut().push(flush.clone()));
+    LISTENER.with(|x| {
+        if x.borrow().is_some() {
+            return;
+        }
+        let window = match web_sys::window() {
+            Some(v) => v,
+            None => return,
+        };
+        let options = EventListenerOptions::enable_prevent_default();
+        let listener = EventListener::new_with_options(&window, "beforeunload", options, |event| {
+            let pending = DEFERRED.with(|x| std::mem::take(&mut *x.borrow_mut()));
+            if pending.is_empty() {
+                return;
+            }
+            for token in pending {
+                token.cancel();
+            }
+            event.prevent_default();
+            if let Some(event) = event.dyn_ref::<BeforeUnloadEvent>() {
+                // older browsers only ask when this is set
+                event.set_return_value("");
+            }
+        });
+        *x.borrow_mut() = Some(listener);
+    });
+}
@@ -0,0 +1,67 @@
+use super::{Fetch, QueryKey, QueryOptions};
+use crate::util::api_error::{ApiError, ApiResult};
+use crate::util::request::RequestOptions;
+use std::any::Any;
+use std::future::Future;
+use std::rc::Rc;
+use yew::prelude::*;
+
+pub struct QueryState<T> {
+    // the last good result, kept while refetching and after a failed refetch
+    pub data: Option<Rc<T>>,
+    pub error: Option<ApiError>,
+    // nothing to show yet
+    pub loading: bool,
+    // a request is in flight, possibly in the background
+    pub fetching: bool,
+    pub refetch: Callback<()>,
+}
+
+#[hook]
+pub fn use_query<T, F, Fut>(key: QueryKey, fetcher: F) -> QueryState<T>
+where
+    T: 'static,
+    F: Fn(RequestOptions) -> Fut + 'static,
+    Fut: Future<Output = ApiResult<T>> + 'static,
+{
+    use_query_with(key, QueryOptions::default(), fetcher)
+}
+
+// the cached result of `key`, served at once and refreshed in the background once stale;
+// `fetcher` must send the options along so the request is dropped when nobody needs it
+#[hook]
+pub fn use_query_with<T, F, Fut>(key: QueryKey, options: QueryOptions, fetcher: F) -> QueryState<T>
+where
+    T: 'static,
+    F: Fn(RequestOptions) -> Fut + 'static,
+    Fut: Future<Output = ApiResult<T>> + 'static,
+{
+    let force_update = use_force_update();
+    // the fetcher is taken together with the key, whatever it reads belongs in the key
+    use_effect_with(key.clone(), move |key| {
+        let fetch: Fetch = Rc::new(move |opts| {
+            let res = fetcher(opts);
+            Box::pin(async move { res.await.map(|v| Rc::new(v) as Rc<dyn Any>) })
+        });
+        let subscription = super::subscribe(
+            key,
+            options,
+            fetch,
+            Rc::new(move || force_update.force_update()),
+        );
+        move || drop(subscription)
+    });
+
+    let refetch = {
+        let key = key.clone();
+        Callback::from(move |_| super::refetch(&key))
+    };
+    let snapshot = super::snapshot::<T>(&key);
+    QueryState {
+        loading: snapshot.data.is_none() && snapshot.error.is_none(),
+        data: snapshot.data,
+        error: snapshot.error,
+        fetching: snapshot.fetching,
+        refetch,
+    }
+}
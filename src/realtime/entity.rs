@@ -0,0 +1,157 @@
+use super::ConnectionState;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+const ENTITY_CHANGED_PRE: &str = "entity_changed:";
+const SUBSCRIBE_PRE: &str = "subscribe:";
+const UNSUBSCRIBE_PRE: &str = "unsubscribe:";
+
+thread_local! {
+    static SUBSCRIBERS: RefCell<Vec<Subscriber>> = const { RefCell::new(Vec::new()) };
+    // the frame and connection state listeners on the service, set up by the first subscriber
+    static LISTENERS: RefCell<Option<(super::Subscription, super::Subscription)>> =
+        const { RefCell::new(None) };
+    static NEXT_ID: Cell<usize> = const { Cell::new(0) };
+}
+
+// what the server announces changes of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Entity {
+    User,
+    // a role itself, or which users hold it
+    Role,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Created,
+    Updated,
+    Deleted,
+}
+
+// `entity_changed:{"entity":"user","kind":"updated","id":12,"data":{..}}`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct EntityChange {
+    pub entity: Entity,
+    pub kind: ChangeKind,
+    pub id: i64,
+    // the new state, when the server sends it along
+    #[serde(default)]
+    pub data: Option<Value>,
+}
+
+impl EntityChange {
+    pub fn data<T: DeserializeOwned>(&self) -> Option<T> {
+        serde_json::from_value(self.data.clone()?).ok()
+    }
+}
+
+#[derive(Serialize)]
+struct Topic {
+    entity: Entity,
+}
+
+struct Subscriber {
+    id: usize,
+    entity: Entity,
+    handler: Rc<dyn Fn(&EntityChange)>,
+}
+
+// keeps the subscription alive until dropped
+pub struct EntitySubscription(usize);
+
+impl Drop for EntitySubscription {
+    fn drop(&mut self) {
+        let entity = SUBSCRIBERS.with(|x| {
+            let mut subscribers = x.borrow_mut();
+            let entity = subscribers.iter().find(|s| s.id == self.0)?.entity;
+            subscribers.retain(|s| s.id != self.0);
+            Some(entity)
+        });
+        if let Some(entity) = entity.filter(|x| !is_subscribed(*x)) {
+            announce(UNSUBSCRIBE_PRE, entity);
+        }
+    }
+}
+
+// `f` runs for every change of `entity` the server pushes, made in any tab or by anybody
+pub fn subscribe(entity: Entity, f: impl Fn(&EntityChange) + 'static) -> EntitySubscription {
+    ensure_listeners();
+    let first = !is_subscribed(entity);
+    let id = NEXT_ID.with(|x| {
+        x.set(x.get() + 1);
+        x.get()
+    });
+    SUBSCRIBERS.with(|x| {
+        x.borrow_mut().push(Subscriber {
+            id,
+            entity,
+            handler: Rc::new(f),
+        })
+    });
+    // the server only pushes the entities somebody asked for
+    if first {
+        announce(SUBSCRIBE_PRE, entity);
+    }
+    EntitySubscription(id)
+}
+
+fn is_subscribed(entity: Entity) -> bool {
+    SUBSCRIBERS.with(|x| x.borrow().iter().any(|s| s.entity == entity))
+}
+
+// frames sent while disconnected would pile up, the topics are announced on every connect instead
+fn announce(prefix: &str, entity: Entity) {
+    if super::state() == ConnectionState::Connected {
+        super::send(prefix, &Topic { entity });
+    }
+}
+
+// a fresh socket knows no topics yet
+fn announce_all() {
+    let mut entities = vec![];
+    SUBSCRIBERS.with(|x| {
+        for s in x.borrow().iter() {
+            if !entities.contains(&s.entity) {
+                entities.push(s.entity);
+            }
+        }
+    });
+    for entity in entities {
+        announce(SUBSCRIBE_PRE, entity);
+    }
+}
+
+fn ensure_listeners() {
+    LISTENERS.with(|x| {
+        if x.borrow().is_some() {
+            return;
+        }
+        let frames = super::subscribe(ENTITY_CHANGED_PRE, dispatch);
+        let state = super::subscribe_state(|state| {
+            if state == ConnectionState::Connected {
+                announce_all();
+            }
+        });
+        *x.borrow_mut() = Some((frames, state));
+    });
+}
+
+fn dispatch(change: EntityChange) {
+    // handlers run outside the borrow, they may subscribe or unsubscribe
+    let handlers = SUBSCRIBERS.with(|x| {
+        x.borrow()
+            .iter()
+            .filter(|s| s.entity == change.entity)
+            .map(|s| s.handler.clone())
+            .collect::<Vec<_>>()
+    });
+    for handler in handlers {
+        handler(&change);
+    }
+}
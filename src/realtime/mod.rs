@@ -0,0 +1,439 @@
+pub mod entity;
+pub mod presence;
+pub mod use_connection_state;
+pub mod use_entity_change;
+pub mod use_presence;
+
+use crate::storage::{self, keys};
+use crate::util::api_error::{ApiError, ApiResult};
+use crate::util::common;
+use crate::util::middleware;
+use crate::util::request::{Host, RequestOptions};
+use futures::channel::mpsc::{self, UnboundedSender};
+use futures::{future, SinkExt, StreamExt};
+use gloo_net::websocket::{futures::WebSocket, Message, State, WebSocketError};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+
+pub use entity::{ChangeKind, Entity, EntityChange};
+
+const SESSION_REVOKED_PRE: &str = "session_revoked:";
+// the server wants a fresh ticket, the token behind the socket ran out
+const AUTH_EXPIRED_PRE: &str = "auth_expired:";
+const AUTH_PRE: &str = "auth:";
+
+thread_local! {
+    static SERVICE: RefCell<RealtimeService> = RefCell::new(RealtimeService::default());
+    static SUBSCRIBERS: RefCell<Vec<Subscriber>> = const { RefCell::new(Vec::new()) };
+    // follows the token, so signing in or out in any tab connects or disconnects
+    static TOKEN_SUBSCRIPTION: RefCell<Option<storage::Subscription>> = const { RefCell::new(None) };
+    static NEXT_ID: Cell<usize> = const { Cell::new(0) };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConnectionState {
+    // signed out, or not started yet
+    Disconnected,
+    Connecting,
+    Connected,
+    // the socket dropped, another try is scheduled
+    Reconnecting,
+}
+
+// the one socket of this session; see `connect`, `subscribe` and `send`
+struct RealtimeService {
+    state: ConnectionState,
+    // bumped by every connect and disconnect, so the tasks of an older socket stop
+    generation: usize,
+    // feeds the writer task of the open socket
+    tx: Option<UnboundedSender<Message>>,
+    // sent once connected
+    outbox: Vec<String>,
+    // failed connects in a row, for the backoff
+    attempt: u32,
+    // the token the open socket is authenticated with, compared after every refresh
+    token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct WsTicket {
+    ticket: String,
+}
+
+#[derive(Serialize)]
+struct AuthReq<'a> {
+    ticket: &'a str,
+}
+
+impl Default for RealtimeService {
+    fn default() -> Self {
+        RealtimeService {
+            state: ConnectionState::Disconnected,
+            generation: 0,
+            tx: None,
+            outbox: vec![],
+            attempt: 0,
+            token: None,
+        }
+    }
+}
+
+enum Listener {
+    // every text frame, or only those starting with the prefix, which is cut off
+    Frame(Option<&'static str>, Rc<dyn Fn(&str)>),
+    State(Rc<dyn Fn(ConnectionState)>),
+}
+
+struct Subscriber {
+    id: usize,
+    listener: Listener,
+}
+
+// keeps the subscription alive until dropped
+pub struct Subscription(usize);
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        SUBSCRIBERS.with(|x| x.borrow_mut().retain(|s| s.id != self.0));
+    }
+}
+
+fn add(listener: Listener) -> Subscription {
+    let id = NEXT_ID.with(|x| {
+        x.set(x.get() + 1);
+        x.get()
+    });
+    SUBSCRIBERS.with(|x| x.borrow_mut().push(Subscriber { id, listener }));
+    Subscription(id)
+}
+
+// `f` gets the json after `prefix` of every matching frame, e.g. `entity_changed:{..}`
+pub fn subscribe<T>(prefix: &'static str, f: impl Fn(T) + 'static) -> Subscription
+where
+    T: DeserializeOwned + 'static,
+{
+    add(Listener::Frame(
+        Some(prefix),
+        Rc::new(move |payload| match serde_json::from_str::<T>(payload) {
+            Ok(v) => f(v),
+            Err(err) => log::warn!("bad {} frame {}: {}", prefix, payload, err),
+        }),
+    ))
+}
+
+// `f` gets every text frame as it is
+pub fn subscribe_text(f: impl Fn(&str) + 'static) -> Subscription {
+    add(Listener::Frame(None, Rc::new(f)))
+}
+
+// `f` runs after every change of the connection state
+pub fn subscribe_state(f: impl Fn(ConnectionState) + 'static) -> Subscription {
+    add(Listener::State(Rc::new(f)))
+}
+
+pub fn state() -> ConnectionState {
+    SERVICE.with(|x| x.borrow().state)
+}
+
+// `prefix` followed by `value` as json
+pub fn send<T: Serialize>(prefix: &str, value: &T) {
+    match serde_json::to_string(value) {
+        Ok(json) => send_text(format!("{prefix}{json}")),
+        Err(err) => log::error!("serialize {} frame failed: {}", prefix, err),
+    }
+}
+
+// queued while the socket is not open yet, dropped on disconnect
+pub fn send_text(text: String) {
+    SERVICE.with(|x| {
+        let mut service = x.borrow_mut();
+        match service.state {
+            ConnectionState::Connected => {
+                if let Some(tx) = &service.tx {
+                    tx.unbounded_send(Message::Text(text)).unwrap_or_default();
+                }
+            }
+            ConnectionState::Disconnected => log::warn!("not connected, frame dropped"),
+            _ => service.outbox.push(text),
+        }
+    });
+}
+
+fn set_state(state: ConnectionState) {
+    let changed = SERVICE.with(|x| {
+        let mut service = x.borrow_mut();
+        let changed = service.state != state;
+        service.state = state;
+        changed
+    });
+    if !changed {
+        return;
+    }
+    // handlers run outside the borrow, they may send or subscribe
+    let handlers = SUBSCRIBERS.with(|x| {
+        x.borrow()
+            .iter()
+            .filter_map(|s| match &s.listener {
+                Listener::State(f) => Some(f.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    });
+    for handler in handlers {
+        handler(state);
+    }
+}
+
+fn dispatch(frame: &str) {
+    if frame.starts_with(SESSION_REVOKED_PRE) {
+        log::info!("current session was revoked");
+        // removes the token, which disconnects
+        common::logout();
+        return;
+    }
+    if frame.starts_with(AUTH_EXPIRED_PRE) {
+        let generation = SERVICE.with(|x| x.borrow().generation);
+        reauth(generation);
+        return;
+    }
+    let handlers = SUBSCRIBERS.with(|x| {
+        x.borrow()
+            .iter()
+            .filter_map(|s| match &s.listener {
+                Listener::Frame(None, f) => Some((f.clone(), frame)),
+                Listener::Frame(Some(prefix), f) => {
+                    frame.strip_prefix(prefix).map(|rest| (f.clone(), rest))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    });
+    for (handler, payload) in handlers {
+        handler(payload);
+    }
+}
+
+fn is_current(generation: usize) -> bool {
+    SERVICE.with(|x| x.borrow().generation == generation)
+}
+
+// opens the socket unless it is open or on its way; does nothing while signed out
+pub fn connect() {
+    if common::get_token().is_err() {
+        return;
+    }
+    let generation = SERVICE.with(|x| {
+        let mut service = x.borrow_mut();
+        if service.state != ConnectionState::Disconnected {
+            return None;
+        }
+        service.generation += 1;
+        Some(service.generation)
+    });
+    if let Some(generation) = generation {
+        set_state(ConnectionState::Connecting);
+        open(generation);
+    }
+}
+
+pub fn disconnect() {
+    SERVICE.with(|x| {
+        let mut service = x.borrow_mut();
+        service.generation += 1;
+        service.attempt = 0;
+        service.outbox.clear();
+        service.token = None;
+        // ends the writer task, which closes the socket
+        service.tx = None;
+    });
+    set_state(ConnectionState::Disconnected);
+}
+
+// one-time and short-lived, so unlike the token it may show up in the url
+async fn ticket() -> ApiResult<String> {
+    let res = RequestOptions::new()
+        .post::<WsTicket, _>(Host::ApiBase, "/ws/ticket", &())
+        .await?;
+    res.data
+        .map(|x| x.ticket)
+        .ok_or_else(|| ApiError::network("no ws ticket in the response"))
+}
+
+fn open(generation: usize) {
+    spawn_local(async move {
+        let token = match common::get_token() {
+            Ok(v) => v,
+            Err(_) => return disconnect(),
+        };
+        let ticket = match ticket().await {
+            Ok(v) => v,
+            // signed out, the token subscription disconnects
+            Err(err) if err.status == 401 => return,
+            Err(err) => {
+                log::error!("get ws ticket error: {}", err);
+                return reconnect(generation);
+            }
+        };
+        if !is_current(generation) {
+            return;
+        }
+        let url = format!(
+            "{}/ws/ws?ticket={}",
+            Host::Ws,
+            js_sys::encode_uri_component(&ticket)
+        );
+        let mut ws = match WebSocket::open(&url) {
+            Ok(v) => v,
+            Err(err) => {
+                log::error!("open ws error: {:#?}", err);
+                return reconnect(generation);
+            }
+        };
+        let ready = future::poll_fn(|cx| SinkExt::<Message>::poll_ready_unpin(&mut ws, cx)).await;
+        if !is_current(generation) {
+            ws.close(None, None).unwrap_or_default();
+            return;
+        }
+        // ready also when the handshake failed and the socket is closed already
+        if let Err(err) = ready {
+            log::error!("connect ws error: {:#?}", err);
+            return reconnect(generation);
+        }
+        if ws.state() != State::Open {
+            return reconnect(generation);
+        }
+        let (mut writer, mut reader) = ws.split();
+        let (tx, mut rx) = mpsc::unbounded::<Message>();
+        spawn_local(async move {
+            while let Some(msg) = rx.next().await {
+                if let Err(err) = writer.send(msg).await {
+                    log::error!("message send error: {:#?}", err);
+                }
+            }
+            writer.close().await.unwrap_or_default();
+        });
+        let outbox = SERVICE.with(|x| {
+            let mut service = x.borrow_mut();
+            service.attempt = 0;
+            service.tx = Some(tx.clone());
+            service.token = Some(token);
+            std::mem::take(&mut service.outbox)
+        });
+        set_state(ConnectionState::Connected);
+        for text in outbox {
+            tx.unbounded_send(Message::Text(text)).unwrap_or_default();
+        }
+        drop(tx);
+        // the token may have been refreshed while the ticket was on its way
+        sync_token();
+
+        while let Some(msg) = reader.next().await {
+            if !is_current(generation) {
+                return;
+            }
+            match msg {
+                Ok(Message::Text(text)) => dispatch(&text),
+                Ok(Message::Bytes(_)) => {}
+                Err(WebSocketError::ConnectionClose(e)) => {
+                    log::info!("connection closed, close event: {:#?}", e);
+                    break;
+                }
+                Err(err @ WebSocketError::ConnectionError) => {
+                    log::error!("connection error: {:#?}", err);
+                    break;
+                }
+                Err(err) => log::error!("read error: {:#?}", err),
+            }
+        }
+        reconnect(generation);
+    });
+}
+
+// retries with backoff for as long as nobody disconnected in the meantime
+fn reconnect(generation: usize) {
+    let attempt = SERVICE.with(|x| {
+        let mut service = x.borrow_mut();
+        if service.generation != generation {
+            return None;
+        }
+        service.tx = None;
+        service.attempt += 1;
+        Some(service.attempt)
+    });
+    let attempt = match attempt {
+        Some(v) => v,
+        None => return,
+    };
+    set_state(ConnectionState::Reconnecting);
+    spawn_local(async move {
+        middleware::sleep(middleware::backoff_ms(attempt)).await;
+        if is_current(generation) {
+            open(generation);
+        }
+    });
+}
+
+// drops the socket and opens a new one right away
+fn restart() {
+    let generation = SERVICE.with(|x| {
+        let mut service = x.borrow_mut();
+        service.generation += 1;
+        service.token = None;
+        service.tx = None;
+        service.generation
+    });
+    set_state(ConnectionState::Reconnecting);
+    open(generation);
+}
+
+// authenticates the open socket again with a fresh ticket, so it survives a token refresh
+fn reauth(generation: usize) {
+    spawn_local(async move {
+        let res = ticket().await;
+        if !is_current(generation) || state() != ConnectionState::Connected {
+            return;
+        }
+        match res {
+            Ok(ticket) => send(AUTH_PRE, &AuthReq { ticket: &ticket }),
+            Err(err) if err.status == 401 => {}
+            Err(err) => {
+                log::warn!("ws re-auth failed, reconnecting: {}", err);
+                restart();
+            }
+        }
+    });
+}
+
+// re-authenticates when the token changed since the socket was opened
+fn sync_token() {
+    let token = common::get_token().ok();
+    let generation = SERVICE.with(|x| {
+        let mut service = x.borrow_mut();
+        if service.state != ConnectionState::Connected || token.is_none() || service.token == token
+        {
+            return None;
+        }
+        service.token = token;
+        Some(service.generation)
+    });
+    if let Some(generation) = generation {
+        reauth(generation);
+    }
+}
+
+// connects when signed in, and from then on follows sign in, refresh and sign out
+pub fn init() {
+    let subscription = keys::TOKEN.subscribe(|token| match token {
+        Some(_) => {
+            connect();
+            sync_token();
+        }
+        None => disconnect(),
+    });
+    TOKEN_SUBSCRIPTION.with(|x| *x.borrow_mut() = subscription);
+    presence::init();
+    connect();
+}
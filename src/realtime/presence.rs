@@ -0,0 +1,506 @@
+use super::ConnectionState;
+use crate::storage::{self, keys};
+use gloo::events::EventListener;
+use gloo::timers::callback::{Interval, Timeout};
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// `presence:{"status":"away"}` out, the same with a `session_id` in
+const PRESENCE_PRE: &str = "presence:";
+// `typing:{"room":"main"}` out, the same with `session_id` and `name` in
+const TYPING_PRE: &str = "typing:";
+// no input for this long counts as away
+const IDLE_MS: f64 = 5.0 * 60_000.0;
+const IDLE_CHECK_MS: u32 = 30_000;
+// at most one typing frame per room this often
+const TYPING_THROTTLE_MS: f64 = 3_000.0;
+// a typing mark goes away unless renewed in time, so it must outlast the throttle
+const TYPING_EXPIRE_MS: u32 = 6_000;
+const ACTIVITY_EVENTS: [&str; 5] = ["mousemove", "mousedown", "keydown", "touchstart", "scroll"];
+
+type Subscribers = Vec<(usize, Rc<dyn Fn()>)>;
+
+thread_local! {
+    static STATE: RefCell<PresenceState> = RefCell::new(PresenceState::default());
+    static SUBSCRIBERS: RefCell<Subscribers> = const { RefCell::new(Vec::new()) };
+    // the socket and storage subscriptions, activity listeners and idle timer; set up by `init`
+    static LISTENERS: RefCell<Option<Listeners>> = const { RefCell::new(None) };
+    static NEXT_ID: Cell<usize> = const { Cell::new(0) };
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Presence {
+    #[default]
+    Online,
+    Away,
+    Offline,
+}
+
+impl Presence {
+    pub fn all() -> [Presence; 3] {
+        [Presence::Online, Presence::Away, Presence::Offline]
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Presence::Online => "online",
+            Presence::Away => "away",
+            Presence::Offline => "offline",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Presence> {
+        Presence::all().into_iter().find(|x| x.code() == code)
+    }
+
+    // a colored dot, see `.presence-dot` in the styles
+    pub fn icon(&self) -> String {
+        format!("fa-solid fa-circle presence-dot is-{}", self.code())
+    }
+
+    // catalog key of the label
+    pub fn label(&self) -> &'static str {
+        match self {
+            Presence::Online => "presence.online",
+            Presence::Away => "presence.away",
+            Presence::Offline => "presence.offline",
+        }
+    }
+}
+
+struct PresenceState {
+    // picked in the header; `Online` still turns into away while idle
+    chosen: Presence,
+    last_activity: f64,
+    idle: bool,
+    // what the server was told last, `None` on a fresh socket
+    sent: Option<Presence>,
+    // by session id; only sessions that reported something
+    peers: HashMap<String, Presence>,
+    // room -> session id -> (name, ms since the epoch the mark expires)
+    typing: HashMap<String, HashMap<String, (String, f64)>>,
+    // room -> when our own typing was last sent
+    typing_sent: HashMap<String, f64>,
+}
+
+impl Default for PresenceState {
+    fn default() -> Self {
+        PresenceState {
+            chosen: Presence::Online,
+            last_activity: 0.0,
+            idle: false,
+            sent: None,
+            peers: HashMap::new(),
+            typing: HashMap::new(),
+            typing_sent: HashMap::new(),
+        }
+    }
+}
+
+impl PresenceState {
+    fn own(&self) -> Presence {
+        match self.chosen {
+            Presence::Online if self.idle => Presence::Away,
+            v => v,
+        }
+    }
+
+    // what to tell the server, `None` when it already knows
+    fn outgoing(&mut self, connected: bool) -> Option<Presence> {
+        let own = self.own();
+        if !connected || self.sent == Some(own) {
+            return None;
+        }
+        self.sent = Some(own);
+        Some(own)
+    }
+
+    // whether we were idle until now
+    fn active(&mut self, now: f64) -> bool {
+        self.last_activity = now;
+        std::mem::replace(&mut self.idle, false)
+    }
+
+    // whether we just went idle
+    fn check_idle(&mut self, now: f64) -> bool {
+        if self.idle || now - self.last_activity < IDLE_MS {
+            return false;
+        }
+        self.idle = true;
+        true
+    }
+
+    // whether a typing frame for `room` is due
+    fn typed(&mut self, room: &str, now: f64) -> bool {
+        let last = self.typing_sent.get(room).copied().unwrap_or_default();
+        if now - last < TYPING_THROTTLE_MS {
+            return false;
+        }
+        self.typing_sent.insert(room.to_string(), now);
+        true
+    }
+
+    // a mark lasts until renewed or expired
+    fn mark_typing(&mut self, change: TypingChanged, now: f64) {
+        let until = now + TYPING_EXPIRE_MS as f64;
+        self.typing
+            .entry(change.room)
+            .or_default()
+            .insert(change.session_id, (change.name, until));
+    }
+
+    fn typing(&self, room: &str, now: f64) -> Vec<String> {
+        let mut names = self
+            .typing
+            .get(room)
+            .map(|sessions| {
+                sessions
+                    .values()
+                    .filter(|(_, until)| *until > now)
+                    .map(|(name, _)| name.clone())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+
+    // whether any mark went away
+    fn expire_typing(&mut self, now: f64) -> bool {
+        let mut expired = false;
+        for sessions in self.typing.values_mut() {
+            let before = sessions.len();
+            sessions.retain(|_, (_, until)| *until > now);
+            expired |= sessions.len() != before;
+        }
+        self.typing.retain(|_, sessions| !sessions.is_empty());
+        expired
+    }
+}
+
+struct Listeners {
+    _frames: (super::Subscription, super::Subscription),
+    _state: super::Subscription,
+    _chosen: Option<storage::Subscription>,
+    _activity: Vec<EventListener>,
+    _idle: Interval,
+}
+
+#[derive(Serialize)]
+struct SetPresence {
+    status: Presence,
+}
+
+#[derive(Deserialize)]
+struct PresenceChanged {
+    session_id: String,
+    status: Presence,
+}
+
+#[derive(Serialize)]
+struct Typing<'a> {
+    room: &'a str,
+}
+
+#[derive(Deserialize)]
+struct TypingChanged {
+    room: String,
+    session_id: String,
+    name: String,
+}
+
+// keeps the subscription alive until dropped
+pub struct PresenceSubscription(usize);
+
+impl Drop for PresenceSubscription {
+    fn drop(&mut self) {
+        SUBSCRIBERS.with(|x| x.borrow_mut().retain(|(id, _)| *id != self.0));
+    }
+}
+
+// `f` runs after every change of anybody's presence or typing, our own included
+pub fn subscribe(f: impl Fn() + 'static) -> PresenceSubscription {
+    let id = NEXT_ID.with(|x| {
+        x.set(x.get() + 1);
+        x.get()
+    });
+    SUBSCRIBERS.with(|x| x.borrow_mut().push((id, Rc::new(f))));
+    PresenceSubscription(id)
+}
+
+fn notify() {
+    // handlers run outside the borrow, they re-render and read the state
+    let handlers = SUBSCRIBERS.with(|x| {
+        x.borrow()
+            .iter()
+            .map(|(_, f)| f.clone())
+            .collect::<Vec<_>>()
+    });
+    for handler in handlers {
+        handler();
+    }
+}
+
+// what the others see of us
+pub fn own() -> Presence {
+    STATE.with(|x| x.borrow().own())
+}
+
+pub fn chosen() -> Presence {
+    STATE.with(|x| x.borrow().chosen)
+}
+
+// remembered per user, so every tab and the next visit show the same
+pub fn set(presence: Presence) {
+    keys::PRESENCE.set(&presence.code().to_string());
+    STATE.with(|x| x.borrow_mut().chosen = presence);
+    update();
+}
+
+// a member of a room is online unless its session said otherwise; everybody is offline to us
+// while our own socket is down
+pub fn of(session_id: &str) -> Presence {
+    if super::state() != ConnectionState::Connected {
+        return Presence::Offline;
+    }
+    STATE.with(|x| {
+        x.borrow()
+            .peers
+            .get(session_id)
+            .copied()
+            .unwrap_or_default()
+    })
+}
+
+// the names of whoever is typing in `room` right now
+pub fn typing(room: &str) -> Vec<String> {
+    let now = js_sys::Date::now();
+    STATE.with(|x| x.borrow().typing(room, now))
+}
+
+// call on every keystroke in `room`, the frames are throttled here
+pub fn typed(room: &str) {
+    if super::state() != ConnectionState::Connected {
+        return;
+    }
+    let now = js_sys::Date::now();
+    let due = STATE.with(|x| x.borrow_mut().typed(room, now));
+    if due {
+        super::send(TYPING_PRE, &Typing { room });
+    }
+}
+
+// drops the mark of a session that sent its message, instead of waiting for it to expire
+pub fn stopped_typing(room: &str, session_id: &str) {
+    let removed = STATE.with(|x| {
+        x.borrow_mut()
+            .typing
+            .get_mut(room)
+            .and_then(|x| x.remove(session_id))
+            .is_some()
+    });
+    if removed {
+        notify();
+    }
+}
+
+// tells the server when what others see of us changed, e.g. after going idle
+fn update() {
+    let connected = super::state() == ConnectionState::Connected;
+    let changed = STATE.with(|x| x.borrow_mut().outgoing(connected));
+    if let Some(status) = changed {
+        super::send(PRESENCE_PRE, &SetPresence { status });
+    }
+    notify();
+}
+
+fn activity() {
+    let now = js_sys::Date::now();
+    let was_idle = STATE.with(|x| x.borrow_mut().active(now));
+    if was_idle {
+        update();
+    }
+}
+
+fn check_idle() {
+    let now = js_sys::Date::now();
+    let went_idle = STATE.with(|x| x.borrow_mut().check_idle(now));
+    if went_idle {
+        update();
+    }
+}
+
+// the pick of the signed in user
+fn stored() -> Presence {
+    keys::PRESENCE
+        .get()
+        .and_then(|x| Presence::from_code(&x))
+        .unwrap_or_default()
+}
+
+fn on_state(connection: ConnectionState) {
+    if connection == ConnectionState::Connected {
+        // a fresh socket does not know our presence yet, and may belong to another user
+        let chosen = stored();
+        STATE.with(|x| {
+            let mut state = x.borrow_mut();
+            state.sent = None;
+            state.chosen = chosen;
+        });
+        return update();
+    }
+    // whatever the others reported may be stale by the time we are back
+    STATE.with(|x| {
+        let mut state = x.borrow_mut();
+        state.sent = None;
+        state.peers.clear();
+        state.typing.clear();
+        state.typing_sent.clear();
+    });
+    notify();
+}
+
+fn on_presence(change: PresenceChanged) {
+    STATE.with(|x| {
+        let mut state = x.borrow_mut();
+        if change.status == Presence::Offline {
+            for sessions in state.typing.values_mut() {
+                sessions.remove(&change.session_id);
+            }
+        }
+        state.peers.insert(change.session_id, change.status);
+    });
+    notify();
+}
+
+fn on_typing(change: TypingChanged) {
+    let now = js_sys::Date::now();
+    STATE.with(|x| x.borrow_mut().mark_typing(change, now));
+    notify();
+    Timeout::new(TYPING_EXPIRE_MS, expire_typing).forget();
+}
+
+fn expire_typing() {
+    let now = js_sys::Date::now();
+    let expired = STATE.with(|x| x.borrow_mut().expire_typing(now));
+    if expired {
+        notify();
+    }
+}
+
+// restores the picked presence and starts watching for activity; once, from `super::init`
+pub fn init() {
+    let chosen = stored();
+    STATE.with(|x| {
+        let mut state = x.borrow_mut();
+        state.chosen = chosen;
+        state.last_activity = js_sys::Date::now();
+    });
+
+    let activity_listeners = web_sys::window()
+        .map(|window| {
+            ACTIVITY_EVENTS
+                .iter()
+                .map(|event| EventListener::new(&window, *event, |_| activity()))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    // picked in another tab
+    let chosen = keys::PRESENCE.subscribe(|v| {
+        if let Some(presence) = v.and_then(|x| Presence::from_code(&x)) {
+            STATE.with(|x| x.borrow_mut().chosen = presence);
+            update();
+        }
+    });
+    let listeners = Listeners {
+        _frames: (
+            super::subscribe(PRESENCE_PRE, on_presence),
+            super::subscribe(TYPING_PRE, on_typing),
+        ),
+        _state: super::subscribe_state(on_state),
+        _chosen: chosen,
+        _activity: activity_listeners,
+        _idle: Interval::new(IDLE_CHECK_MS, check_idle),
+    };
+    LISTENERS.with(|x| *x.borrow_mut() = Some(listeners));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typing(room: &str, session_id: &str, name: &str) -> TypingChanged {
+        TypingChanged {
+            room: room.to_string(),
+            session_id: session_id.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn typing_is_throttled_per_room() {
+        let mut state = PresenceState::default();
+        assert!(state.typed("main", 10_000.0));
+        assert!(!state.typed("main", 10_001.0));
+        assert!(!state.typed("main", 10_000.0 + TYPING_THROTTLE_MS - 1.0));
+        // other rooms have their own window
+        assert!(state.typed("ops", 10_001.0));
+        assert!(state.typed("main", 10_000.0 + TYPING_THROTTLE_MS));
+    }
+
+    #[test]
+    fn typing_marks_expire() {
+        let mut state = PresenceState::default();
+        state.mark_typing(typing("main", "s1", "Tom"), 0.0);
+        state.mark_typing(typing("main", "s2", "Ann"), 2_000.0);
+        let expire = TYPING_EXPIRE_MS as f64;
+        assert_eq!(state.typing("main", expire - 1.0), vec!["Ann", "Tom"]);
+        assert!(state.typing("ops", 0.0).is_empty());
+        // gone from the list at once, even before the timer cleaned up
+        assert_eq!(state.typing("main", expire), vec!["Ann"]);
+
+        assert!(state.expire_typing(expire));
+        assert!(!state.expire_typing(expire), "nothing left to expire");
+        // renewed marks last from the renewal
+        state.mark_typing(typing("main", "s2", "Ann"), expire);
+        assert_eq!(state.typing("main", 2_000.0 + expire), vec!["Ann"]);
+        assert!(state.expire_typing(2.0 * expire));
+        assert!(state.typing.is_empty());
+    }
+
+    #[test]
+    fn idle_turns_online_into_away() {
+        let mut state = PresenceState::default();
+        assert_eq!(state.outgoing(true), Some(Presence::Online));
+
+        assert!(!state.check_idle(IDLE_MS - 1.0));
+        assert!(state.check_idle(IDLE_MS));
+        assert!(!state.check_idle(IDLE_MS * 2.0), "already idle");
+        assert_eq!(state.own(), Presence::Away);
+        assert_eq!(state.outgoing(true), Some(Presence::Away));
+        assert_eq!(state.outgoing(true), None);
+
+        assert!(state.active(IDLE_MS * 2.0));
+        assert!(!state.active(IDLE_MS * 2.0));
+        assert_eq!(state.own(), Presence::Online);
+        assert_eq!(state.outgoing(true), Some(Presence::Online));
+        // idle again only after another full period
+        assert!(!state.check_idle(IDLE_MS * 3.0 - 1.0));
+    }
+
+    #[test]
+    fn idle_keeps_a_picked_presence() {
+        let mut state = PresenceState {
+            chosen: Presence::Offline,
+            ..Default::default()
+        };
+        assert!(state.check_idle(IDLE_MS));
+        assert_eq!(state.own(), Presence::Offline);
+        // nothing is sent while the socket is down
+        assert_eq!(state.outgoing(false), None);
+        assert_eq!(state.outgoing(true), Some(Presence::Offline));
+    }
+}
@@ -0,0 +1,17 @@
+use super::{ConnectionState, Subscription};
+use yew::prelude::*;
+
+// the state of the shared socket, re-rendering whenever it changes
+#[hook]
+pub fn use_connection_state() -> ConnectionState {
+    let force_update = use_force_update();
+    let subscription = use_mut_ref(|| None::<Subscription>);
+    use_effect_with((), move |_| {
+        *subscription.borrow_mut() =
+            Some(super::subscribe_state(move |_| force_update.force_update()));
+        move || {
+            subscription.borrow_mut().take();
+        }
+    });
+    super::state()
+}
@@ -0,0 +1,16 @@
+use super::entity::{self, Entity, EntityChange};
+use yew::prelude::*;
+
+// `onchange` runs for every pushed change of `entity` while the component is mounted,
+// e.g. to invalidate or patch the queries showing it
+#[hook]
+pub fn use_entity_change(entity: Entity, onchange: Callback<EntityChange>) {
+    // the latest callback, so it may read the current props
+    let latest = use_mut_ref(|| onchange.clone());
+    *latest.borrow_mut() = onchange;
+    use_effect_with(entity, move |entity| {
+        let subscription =
+            entity::subscribe(*entity, move |change| latest.borrow().emit(change.clone()));
+        move || drop(subscription)
+    });
+}
@@ -0,0 +1,17 @@
+use super::presence::{self, Presence, PresenceSubscription};
+use yew::prelude::*;
+
+// our own presence, re-rendering whenever anybody's presence or typing changes,
+// so `presence::of` and `presence::typing` can be read during render
+#[hook]
+pub fn use_presence() -> Presence {
+    let force_update = use_force_update();
+    let subscription = use_mut_ref(|| None::<PresenceSubscription>);
+    use_effect_with((), move |_| {
+        *subscription.borrow_mut() = Some(presence::subscribe(move || force_update.force_update()));
+        move || {
+            subscription.borrow_mut().take();
+        }
+    });
+    presence::own()
+}
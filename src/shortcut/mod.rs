@@ -0,0 +1,299 @@
+pub mod use_shortcut;
+
+use gloo::events::{EventListener, EventListenerOptions};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlElement, KeyboardEvent};
+
+thread_local! {
+    static REGISTRY: RefCell<Vec<Registered>> = const { RefCell::new(Vec::new()) };
+    static LISTENER: RefCell<Option<EventListener>> = const { RefCell::new(None) };
+    static NEXT_ID: Cell<usize> = const { Cell::new(0) };
+}
+
+// a parsed binding such as "Mod+K" or "?"; `Mod` is Cmd on macOS and Ctrl elsewhere
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyBinding {
+    key: String,
+    modifier: bool,
+    shift: bool,
+    alt: bool,
+}
+
+impl KeyBinding {
+    pub fn parse(binding: &str) -> KeyBinding {
+        let mut res = KeyBinding {
+            key: String::new(),
+            modifier: false,
+            shift: false,
+            alt: false,
+        };
+        // split on the last `+` only, so "Mod++" binds the plus key
+        let (mods, key) = match binding.rsplit_once('+') {
+            Some((mods, "")) => (mods.strip_suffix('+').unwrap_or(mods), "+"),
+            Some(v) => v,
+            None => ("", binding),
+        };
+        for m in mods.split('+').filter(|x| !x.is_empty()) {
+            match m.to_lowercase().as_str() {
+                "mod" | "ctrl" | "cmd" | "meta" => res.modifier = true,
+                "shift" => res.shift = true,
+                "alt" | "option" => res.alt = true,
+                v => log::warn!("unknown modifier {} in shortcut {}", v, binding),
+            }
+        }
+        res.key = key.to_lowercase();
+        res
+    }
+
+    fn matches(&self, e: &KeyboardEvent) -> bool {
+        self.matches_keys(
+            &e.key(),
+            e.ctrl_key() || e.meta_key(),
+            e.shift_key(),
+            e.alt_key(),
+        )
+    }
+
+    // `key` as in `KeyboardEvent.key`, `modifier` is ctrl or meta
+    fn matches_keys(&self, key: &str, modifier: bool, shift: bool, alt: bool) -> bool {
+        let key = key.to_lowercase();
+        // symbols such as "?" already imply shift, so it only counts for letters and named keys
+        let shift_matters =
+            self.shift || key.chars().count() > 1 || key.chars().all(char::is_alphanumeric);
+        key == self.key
+            && modifier == self.modifier
+            && alt == self.alt
+            && (!shift_matters || shift == self.shift)
+    }
+
+    // shown in the help overlay, e.g. "⌘ K" or "Ctrl K"
+    pub fn display(&self) -> String {
+        let mut parts = vec![];
+        if self.modifier {
+            parts.push(if is_mac() { "⌘" } else { "Ctrl" }.to_string());
+        }
+        if self.alt {
+            parts.push(if is_mac() { "⌥" } else { "Alt" }.to_string());
+        }
+        if self.shift {
+            parts.push("Shift".to_string());
+        }
+        parts.push(match self.key.as_str() {
+            v if v.chars().count() == 1 => v.to_uppercase(),
+            v => v.to_string(),
+        });
+        parts.join(" ")
+    }
+}
+
+fn is_mac() -> bool {
+    web_sys::window()
+        .and_then(|x| x.navigator().platform().ok())
+        .is_some_and(|x| x.to_lowercase().contains("mac"))
+}
+
+struct Registered {
+    id: usize,
+    name: &'static str,
+    binding: KeyBinding,
+    // catalog key of the help text
+    description: &'static str,
+    handler: Rc<dyn Fn()>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ShortcutConflict {
+    pub binding: String,
+    pub existing: &'static str,
+}
+
+impl std::fmt::Display for ShortcutConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} is already bound to {}", self.binding, self.existing)
+    }
+}
+
+// keeps the shortcut registered until dropped
+pub struct ShortcutGuard(usize);
+
+impl Drop for ShortcutGuard {
+    fn drop(&mut self) {
+        REGISTRY.with(|x| x.borrow_mut().retain(|r| r.id != self.0));
+    }
+}
+
+// single keys would fire while typing, so they are skipped inside text fields
+fn is_typing(e: &KeyboardEvent) -> bool {
+    e.target()
+        .and_then(|x| x.dyn_into::<HtmlElement>().ok())
+        .is_some_and(|x| {
+            x.is_content_editable()
+                || matches!(x.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT")
+        })
+}
+
+fn on_keydown(e: &KeyboardEvent) {
+    if e.repeat() {
+        return;
+    }
+    let typing = is_typing(e);
+    let handler = REGISTRY.with(|x| {
+        x.borrow()
+            .iter()
+            .rev()
+            .filter(|r| !typing || r.binding.modifier || r.binding.alt)
+            .find(|r| r.binding.matches(e))
+            .map(|r| r.handler.clone())
+    });
+    // called outside the borrow, handlers may register or drop shortcuts
+    if let Some(handler) = handler {
+        e.prevent_default();
+        handler();
+    }
+}
+
+fn ensure_listener() {
+    LISTENER.with(|x| {
+        if x.borrow().is_some() {
+            return;
+        }
+        let window = match web_sys::window() {
+            Some(v) => v,
+            None => return,
+        };
+        // not passive, so ctrl+k can be kept from focusing the browser search bar
+        let listener = EventListener::new_with_options(
+            &window,
+            "keydown",
+            EventListenerOptions::enable_prevent_default(),
+            |e| {
+                if let Some(e) = e.dyn_ref::<KeyboardEvent>() {
+                    on_keydown(e);
+                }
+            },
+        );
+        *x.borrow_mut() = Some(listener);
+    });
+}
+
+// `name` identifies the owner in conflict reports; a binding can only have one owner
+pub fn register(
+    name: &'static str,
+    binding: &str,
+    description: &'static str,
+    handler: impl Fn() + 'static,
+) -> Result<ShortcutGuard, ShortcutConflict> {
+    ensure_listener();
+    let parsed = KeyBinding::parse(binding);
+    REGISTRY.with(|x| {
+        let mut registry = x.borrow_mut();
+        if let Some(existing) = registry.iter().find(|r| r.binding == parsed) {
+            return Err(ShortcutConflict {
+                binding: binding.to_string(),
+                existing: existing.name,
+            });
+        }
+        let id = NEXT_ID.with(|x| {
+            x.set(x.get() + 1);
+            x.get()
+        });
+        registry.push(Registered {
+            id,
+            name,
+            binding: parsed,
+            description,
+            handler: Rc::new(handler),
+        });
+        Ok(ShortcutGuard(id))
+    })
+}
+
+// (binding, catalog key of the description) in registration order
+pub fn bindings() -> Vec<(KeyBinding, &'static str)> {
+    REGISTRY.with(|x| {
+        x.borrow()
+            .iter()
+            .map(|r| (r.binding.clone(), r.description))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binding(key: &str, modifier: bool, shift: bool, alt: bool) -> KeyBinding {
+        KeyBinding {
+            key: key.to_string(),
+            modifier,
+            shift,
+            alt,
+        }
+    }
+
+    #[test]
+    fn parse_modifiers() {
+        assert_eq!(KeyBinding::parse("Mod+K"), binding("k", true, false, false));
+        assert_eq!(
+            KeyBinding::parse("ctrl+k"),
+            binding("k", true, false, false)
+        );
+        assert_eq!(
+            KeyBinding::parse("Cmd+Shift+P"),
+            binding("p", true, true, false)
+        );
+        assert_eq!(
+            KeyBinding::parse("Alt+Enter"),
+            binding("enter", false, false, true)
+        );
+        assert_eq!(
+            KeyBinding::parse("Option+x"),
+            binding("x", false, false, true)
+        );
+        assert_eq!(
+            KeyBinding::parse("Escape"),
+            binding("escape", false, false, false)
+        );
+    }
+
+    #[test]
+    fn parse_symbols() {
+        assert_eq!(KeyBinding::parse("?"), binding("?", false, false, false));
+        assert_eq!(KeyBinding::parse("+"), binding("+", false, false, false));
+        assert_eq!(KeyBinding::parse("Mod++"), binding("+", true, false, false));
+        assert_eq!(
+            KeyBinding::parse("Shift++"),
+            binding("+", false, true, false)
+        );
+    }
+
+    #[test]
+    fn matches_modifiers() {
+        let b = KeyBinding::parse("Mod+K");
+        assert!(b.matches_keys("k", true, false, false));
+        assert!(b.matches_keys("K", true, false, false));
+        assert!(!b.matches_keys("k", false, false, false));
+        assert!(!b.matches_keys("k", true, false, true));
+        assert!(!b.matches_keys("k", true, true, false));
+        assert!(!b.matches_keys("j", true, false, false));
+    }
+
+    #[test]
+    fn matches_shift() {
+        // "?" is typed with shift on most layouts, the binding does not have to say so
+        let b = KeyBinding::parse("?");
+        assert!(b.matches_keys("?", false, true, false));
+        assert!(b.matches_keys("?", false, false, false));
+        assert!(!b.matches_keys("?", true, true, false));
+
+        let b = KeyBinding::parse("Shift+Enter");
+        assert!(b.matches_keys("Enter", false, true, false));
+        assert!(!b.matches_keys("Enter", false, false, false));
+
+        let b = KeyBinding::parse("Escape");
+        assert!(b.matches_keys("Escape", false, false, false));
+        assert!(!b.matches_keys("Escape", false, true, false));
+    }
+}
@@ -0,0 +1,29 @@
+use super::ShortcutGuard;
+use yew::prelude::*;
+
+// registers `binding` while the component is mounted; conflicts are logged and the later one is ignored
+#[hook]
+pub fn use_shortcut(
+    name: &'static str,
+    binding: &'static str,
+    description: &'static str,
+    callback: Callback<()>,
+) {
+    let guard = use_mut_ref(|| None::<ShortcutGuard>);
+    // the latest callback, so the registration does not have to change with it
+    let current = use_mut_ref(|| callback.clone());
+    *current.borrow_mut() = callback;
+    use_effect_with(binding, move |binding| {
+        let handler = move || {
+            let callback = current.borrow().clone();
+            callback.emit(());
+        };
+        match super::register(name, binding, description, handler) {
+            Ok(v) => *guard.borrow_mut() = Some(v),
+            Err(err) => log::warn!("shortcut not registered: {}", err),
+        }
+        move || {
+            guard.borrow_mut().take();
+        }
+    });
+}
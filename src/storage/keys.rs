@@ -0,0 +1,28 @@
+use super::{Scope, StorageKey};
+use crate::component::menu::SavedMenu;
+use crate::util::common::CurrentUser;
+
+// every entry the app keeps in local storage
+
+pub static TOKEN: StorageKey<String> = StorageKey::new("token", Scope::Env).legacy("evolve_token");
+pub static CURRENT_USER: StorageKey<CurrentUser> =
+    StorageKey::new("current_user", Scope::Env).legacy("current_user");
+
+pub static LOCALE: StorageKey<String> = StorageKey::new("locale", Scope::Device);
+pub static THEME: StorageKey<String> = StorageKey::new("theme", Scope::Device);
+
+pub static MENU_COLLAPSED: StorageKey<bool> = StorageKey::new("menu_collapsed", Scope::User);
+pub static MENU_EXPANDED: StorageKey<SavedMenu> = StorageKey::new("menu_expanded", Scope::User);
+
+// online, away or offline as picked in the header
+pub static PRESENCE: StorageKey<String> = StorageKey::new("presence", Scope::User);
+
+// unix time a verification code may be sent again; one child key per form
+pub static CODE_COOLDOWN: StorageKey<i64> = StorageKey::new("code_cooldown", Scope::Env);
+
+// filled in after register and reset password so the login form can pick it up
+pub static REMEMBERED_EMAIL: StorageKey<String> =
+    StorageKey::new("remembered_email", Scope::Env).legacy("email");
+
+// written by older builds and no longer read; `pwd` held the remembered password in plain text
+pub const OBSOLETE: [&str; 3] = ["selected_navbar_name", "selected_navbar_parent_name", "pwd"];
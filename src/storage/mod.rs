@@ -0,0 +1,444 @@
+pub mod keys;
+pub mod use_storage;
+
+use crate::util::request::Host;
+use gloo::events::EventListener;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use web_sys::StorageEvent;
+
+const PREFIX: &str = "evolve";
+
+thread_local! {
+    // used when local storage is missing or refuses writes, e.g. in private modes
+    static MEMORY: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+    static SUBSCRIBERS: RefCell<Vec<Subscriber>> = const { RefCell::new(Vec::new()) };
+    static LISTENER: RefCell<Option<EventListener>> = const { RefCell::new(None) };
+    static NEXT_ID: Cell<usize> = const { Cell::new(0) };
+}
+
+// what a key is namespaced by
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scope {
+    // shared by every server and user of this browser, e.g. the language
+    Device,
+    // per api server, so a test and a production login do not mix
+    Env,
+    // per api server and signed in user; unavailable while nobody is signed in
+    User,
+}
+
+// turns the value stored under `from` into the one of version `from + 1`; `None` drops it
+pub type Migration = fn(from: u32, value: Value) -> Option<Value>;
+
+// a typed entry; values are stored as `{"v": version, "data": ..}`
+pub struct StorageKey<T> {
+    name: Cow<'static, str>,
+    scope: Scope,
+    version: u32,
+    migrate: Option<Migration>,
+    // the unprefixed key older builds wrote, imported on first read
+    legacy: Option<&'static str>,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for StorageKey<T> {
+    fn clone(&self) -> Self {
+        StorageKey {
+            name: self.name.clone(),
+            scope: self.scope,
+            version: self.version,
+            migrate: self.migrate,
+            legacy: self.legacy,
+            _type: PhantomData,
+        }
+    }
+}
+
+impl<T> PartialEq for StorageKey<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.scope == other.scope && self.version == other.version
+    }
+}
+
+impl<T> std::fmt::Debug for StorageKey<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "StorageKey({:?}, {})", self.scope, self.name)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    v: u32,
+    data: Value,
+}
+
+impl<T> StorageKey<T> {
+    pub const fn new(name: &'static str, scope: Scope) -> Self {
+        StorageKey {
+            name: Cow::Borrowed(name),
+            scope,
+            version: 1,
+            migrate: None,
+            legacy: None,
+            _type: PhantomData,
+        }
+    }
+
+    // no entry has changed its shape since the storage module came in; the first one that
+    // does bumps its version here
+    #[allow(dead_code)]
+    pub const fn version(mut self, version: u32, migrate: Migration) -> Self {
+        self.version = version;
+        self.migrate = Some(migrate);
+        self
+    }
+
+    pub const fn legacy(mut self, name: &'static str) -> Self {
+        self.legacy = Some(name);
+        self
+    }
+
+    // one key per `suffix`, e.g. a cooldown per form
+    pub fn child(&self, suffix: &str) -> Self {
+        StorageKey {
+            name: Cow::Owned(format!("{}/{}", self.name, suffix)),
+            legacy: None,
+            ..self.clone()
+        }
+    }
+
+    // the key in local storage; `None` for a user key while signed out
+    pub fn full_key(&self) -> Option<String> {
+        let env = env();
+        match self.scope {
+            Scope::Device => Some(format!("{}/{}", PREFIX, self.name)),
+            Scope::Env => Some(format!("{}/{}/{}", PREFIX, env, self.name)),
+            Scope::User => {
+                let id = keys::CURRENT_USER.get()?.id;
+                Some(format!("{}/{}/user/{}/{}", PREFIX, env, id, self.name))
+            }
+        }
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> StorageKey<T> {
+    pub fn get(&self) -> Option<T> {
+        let key = self.full_key()?;
+        let raw = match read(&key) {
+            Some(v) => v,
+            None => return self.import_legacy(),
+        };
+        // an unreadable entry is treated as missing and overwritten by the next `set`
+        let envelope = match serde_json::from_str::<Envelope>(&raw) {
+            Ok(v) => v,
+            Err(err) => {
+                log::warn!("bad storage entry {}: {}", key, err);
+                return None;
+            }
+        };
+        self.upgrade(envelope)
+    }
+
+    pub fn set(&self, value: &T) {
+        let key = match self.full_key() {
+            Some(v) => v,
+            None => return,
+        };
+        let envelope = match serde_json::to_value(value) {
+            Ok(data) => Envelope {
+                v: self.version,
+                data,
+            },
+            Err(err) => {
+                log::error!("serialize storage entry {} failed: {}", key, err);
+                return;
+            }
+        };
+        write(&key, &serde_json::to_string(&envelope).unwrap_or_default());
+        notify(&key);
+    }
+
+    pub fn remove(&self) {
+        if let Some(legacy) = self.legacy {
+            delete(legacy);
+        }
+        if let Some(key) = self.full_key() {
+            delete(&key);
+            notify(&key);
+        }
+    }
+
+    // `f` runs after every change of the value, in this tab or another one
+    pub fn subscribe(&self, f: impl Fn(Option<T>) + 'static) -> Option<Subscription>
+    where
+        T: 'static,
+    {
+        let key = self.full_key()?;
+        ensure_listener();
+        let id = NEXT_ID.with(|x| {
+            x.set(x.get() + 1);
+            x.get()
+        });
+        let this = self.clone();
+        SUBSCRIBERS.with(|x| {
+            x.borrow_mut().push(Subscriber {
+                id,
+                key,
+                handler: Rc::new(move || f(this.get())),
+            })
+        });
+        Some(Subscription(id))
+    }
+
+    fn upgrade(&self, mut envelope: Envelope) -> Option<T> {
+        while envelope.v < self.version {
+            let migrate = self.migrate?;
+            envelope.data = migrate(envelope.v, envelope.data)?;
+            envelope.v += 1;
+        }
+        if envelope.v > self.version {
+            // written by a newer build, leave it alone
+            return None;
+        }
+        serde_json::from_value(envelope.data).ok()
+    }
+
+    fn import_legacy(&self) -> Option<T> {
+        let legacy = self.legacy?;
+        let value = self.parse_legacy(read(legacy)?)?;
+        self.set(&value);
+        delete(legacy);
+        Some(value)
+    }
+
+    // old builds stored plain strings or json under bare names, taken as version 1
+    fn parse_legacy(&self, raw: String) -> Option<T> {
+        // "123456" is json, but a string key wants it verbatim, so both readings are tried
+        serde_json::from_str::<Value>(&raw)
+            .ok()
+            .into_iter()
+            .chain([Value::String(raw)])
+            .find_map(|data| self.upgrade(Envelope { v: 1, data }))
+    }
+}
+
+struct Subscriber {
+    id: usize,
+    key: String,
+    handler: Rc<dyn Fn()>,
+}
+
+// keeps the subscription alive until dropped
+pub struct Subscription(usize);
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        SUBSCRIBERS.with(|x| x.borrow_mut().retain(|s| s.id != self.0));
+    }
+}
+
+// the api host, so each server gets its own namespace
+fn env() -> String {
+    let host = Host::ApiBase.to_string();
+    let host = host.split_once("://").map(|x| x.1).unwrap_or(&host);
+    host.split('/').next().unwrap_or_default().to_string()
+}
+
+fn local() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+fn read(key: &str) -> Option<String> {
+    let stored = local().and_then(|x| x.get_item(key).ok().flatten());
+    stored.or_else(|| MEMORY.with(|x| x.borrow().get(key).cloned()))
+}
+
+fn write(key: &str, value: &str) {
+    match local().map(|x| x.set_item(key, value)) {
+        Some(Ok(_)) => {
+            MEMORY.with(|x| x.borrow_mut().remove(key));
+        }
+        res => {
+            if let Some(Err(err)) = res {
+                log::warn!(
+                    "local storage write failed, keeping {} in memory: {:?}",
+                    key,
+                    err
+                );
+            }
+            MEMORY.with(|x| x.borrow_mut().insert(key.to_string(), value.to_string()));
+        }
+    }
+}
+
+fn delete(key: &str) {
+    if let Some(storage) = local() {
+        storage.remove_item(key).unwrap_or_default();
+    }
+    MEMORY.with(|x| x.borrow_mut().remove(key));
+}
+
+// `None` means everything changed, as after `localStorage.clear()`
+fn notify_matching(key: Option<&str>) {
+    // handlers run outside the borrow, they may subscribe or unsubscribe
+    let handlers = SUBSCRIBERS.with(|x| {
+        x.borrow()
+            .iter()
+            .filter(|s| key.is_none_or(|k| s.key == k))
+            .map(|s| s.handler.clone())
+            .collect::<Vec<_>>()
+    });
+    for handler in handlers {
+        handler();
+    }
+}
+
+fn notify(key: &str) {
+    notify_matching(Some(key));
+}
+
+// the `storage` event only fires in the other tabs, writes in this one notify directly
+fn ensure_listener() {
+    LISTENER.with(|x| {
+        if x.borrow().is_some() {
+            return;
+        }
+        let window = match web_sys::window() {
+            Some(v) => v,
+            None => return,
+        };
+        let listener = EventListener::new(&window, "storage", |e| {
+            if let Some(e) = e.dyn_ref::<StorageEvent>() {
+                notify_matching(e.key().as_deref());
+            }
+        });
+        *x.borrow_mut() = Some(listener);
+    });
+}
+
+// drops keys nothing reads any more
+pub fn init() {
+    for key in keys::OBSOLETE {
+        delete(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Named {
+        name: String,
+    }
+
+    // v1 was the bare name, v2 wraps it
+    fn migrate_named(from: u32, value: Value) -> Option<Value> {
+        match from {
+            1 => Some(json!({ "name": value })),
+            _ => None,
+        }
+    }
+
+    fn named(name: &str) -> Option<Named> {
+        Some(Named {
+            name: name.to_string(),
+        })
+    }
+
+    fn envelope(v: u32, data: Value) -> Envelope {
+        Envelope { v, data }
+    }
+
+    #[test]
+    fn upgrade_current_version() {
+        assert_eq!(
+            keys::LOCALE.upgrade(envelope(1, json!("zh"))),
+            Some("zh".to_string())
+        );
+        assert_eq!(
+            keys::MENU_COLLAPSED.upgrade(envelope(1, json!(true))),
+            Some(true)
+        );
+        // the wrong shape reads as missing
+        assert_eq!(
+            keys::MENU_COLLAPSED.upgrade(envelope(1, json!("yes"))),
+            None
+        );
+        // written by a newer build
+        assert_eq!(keys::LOCALE.upgrade(envelope(2, json!("zh"))), None);
+    }
+
+    #[test]
+    fn upgrade_migrates() {
+        let key = StorageKey::<Named>::new("named", Scope::Device).version(2, migrate_named);
+        assert_eq!(key.upgrade(envelope(1, json!("a"))), named("a"));
+        assert_eq!(key.upgrade(envelope(2, json!({ "name": "b" }))), named("b"));
+
+        // a migration that gives up drops the entry
+        let key = StorageKey::<Named>::new("named", Scope::Device).version(3, migrate_named);
+        assert_eq!(key.upgrade(envelope(1, json!("a"))), None);
+        assert_eq!(key.upgrade(envelope(3, json!({ "name": "c" }))), named("c"));
+    }
+
+    #[test]
+    fn legacy_names() {
+        assert_eq!(keys::TOKEN.legacy, Some("evolve_token"));
+        assert_eq!(keys::CURRENT_USER.legacy, Some("current_user"));
+        assert_eq!(keys::REMEMBERED_EMAIL.legacy, Some("email"));
+        // the remembered password is deleted, never imported
+        assert!(keys::OBSOLETE.contains(&"pwd"));
+        // nothing to import, so storage is not even read
+        assert_eq!(keys::LOCALE.legacy, None);
+        assert_eq!(keys::LOCALE.import_legacy(), None);
+        // children never import
+        assert_eq!(keys::TOKEN.child("x").legacy, None);
+    }
+
+    #[test]
+    fn import_legacy_strings() {
+        assert_eq!(
+            keys::TOKEN.parse_legacy("eyJhbGciOiJIUzI1NiJ9.e30.c2ln".to_string()),
+            Some("eyJhbGciOiJIUzI1NiJ9.e30.c2ln".to_string())
+        );
+        assert_eq!(
+            keys::REMEMBERED_EMAIL.parse_legacy("hello@example.com".to_string()),
+            Some("hello@example.com".to_string())
+        );
+        // valid json, but a string key keeps it verbatim
+        assert_eq!(
+            keys::TOKEN.parse_legacy("123456".to_string()),
+            Some("123456".to_string())
+        );
+        assert_eq!(
+            keys::TOKEN.parse_legacy("\"quoted\"".to_string()),
+            Some("quoted".to_string())
+        );
+    }
+
+    #[test]
+    fn import_legacy_current_user() {
+        // as the login page of older builds wrote it, without permissions
+        let raw = r#"{"id":7,"type":"user","email":"hello@example.com","name":"Scarlett",
+            "mobile":"13800001111","laston":1700000000,"created_at":1690000000,"updated_at":1695000000}"#;
+        let user = keys::CURRENT_USER.parse_legacy(raw.to_string()).unwrap();
+        assert_eq!(user.id, 7);
+        assert_eq!(user.email, "hello@example.com");
+        assert_eq!(user.name.as_deref(), Some("Scarlett"));
+        assert_eq!(user.permissions, None);
+        assert!(user.can("user:read"));
+
+        assert!(keys::CURRENT_USER
+            .parse_legacy("not json".to_string())
+            .is_none());
+    }
+}
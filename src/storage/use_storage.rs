@@ -0,0 +1,23 @@
+use super::{StorageKey, Subscription};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use yew::prelude::*;
+
+// the current value of `key`, re-rendering whenever it changes, in this tab or another one
+#[hook]
+pub fn use_storage<T>(key: &'static StorageKey<T>) -> Option<T>
+where
+    T: Serialize + DeserializeOwned + 'static,
+{
+    let force_update = use_force_update();
+    let subscription = use_mut_ref(|| None::<Subscription>);
+    // user keys resolve to another entry once somebody signs in
+    let full_key = key.full_key();
+    use_effect_with(full_key, move |_| {
+        *subscription.borrow_mut() = key.subscribe(move |_| force_update.force_update());
+        move || {
+            subscription.borrow_mut().take();
+        }
+    });
+    key.get()
+}
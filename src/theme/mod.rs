@@ -0,0 +1,175 @@
+pub mod use_theme;
+
+use crate::storage::{keys, Subscription};
+use crate::util::tauri;
+use gloo::events::EventListener;
+use std::cell::{Cell, RefCell};
+use web_sys::MediaQueryList;
+
+const DARK_QUERY: &str = "(prefers-color-scheme: dark)";
+// emitted by the tauri shell when the os theme changes
+const THEME_CHANGED_EVENT: &str = "theme-changed";
+
+thread_local! {
+    static MODE: Cell<ThemeMode> = const { Cell::new(ThemeMode::System) };
+    static SYSTEM: Cell<Theme> = const { Cell::new(Theme::Light) };
+    static MEDIA_LISTENER: RefCell<Option<EventListener>> = const { RefCell::new(None) };
+    static THEME_SUBSCRIPTION: RefCell<Option<Subscription>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ThemeMode {
+    Light,
+    Dark,
+    #[default]
+    System,
+}
+
+impl ThemeMode {
+    pub fn all() -> [ThemeMode; 3] {
+        [ThemeMode::Light, ThemeMode::Dark, ThemeMode::System]
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ThemeMode::Light => "light",
+            ThemeMode::Dark => "dark",
+            ThemeMode::System => "system",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<ThemeMode> {
+        ThemeMode::all().into_iter().find(|x| x.code() == code)
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            ThemeMode::Light => "fa-solid fa-sun",
+            ThemeMode::Dark => "fa-solid fa-moon",
+            ThemeMode::System => "fa-solid fa-circle-half-stroke",
+        }
+    }
+
+    // catalog key of the label
+    pub fn label(&self) -> &'static str {
+        match self {
+            ThemeMode::Light => "theme.light",
+            ThemeMode::Dark => "theme.dark",
+            ThemeMode::System => "theme.system",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Theme {
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+        }
+    }
+
+    fn from_dark(dark: bool) -> Theme {
+        if dark {
+            Theme::Dark
+        } else {
+            Theme::Light
+        }
+    }
+}
+
+pub fn mode() -> ThemeMode {
+    MODE.with(|x| x.get())
+}
+
+// the theme actually shown, with `System` resolved
+pub fn current() -> Theme {
+    match mode() {
+        ThemeMode::Light => Theme::Light,
+        ThemeMode::Dark => Theme::Dark,
+        ThemeMode::System => SYSTEM.with(|x| x.get()),
+    }
+}
+
+pub fn set_mode(mode: ThemeMode) {
+    keys::THEME.set(&mode.code().to_string());
+    MODE.with(|x| x.set(mode));
+    apply();
+}
+
+fn set_system(theme: Theme) {
+    SYSTEM.with(|x| x.set(theme));
+    apply();
+}
+
+fn apply() {
+    if let Some(el) = web_sys::window()
+        .and_then(|x| x.document())
+        .and_then(|x| x.document_element())
+    {
+        el.set_attribute("data-theme", current().code())
+            .unwrap_or_default();
+    }
+}
+
+fn media_query() -> Option<MediaQueryList> {
+    web_sys::window()?.match_media(DARK_QUERY).ok().flatten()
+}
+
+// loads the saved mode and starts following the system theme; `on_change` fires when it flips
+pub fn init(on_change: impl Fn() + 'static) {
+    let mode = keys::THEME
+        .get()
+        .and_then(|x| ThemeMode::from_code(&x))
+        .unwrap_or_default();
+    MODE.with(|x| x.set(mode));
+
+    let on_change = std::rc::Rc::new(on_change);
+    {
+        // a mode picked in another tab applies here as well
+        let on_change = on_change.clone();
+        let subscription = keys::THEME.subscribe(move |v| {
+            if let Some(mode) = v.and_then(|x| ThemeMode::from_code(&x)) {
+                MODE.with(|x| x.set(mode));
+                apply();
+                on_change();
+            }
+        });
+        THEME_SUBSCRIPTION.with(|x| *x.borrow_mut() = subscription);
+    }
+
+    if tauri::is_tauri() {
+        // the webview does not always report prefers-color-scheme, ask the shell instead
+        {
+            let on_change = on_change.clone();
+            tauri::listen(THEME_CHANGED_EVENT, move |v: String| {
+                set_system(Theme::from_dark(v == "dark"));
+                on_change();
+            });
+        }
+        wasm_bindgen_futures::spawn_local(async move {
+            match tauri::invoke::<String, _>("system_theme", &serde_json::json!({})).await {
+                Ok(v) => {
+                    set_system(Theme::from_dark(v == "dark"));
+                    on_change();
+                }
+                Err(err) => log::warn!("get system theme failed: {}", err),
+            }
+        });
+    } else if let Some(query) = media_query() {
+        SYSTEM.with(|x| x.set(Theme::from_dark(query.matches())));
+        let listener = EventListener::new(&query, "change", move |_| {
+            if let Some(query) = media_query() {
+                set_system(Theme::from_dark(query.matches()));
+                on_change();
+            }
+        });
+        MEDIA_LISTENER.with(|x| *x.borrow_mut() = Some(listener));
+    }
+    apply();
+}
@@ -0,0 +1,76 @@
+use super::{Theme, ThemeMode};
+use yew::prelude::*;
+
+#[derive(Clone, PartialEq)]
+pub struct ThemeHandle {
+    mode: ThemeMode,
+    theme: Theme,
+    set_mode: Callback<ThemeMode>,
+}
+
+impl ThemeHandle {
+    pub fn mode(&self) -> ThemeMode {
+        self.mode
+    }
+
+    pub fn theme(&self) -> Theme {
+        self.theme
+    }
+
+    pub fn set_mode(&self, mode: ThemeMode) {
+        self.set_mode.emit(mode);
+    }
+
+    // light -> dark -> system -> light
+    pub fn toggle(&self) {
+        let modes = ThemeMode::all();
+        let i = modes
+            .iter()
+            .position(|x| *x == self.mode)
+            .unwrap_or_default();
+        self.set_mode(modes[(i + 1) % modes.len()]);
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ThemeProviderProps {
+    pub children: Html,
+}
+
+#[function_component(ThemeProvider)]
+pub fn theme_provider(props: &ThemeProviderProps) -> Html {
+    let force_update = use_force_update();
+    {
+        // runs before the first render so the saved theme is applied without a flash
+        let force_update = force_update.clone();
+        use_memo((), move |_| {
+            super::init(move || force_update.force_update())
+        });
+    }
+    let set_mode = {
+        let force_update = force_update.clone();
+        Callback::from(move |mode: ThemeMode| {
+            super::set_mode(mode);
+            force_update.force_update();
+        })
+    };
+    let ctx = ThemeHandle {
+        mode: super::mode(),
+        theme: super::current(),
+        set_mode,
+    };
+    html! {
+        <ContextProvider<ThemeHandle> context={ctx}>
+            {props.children.clone()}
+        </ContextProvider<ThemeHandle>>
+    }
+}
+
+#[hook]
+pub fn use_theme() -> ThemeHandle {
+    use_context::<ThemeHandle>().unwrap_or_else(|| ThemeHandle {
+        mode: super::mode(),
+        theme: super::current(),
+        set_mode: Callback::from(super::set_mode),
+    })
+}
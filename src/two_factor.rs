@@ -0,0 +1,267 @@
+use crate::component::message_item::MessageItemValue;
+use crate::component::message_list::{self, MessageList};
+use crate::form::use_form::{use_form, FieldConfig};
+use crate::form::verification_code::VerificationCodeInput;
+use crate::form::FormError;
+use crate::i18n::{self, use_i18n::use_i18n};
+use crate::util::common;
+use crate::util::request::{self, Host, RequestOptions};
+use crate::util::use_cancel_token::use_cancel_token;
+use crate::util::validation;
+use qrcode::render::svg;
+use qrcode::QrCode;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct TwoFactorStatus {
+    pub enabled: bool,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct TwoFactorEnrollment {
+    pub secret: String,
+    pub otpauth_url: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct RecoveryCodes {
+    pub recovery_codes: Vec<String>,
+}
+
+const CODE: &str = "code";
+
+#[derive(Serialize)]
+pub struct TwoFactorCodeReq {
+    pub code: String,
+}
+
+#[derive(Clone, PartialEq)]
+enum Step {
+    Loading,
+    // the status could not be fetched, with the reason
+    Failed(String),
+    Disabled,
+    Enrolling(String, String),
+    RecoveryCodes(Vec<String>),
+    Enabled,
+}
+
+fn qr_svg(url: &str) -> String {
+    match QrCode::new(url.as_bytes()) {
+        Ok(code) => code
+            .render::<svg::Color>()
+            .min_dimensions(200, 200)
+            .build(),
+        Err(err) => {
+            log::error!("render qr code error: {}", err);
+            String::default()
+        }
+    }
+}
+
+#[function_component(TwoFactorSetup)]
+pub fn two_factor_setup() -> Html {
+    let force_update = use_force_update();
+    let i18n = use_i18n();
+    let message: Rc<RefCell<Option<MessageItemValue>>> = use_mut_ref(|| None);
+    let step = use_mut_ref(|| Step::Loading);
+    let cancel = use_cancel_token();
+
+    let load = {
+        let step = step.clone();
+        let force_update = force_update.clone();
+        let cancel = cancel.clone();
+        Callback::from(move |_| {
+            let step = step.clone();
+            let force_update = force_update.clone();
+            let cancel = cancel.clone();
+            *step.borrow_mut() = Step::Loading;
+            force_update.force_update();
+            spawn_local(async move {
+                let res = RequestOptions::new()
+                    .cancel(&cancel)
+                    .get::<TwoFactorStatus, Vec<(&str, &str)>, _>(
+                        Host::ApiBase,
+                        "/auth/two_factor/status",
+                        None,
+                    )
+                    .await;
+                *step.borrow_mut() = match res {
+                    Ok(res) => match res.data {
+                        Some(v) if v.enabled => Step::Enabled,
+                        _ => Step::Disabled,
+                    },
+                    Err(err) if err.cancelled => return,
+                    Err(err) => Step::Failed(err.user_message()),
+                };
+                force_update.force_update();
+            });
+        })
+    };
+
+    {
+        let load = load.clone();
+        use_effect_with((), move |_| load.emit(()));
+    }
+
+    let form = use_form(|| vec![FieldConfig::new(CODE).rule(validation::code())]);
+
+    let enroll = {
+        let step = step.clone();
+        let message = message.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            let step = step.clone();
+            let message = message.clone();
+            let force_update = force_update.clone();
+            spawn_local(async move {
+                match request::post::<TwoFactorEnrollment, _>(
+                    Host::ApiBase,
+                    "/auth/two_factor/enroll",
+                    &(),
+                )
+                .await
+                {
+                    Ok(res) => {
+                        if let Some(v) = res.data {
+                            *step.borrow_mut() = Step::Enrolling(v.secret, v.otpauth_url);
+                        }
+                    }
+                    Err(err) => *message.borrow_mut() = Some(message_list::api_error(&err)),
+                }
+                force_update.force_update();
+            });
+        })
+    };
+
+    // activate the enrollment, or regenerate recovery codes once enabled
+    let submit_code = |path: &'static str| {
+        let step = step.clone();
+        let form_handle = form.clone();
+        form.onsubmit(move |values| {
+            let step = step.clone();
+            let form = form_handle.clone();
+            let req = TwoFactorCodeReq {
+                code: values.get(CODE).to_string(),
+            };
+            async move {
+                let res = request::post::<RecoveryCodes, _>(Host::ApiBase, path, &req)
+                    .await
+                    .map_err(|err| FormError::Field(CODE, err.user_message()))?;
+                form.reset();
+                *step.borrow_mut() =
+                    Step::RecoveryCodes(res.data.map(|x| x.recovery_codes).unwrap_or_default());
+                Ok(())
+            }
+        })
+    };
+    let activate = submit_code("/auth/two_factor/activate");
+    let regenerate = submit_code("/auth/two_factor/recovery_codes");
+
+    let disable = {
+        let step = step.clone();
+        let message = message.clone();
+        let form_handle = form.clone();
+        form.onsubmit(move |values| {
+            let step = step.clone();
+            let message = message.clone();
+            let form = form_handle.clone();
+            let req = TwoFactorCodeReq {
+                code: values.get(CODE).to_string(),
+            };
+            async move {
+                request::post::<serde_json::Value, _>(
+                    Host::ApiBase,
+                    "/auth/two_factor/disable",
+                    &req,
+                )
+                .await
+                .map_err(|err| FormError::Field(CODE, err.user_message()))?;
+                form.reset();
+                *step.borrow_mut() = Step::Disabled;
+                *message.borrow_mut() = Some(message_list::ok(&i18n::t("two_factor.disabled")));
+                Ok(())
+            }
+        })
+    };
+
+    let done = {
+        let step = step.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            *step.borrow_mut() = Step::Enabled;
+            force_update.force_update();
+        })
+    };
+
+    let code_field = html! {
+        <VerificationCodeInput form={form.clone()} name={CODE} label={i18n.t("code.label")} modifier=""/>
+    };
+
+    let step = step.borrow().clone();
+    html! {
+    <>
+    <MessageList value = {(*message.borrow()).clone()}/>
+    <div class="detail-container">
+        <div class="box">
+            <p class="title is-6">{i18n.t("nav.two_factor")}</p>
+            {
+                match step {
+                    Step::Loading => html!{ <div class="table-loading"></div> },
+                    Step::Failed(reason) => html!{
+                        <>
+                        <div class="notification is-danger is-light">{reason}</div>
+                        <button class="button is-light" onclick={load.reform(|_| ())}>{i18n.t("common.retry")}</button>
+                        </>
+                    },
+                    Step::Disabled => html!{
+                        <>
+                        <p class="block">{i18n.t("two_factor.intro")}</p>
+                        <button class="button is-primary" onclick={enroll}>{i18n.t("two_factor.enable")}</button>
+                        </>
+                    },
+                    Step::Enrolling(secret, otpauth_url) => html!{
+                        <>
+                        <p class="block">{i18n.t("two_factor.scan")}</p>
+                        <div class="block two-factor-qr">
+                            {common::create_html("div", &qr_svg(&otpauth_url))}
+                        </div>
+                        <p class="block">{i18n.t("two_factor.manual_key")}{" "}<code>{secret}</code></p>
+                        {code_field}
+                        <button class="button is-primary" onclick={activate.reform(|_| ())}>{i18n.t("login.verify")}</button>
+                        </>
+                    },
+                    Step::RecoveryCodes(codes) => html!{
+                        <>
+                        <div class="notification is-warning is-light">
+                            {i18n.t("two_factor.save_recovery_codes")}
+                        </div>
+                        <div class="block recovery-codes">
+                        {
+                            codes.iter().map(|x| html!{ <code>{x.clone()}</code> }).collect::<Html>()
+                        }
+                        </div>
+                        <button class="button is-primary" onclick={done}>{i18n.t("two_factor.done")}</button>
+                        </>
+                    },
+                    Step::Enabled => html!{
+                        <>
+                        <p class="block"><span class="tag is-success is-light">{i18n.t("two_factor.enabled")}</span>{" "}{i18n.t("two_factor.manage")}</p>
+                        {code_field}
+                        <div class="buttons">
+                            <button class="button is-light is-info" onclick={regenerate.reform(|_| ())}>{i18n.t("two_factor.regenerate")}</button>
+                            <button class="button is-light is-danger" onclick={disable.reform(|_| ())}>{i18n.t("two_factor.disable")}</button>
+                        </div>
+                        </>
+                    },
+                }
+            }
+        </div>
+    </div>
+    </>
+    }
+}
@@ -0,0 +1,424 @@
+use crate::component::message_item::MessageItemValue;
+use crate::component::message_list::{self, MessageList};
+use crate::component::session_list::SessionList;
+use crate::confirm_form::ConfirmForm;
+use crate::i18n::{self, use_i18n::use_i18n};
+use crate::query::use_mutation::use_mutation;
+use crate::query::use_query::use_query;
+use crate::query::{self, keys};
+use crate::realtime::use_entity_change::use_entity_change;
+use crate::realtime::{ChangeKind, Entity, EntityChange};
+use crate::util::common;
+use crate::util::request::{self, Host};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::rc::Rc;
+use web_sys::{Event, HtmlInputElement};
+use yew::prelude::*;
+use yew::Properties;
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Role {
+    pub id: i64,
+    pub name: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Activity {
+    pub id: i64,
+    pub action: String,
+    pub description: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct UserDetailValue {
+    pub id: i64,
+    pub r#type: String,
+    pub email: String,
+    pub name: Option<String>,
+    pub mobile: Option<String>,
+    pub status: String,
+    pub laston: Option<i64>,
+    pub created_at: i64,
+    pub updated_at: Option<i64>,
+    #[serde(default)]
+    pub roles: Vec<Role>,
+    #[serde(default)]
+    pub activities: Vec<Activity>,
+}
+
+// the columns a pushed user change carries; roles and activities come with the detail only
+#[derive(Deserialize)]
+struct ChangedUser {
+    name: Option<String>,
+    mobile: Option<String>,
+    status: String,
+    updated_at: Option<i64>,
+}
+
+#[derive(Serialize, Clone, Default)]
+pub struct UpdateReq {
+    pub id: i64,
+    pub name: Option<String>,
+    pub mobile: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct DeleteReq {
+    pub ids: Vec<i64>,
+}
+
+#[derive(Serialize)]
+pub struct ResetPwdReq {
+    pub id: i64,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ConfirmOperation {
+    Delete,
+    ResetPwd,
+}
+
+#[derive(PartialEq, Properties)]
+pub struct UserDetailProps {
+    pub id: i64,
+}
+
+#[function_component(UserDetail)]
+pub fn user_detail(props: &UserDetailProps) -> Html {
+    let i18n = use_i18n();
+    let force_update = use_force_update();
+    let message: Rc<RefCell<Option<MessageItemValue>>> = use_mut_ref(|| None);
+    let edit_form: Rc<RefCell<Option<UpdateReq>>> = use_mut_ref(|| None);
+    let confirm: Rc<RefCell<Option<ConfirmOperation>>> = use_mut_ref(|| None);
+    let id = props.id;
+    let detail = use_query(keys::user_detail(id), move |opts| async move {
+        let res = opts
+            .get::<UserDetailValue, Vec<(&str, &str)>, _>(
+                Host::ApiBase,
+                &format!("/user/{id}/detail"),
+                None,
+            )
+            .await?;
+        Ok(res.data)
+    });
+    let update = use_mutation(vec![keys::users()], |req: UpdateReq| async move {
+        request::put::<serde_json::Value, _>(Host::ApiBase, "/user/update", &req).await
+    });
+    let delete = use_mutation(vec![keys::users()], move |_: ()| async move {
+        request::delete::<serde_json::Value, _>(
+            Host::ApiBase,
+            "/user/delete",
+            &DeleteReq { ids: vec![id] },
+        )
+        .await
+    });
+    // the user is signed out everywhere
+    let reset_pwd = use_mutation(
+        vec![keys::user_sessions(Some(id))],
+        move |_: ()| async move {
+            request::post::<serde_json::Value, _>(
+                Host::ApiBase,
+                "/user/reset_pwd",
+                &ResetPwdReq { id },
+            )
+            .await
+        },
+    );
+
+    // someone else changed or deleted this user
+    {
+        let message = message.clone();
+        let force_update = force_update.clone();
+        use_entity_change(
+            Entity::User,
+            Callback::from(move |change: EntityChange| {
+                if change.id != id {
+                    return;
+                }
+                if change.kind == ChangeKind::Deleted {
+                    *message.borrow_mut() = Some(message_list::warn(&i18n::t(
+                        "user_detail.deleted_elsewhere",
+                    )));
+                    force_update.force_update();
+                } else if let Some(user) = change.data::<ChangedUser>() {
+                    query::set_data(&keys::user_detail(id), |value: &Option<UserDetailValue>| {
+                        value.clone().map(|v| UserDetailValue {
+                            name: user.name.clone(),
+                            mobile: user.mobile.clone(),
+                            status: user.status.clone(),
+                            updated_at: user.updated_at,
+                            ..v
+                        })
+                    });
+                } else {
+                    query::invalidate(&keys::user_detail(id));
+                }
+            }),
+        );
+    }
+    {
+        // the roles are listed by name, a renamed one needs a fresh detail
+        let refetch = detail.refetch.clone();
+        use_entity_change(Entity::Role, Callback::from(move |_| refetch.emit(())));
+    }
+
+    {
+        let message = message.clone();
+        let force_update = force_update.clone();
+        use_effect_with(detail.error.clone(), move |err| {
+            if let Some(err) = err {
+                *message.borrow_mut() = Some(message_list::api_error(err));
+                force_update.force_update();
+            }
+        });
+    }
+
+    let edit = {
+        let value = detail.data.clone();
+        let edit_form = edit_form.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            if let Some(v) = value.as_deref().and_then(|x| x.as_ref()) {
+                *edit_form.borrow_mut() = Some(UpdateReq {
+                    id: v.id,
+                    name: v.name.clone(),
+                    mobile: v.mobile.clone(),
+                });
+                force_update.force_update();
+            }
+        })
+    };
+
+    let edit_close = {
+        let edit_form = edit_form.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            *edit_form.borrow_mut() = None;
+            force_update.force_update();
+        })
+    };
+
+    let name_change = {
+        let edit_form = edit_form.clone();
+        Callback::from(move |e: Event| {
+            let el: HtmlInputElement = e.target_unchecked_into();
+            if let Some(form) = &mut *edit_form.borrow_mut() {
+                form.name = Some(el.value());
+            }
+        })
+    };
+
+    let mobile_change = {
+        let edit_form = edit_form.clone();
+        Callback::from(move |e: Event| {
+            let el: HtmlInputElement = e.target_unchecked_into();
+            if let Some(form) = &mut *edit_form.borrow_mut() {
+                form.mobile = Some(el.value());
+            }
+        })
+    };
+
+    // the page shows the new values while the request is out, and the old ones again if it fails
+    let edit_save = {
+        let edit_form = edit_form.clone();
+        let message = message.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            let req = match edit_form.borrow_mut().take() {
+                Some(v) => v,
+                None => return,
+            };
+            let optimistic = query::patch(
+                &keys::user_detail(req.id),
+                |value: &Option<UserDetailValue>| {
+                    value.clone().map(|v| UserDetailValue {
+                        name: req.name.clone(),
+                        mobile: req.mobile.clone(),
+                        ..v
+                    })
+                },
+            );
+            let message = message.clone();
+            let force_update = force_update.clone();
+            update.run_optimistic(req, optimistic, move |res| {
+                *message.borrow_mut() = Some(match res {
+                    Ok(_) => message_list::ok(&i18n::t("user_detail.updated")),
+                    Err(err) => message_list::rolled_back(&err),
+                });
+                force_update.force_update();
+            });
+        })
+    };
+
+    let open_confirm = {
+        let confirm = confirm.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |operation: ConfirmOperation| {
+            *confirm.borrow_mut() = Some(operation);
+            force_update.force_update();
+        })
+    };
+
+    let confirm_close = {
+        let confirm = confirm.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            *confirm.borrow_mut() = None;
+            force_update.force_update();
+        })
+    };
+
+    let busy = delete.pending() || reset_pwd.pending();
+    // no undo window here unlike the list, the page is left as soon as the user is gone
+    let confirm_confirm = {
+        let confirm = confirm.clone();
+        let message = message.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            let operation = confirm.borrow_mut().take();
+            let message = message.clone();
+            let force_update = force_update.clone();
+            match operation {
+                Some(ConfirmOperation::Delete) => delete.run((), move |res| match res {
+                    Ok(_) => common::redirect("/main/user"),
+                    Err(err) => {
+                        *message.borrow_mut() = Some(message_list::api_error(&err));
+                        force_update.force_update();
+                    }
+                }),
+                Some(ConfirmOperation::ResetPwd) => reset_pwd.run((), move |res| {
+                    *message.borrow_mut() = Some(match res {
+                        Ok(_) => message_list::ok(&i18n::t("user_detail.reset_pwd_sent")),
+                        Err(err) => message_list::api_error(&err),
+                    });
+                    force_update.force_update();
+                }),
+                None => force_update.force_update(),
+            }
+        })
+    };
+
+    let on_delete = {
+        let open_confirm = open_confirm.clone();
+        Callback::from(move |_| open_confirm.emit(ConfirmOperation::Delete))
+    };
+
+    let on_reset_pwd = {
+        let open_confirm = open_confirm.clone();
+        Callback::from(move |_| open_confirm.emit(ConfirmOperation::ResetPwd))
+    };
+
+    let confirm_content = match *confirm.borrow() {
+        Some(ConfirmOperation::Delete) => i18n.t("user_list.delete_confirm"),
+        Some(ConfirmOperation::ResetPwd) => i18n.t("user_detail.reset_pwd_confirm"),
+        None => String::default(),
+    };
+
+    let value = detail.data.as_deref().and_then(|x| x.as_ref());
+    html! {
+    <>
+    <MessageList value = {(*message.borrow()).clone()}/>
+    if confirm.borrow().is_some() {
+        <ConfirmForm onclose = {confirm_close} onconfirm = {confirm_confirm} content = {confirm_content}/>
+    }
+    if let Some(form) = &*edit_form.borrow() {
+        <div class="modal is-active">
+            <div class="modal-background"></div>
+            <div class="modal-card">
+                <header class="modal-card-head">
+                <p class="modal-card-title">{i18n.t("user_detail.edit_title")}</p>
+                <button class="delete" aria-label="close" onclick={edit_close.clone()}></button>
+                </header>
+                <section class="modal-card-body">
+                <div class="field">
+                    <label class="label">{i18n.t("user.name")}</label>
+                    <div class="control">
+                    <input class="input" value={form.name.clone()} type="text" placeholder="Scarlett" onchange={name_change}/>
+                    </div>
+                </div>
+                <div class="field">
+                    <label class="label">{i18n.t("user.mobile")}</label>
+                    <div class="control">
+                    <input class="input" value={form.mobile.clone()} type="text" placeholder="13800001111" onchange={mobile_change}/>
+                    </div>
+                </div>
+                </section>
+                <footer class="modal-card-foot">
+                <button class="button is-success" onclick={edit_save}>{i18n.t("account.save")}</button>
+                <button class="button" onclick={edit_close}>{i18n.t("common.cancel")}</button>
+                </footer>
+            </div>
+        </div>
+    }
+    <div class="search-container">
+        <div class="field is-grouped">
+        <p class="control">
+            <a class="button is-light" href="/main/user">{i18n.t("common.back")}</a>
+        </p>
+        <p class="control">
+            <button class="button is-light is-warning" disabled={value.is_none()} onclick={edit}>{i18n.t("user_list.edit")}</button>
+        </p>
+        <p class="control">
+            <button class="button is-light is-info" disabled={value.is_none() || busy} onclick={on_reset_pwd}>{i18n.t("user_detail.reset_pwd")}</button>
+        </p>
+        <p class="control">
+            <button class="button is-light is-danger" disabled={value.is_none() || busy} onclick={on_delete}>{i18n.t("user_list.delete")}</button>
+        </p>
+        </div>
+    </div>
+    {
+        match value {
+            Some(v) => html!{
+                <div class="detail-container">
+                    <div class="box">
+                        <p class="title is-5">{v.name.clone().unwrap_or(i18n.t("header.unnamed"))}</p>
+                        <p class="subtitle is-6">{v.email.clone()}</p>
+                        <div class="columns is-multiline">
+                            <div class="column is-3"><p class="heading">{i18n.t("user.type")}</p><p>{v.r#type.clone()}</p></div>
+                            <div class="column is-3"><p class="heading">{i18n.t("user.status")}</p><p>{v.status.clone()}</p></div>
+                            <div class="column is-3"><p class="heading">{i18n.t("user.mobile")}</p><p>{v.mobile.clone().unwrap_or_default()}</p></div>
+                            <div class="column is-3"><p class="heading">{i18n.t("user.laston")}</p><p>{v.laston.map(|x| i18n.format_datetime(x)).unwrap_or_default()}</p></div>
+                            <div class="column is-3"><p class="heading">{i18n.t("user.created_at")}</p><p>{i18n.format_datetime(v.created_at)}</p></div>
+                            <div class="column is-3"><p class="heading">{i18n.t("user.updated_at")}</p><p>{v.updated_at.map(|x| i18n.format_datetime(x)).unwrap_or_default()}</p></div>
+                        </div>
+                        <p class="heading">{i18n.t("user_detail.roles")}</p>
+                        <div class="tags">
+                        {
+                            v.roles.iter().map(|x| html!{
+                                <span class="tag is-info is-light">{x.name.clone()}</span>
+                            }).collect::<Html>()
+                        }
+                        </div>
+                    </div>
+                    <div class="box">
+                        <p class="title is-6">{i18n.t("user_detail.sessions")}</p>
+                        <SessionList user_id={Some(v.id)}/>
+                    </div>
+                    <div class="box">
+                        <p class="title is-6">{i18n.t("user_detail.activities")}</p>
+                        <ul class="timeline">
+                        {
+                            v.activities.iter().map(|x| html!{
+                                <li class="timeline-item">
+                                    <p class="heading">{i18n.format_datetime(x.created_at)}{x.ip.clone().map(|ip| format!(" · {ip}")).unwrap_or_default()}</p>
+                                    <p><b>{x.action.clone()}</b>{" "}{x.description.clone().unwrap_or_default()}</p>
+                                </li>
+                            }).collect::<Html>()
+                        }
+                        </ul>
+                    </div>
+                </div>
+            },
+            None => if detail.loading {
+                html!{ <div class="table-loading"></div> }
+            } else {
+                html!{}
+            },
+        }
+    }
+    </>
+    }
+}
@@ -1,10 +1,10 @@
-use crate::component::message_item::MessageItemValue;
-use crate::component::message_list::{self, MessageList};
-use crate::util::common;
-use user_cli::apis::user_controller_api::{self, UpdateError};
-use user_cli::apis::Error;
-use user_cli::models::{User, UserUpdateReq};
-use wasm_bindgen_futures::spawn_local;
+use crate::query::use_mutation::use_mutation;
+use crate::query::{self, keys};
+use crate::user_list::UserPage;
+use crate::util::api_error::ApiError;
+use crate::util::request::RequestOptions;
+use evolve_axum_cli::apis::user_controller_api;
+use evolve_axum_cli::models::{User, UserUpdateReq};
 use web_sys::{Event, HtmlInputElement};
 use yew::prelude::*;
 use yew::Properties;
@@ -15,15 +15,15 @@ pub struct UserFormProps {
     pub value: User,
     #[prop_or_default]
     pub onupdate: Callback<()>,
+    // a save that failed after the form was closed
+    #[prop_or_default]
+    pub onerror: Callback<ApiError>,
     pub onclose: Callback<()>,
 }
 
 #[function_component(UserForm)]
 pub fn user_form(props: &UserFormProps) -> Html {
-    let messages: std::rc::Rc<std::cell::RefCell<Option<MessageItemValue>>> =
-        use_mut_ref(|| Default::default());
     let value = use_mut_ref(|| props.value.clone());
-    let force_update = use_force_update();
 
     let close = {
         let onclose = props.onclose.clone();
@@ -31,50 +31,43 @@ pub fn user_form(props: &UserFormProps) -> Html {
             onclose.emit(());
         })
     };
+    let update_user = use_mutation(vec![keys::users()], |req: UserUpdateReq| async move {
+        RequestOptions::new()
+            .cli("user_controller_api::update", true, |config| {
+                let req = req.clone();
+                async move { user_controller_api::update(&config, req).await }
+            })
+            .await
+    });
+    // the list shows the change at once; a refused one is rolled back and reported to the list
     let update = {
         let value = value.clone();
-        let messages = messages.clone();
         let onupdate = props.onupdate.clone();
-        let force_update = force_update.clone();
+        let onerror = props.onerror.clone();
         Callback::from(move |_e: MouseEvent| {
-            let messages = messages.clone();
-            let onupdate = onupdate.clone();
-            let force_update = force_update.clone();
-            let value = value.clone();
+            let onerror = onerror.clone();
             let value = value.borrow();
             let req = UserUpdateReq {
                 id: value.id,
                 mobile: value.mobile.clone(),
                 name: value.name.clone(),
             };
-            spawn_local(async move {
-                match user_controller_api::update(&common::get_cli_config().unwrap(), req).await {
-                    Ok(_) => {
-                        onupdate.emit(());
-                    }
-                    Err(err) => {
-                        match err {
-                            Error::ResponseError(res_err) => match res_err.entity {
-                                Some(UpdateError::Status400(e))
-                                | Some(UpdateError::Status401(e))
-                                | Some(UpdateError::Status500(e)) => {
-                                    *messages.borrow_mut() =
-                                        Some(message_list::error(&format!("{}", e.msg)));
-                                }
-                                _ => {
-                                    *messages.borrow_mut() =
-                                        Some(message_list::error(&format!("{}", res_err.content)));
-                                }
-                            },
-                            _ => {
-                                *messages.borrow_mut() =
-                                    Some(message_list::error(&format!("{}", err)));
-                            }
-                        }
-                        force_update.force_update();
-                    }
-                };
+            let optimistic = query::patch(&keys::user_searches(), |page: &UserPage| {
+                let mut page = page.clone();
+                for x in page.data.iter_mut().filter(|x| x.user.id == req.id) {
+                    x.user.name = req.name.clone();
+                    x.user.mobile = req.mobile.clone();
+                    x.formatter.name = req.name.clone().unwrap_or_default();
+                    x.formatter.mobile = req.mobile.clone().unwrap_or_default();
+                }
+                page
+            });
+            update_user.run_optimistic(req, optimistic, move |res| {
+                if let Err(err) = res {
+                    onerror.emit(err);
+                }
             });
+            onupdate.emit(());
         })
     };
     let name_change = {
@@ -96,7 +89,6 @@ pub fn user_form(props: &UserFormProps) -> Html {
         <div class="modal is-active">
             <div class="modal-background"></div>
             <div class="modal-card">
-                <MessageList value={messages.borrow().clone()}/>
                 <header class="modal-card-head">
                 <p class="modal-card-title">{"User Edit"}</p>
                 <button class="delete" aria-label="close" onclick={close.clone()}></button>
@@ -144,7 +136,7 @@ pub fn user_form(props: &UserFormProps) -> Html {
 
                 </section>
                 <footer class="modal-card-foot">
-                <button class="button is-success"  onclick={update}>{"Save changes"}</button>
+                <button class="button is-success" onclick={update}>{"Save changes"}</button>
                 <button class="button" onclick={close} >{"Cancel"}</button>
                 </footer>
             </div>
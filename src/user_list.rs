@@ -1,74 +1,96 @@
 use crate::component::message_list::{self, MessageList};
 use crate::component::pager::{self, Page, Pager};
 use crate::confirm_form::ConfirmForm;
+use crate::i18n::{self, use_i18n::use_i18n};
+use crate::query::use_mutation::use_mutation;
+use crate::query::use_query::use_query;
+use crate::query::{self, keys};
+use crate::realtime::use_entity_change::use_entity_change;
+use crate::realtime::Entity;
 use crate::user_form::UserForm;
 
+use crate::util::api_error::ApiError;
 use crate::util::common;
-use serde::Serialize;
+use crate::util::request::RequestOptions;
+use evolve_axum_cli::apis::user_controller_api;
+use evolve_axum_cli::models::{SearchedUser, User, UserDeleteReq};
 use std::cell::RefCell;
 use std::rc::Rc;
-use user_cli::apis::user_controller_api;
-use user_cli::models::{User, UserDeleteReq};
-use wasm_bindgen_futures::spawn_local;
 use yew::prelude::*;
 
-#[derive(Serialize)]
-pub struct DeleteReq {
-    pub ids: Vec<i64>,
+// seconds a delete can still be undone before it is sent
+const UNDO_SECONDS: u32 = 8;
+
+// one page of a search, as cached
+#[derive(Clone)]
+pub struct UserPage {
+    pub data: Vec<SearchedUser>,
+    pub total: i64,
 }
 
 #[function_component(UserList)]
 pub fn user_list() -> Html {
-    let refresh_list = use_state(|| false);
+    let i18n = use_i18n();
     let force_update = use_force_update();
     let selected_row: Rc<RefCell<Option<User>>> = use_mut_ref(|| None);
     let message = use_mut_ref(|| None);
     let key_word = use_mut_ref(|| String::default());
     let user_form_closed = use_mut_ref(|| true);
     let confirm_form_closed = use_mut_ref(|| true);
-    let loading = use_mut_ref(|| false);
     let index = use_mut_ref(|| 1);
-    let total = use_mut_ref(|| 0);
     let size = use_mut_ref(|| pager::DEFAULT_PAGE_SIZE);
-    let data = use_mut_ref(|| Default::default());
+    let search = {
+        let key_word = key_word.borrow().clone();
+        let index = *index.borrow();
+        let size = *size.borrow();
+        use_query(keys::user_search(&key_word, index, size), move |opts| {
+            let key_word = key_word.clone();
+            async move {
+                let res = opts
+                    .cli("user_controller_api::search", true, |config| {
+                        let key_word = key_word.clone();
+                        async move {
+                            user_controller_api::search(&config, &key_word, index, size as i64)
+                                .await
+                        }
+                    })
+                    .await?;
+                Ok(UserPage {
+                    data: res.data,
+                    total: res.total,
+                })
+            }
+        })
+    };
+    let delete_user = use_mutation(vec![keys::users()], |user_id: i64| async move {
+        RequestOptions::new()
+            .cli("user_controller_api::delete", true, |config| async move {
+                user_controller_api::delete(&config, UserDeleteReq { ids: vec![user_id] }).await
+            })
+            .await
+    });
+
+    // edits by other admins show up without a manual refresh
+    use_entity_change(
+        Entity::User,
+        Callback::from(|_| query::invalidate(&keys::users())),
+    );
+    use_entity_change(
+        Entity::Role,
+        Callback::from(|_| query::invalidate(&keys::users())),
+    );
+
     {
         let message = message.clone();
-        let key_word = key_word.clone();
-        let index = index.clone();
-        let size = size.clone();
-        let total = total.clone();
-        let loading = loading.clone();
-        let data = data.clone();
         let force_update = force_update.clone();
-        let refresh_list = refresh_list.clone();
-        let selected_row = selected_row.clone();
-        use_effect_with(refresh_list, move |_| {
-            spawn_local(async move {
-                match user_controller_api::search(
-                    &common::get_cli_config().unwrap(),
-                    key_word.borrow().as_str(),
-                    *index.borrow(),
-                    *size.borrow() as i64,
-                )
-                .await
-                {
-                    Ok(res) => {
-                        *data.borrow_mut() = res.data;
-                        *total.borrow_mut() = res.total;
-                        *loading.borrow_mut() = false;
-                        force_update.force_update();
-                    }
-                    Err(err) => {
-                        *message.borrow_mut() = Some(message_list::error(&format!("{}", err)));
-                        *loading.borrow_mut() = false;
-                    }
-                };
-            });
-            *selected_row.borrow_mut() = None;
-        })
+        use_effect_with(search.error.clone(), move |err| {
+            if let Some(err) = err {
+                *message.borrow_mut() = Some(message_list::api_error(err));
+                force_update.force_update();
+            }
+        });
     }
 
-
     let user_form_close = {
         let user_form_closed = user_form_closed.clone();
         let force_update = force_update.clone();
@@ -78,14 +100,16 @@ pub fn user_list() -> Html {
         })
     };
 
+    // the row already shows the change, the form's mutation reconciles it
     let user_form_update = {
         let user_form_closed = user_form_closed.clone();
-        let refresh = refresh_list.clone();
-        let index = index.clone();
+        let selected_row = selected_row.clone();
+        let force_update = force_update.clone();
         Callback::from(move |_e| {
             *user_form_closed.borrow_mut() = true;
-            *index.borrow_mut() = 1;
-            refresh.set(!*refresh);
+            // holds the values from before the edit
+            *selected_row.borrow_mut() = None;
+            force_update.force_update();
         })
     };
 
@@ -98,31 +122,59 @@ pub fn user_list() -> Html {
         })
     };
 
+    // the row disappears at once, the request only goes out once the undo window is over,
+    // or right away when the page is left within it
     let confirm_form_confirm = {
         let confirm_form_closed = confirm_form_closed.clone();
-        let refresh = refresh_list.clone();
-        let index = index.clone();
         let selected_row = selected_row.clone();
         let message = message.clone();
+        let force_update = force_update.clone();
         Callback::from(move |_| {
-            let user_id = selected_row.borrow().clone().unwrap().id;
-            let message = message.clone();
-            spawn_local(async move {
-                match user_controller_api::delete(
-                    &common::get_cli_config().unwrap(),
-                    UserDeleteReq { ids: vec![user_id] },
-                )
-                .await
-                {
-                    Ok(_) => {}
-                    Err(err) => {
-                        *message.borrow_mut() = Some(message_list::error(&format!("{}", err)));
-                    }
+            let user = match selected_row.borrow_mut().take() {
+                Some(v) => v,
+                None => return,
+            };
+            let optimistic = query::patch(&keys::user_searches(), |page: &UserPage| {
+                let data = page
+                    .data
+                    .iter()
+                    .filter(|x| x.user.id != user.id)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                UserPage {
+                    total: page.total - (page.data.len() - data.len()) as i64,
+                    data,
                 }
             });
+            let undo = {
+                let message = message.clone();
+                let force_update = force_update.clone();
+                delete_user.run_deferred(user.id, optimistic, UNDO_SECONDS * 1000, move |res| {
+                    match res {
+                        Err(err) if err.cancelled => {}
+                        Err(err) => *message.borrow_mut() = Some(message_list::rolled_back(&err)),
+                        Ok(_) => return,
+                    }
+                    force_update.force_update();
+                })
+            };
+            let name = user.name.clone().unwrap_or(user.email.clone());
+            *message.borrow_mut() = Some(message_list::undo(
+                &i18n::t_args("user_list.deleted", &[("name", name.as_str())]),
+                UNDO_SECONDS,
+                Callback::from(move |_| undo.cancel()),
+            ));
             *confirm_form_closed.borrow_mut() = true;
-            *index.borrow_mut() = 1;
-            refresh.set(!*refresh);
+            force_update.force_update();
+        })
+    };
+
+    let user_form_error = {
+        let message = message.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |err: ApiError| {
+            *message.borrow_mut() = Some(message_list::rolled_back(&err));
+            force_update.force_update();
         })
     };
 
@@ -131,14 +183,19 @@ pub fn user_list() -> Html {
     let key_word_change = {
         let key_word_ref = key_word_ref.clone();
         let key_word = key_word.clone();
-        let refresh = refresh_list.clone();
         let index = index.clone();
+        let selected_row = selected_row.clone();
+        let force_update = force_update.clone();
         Callback::from(move |_| {
             let input = key_word_ref.cast::<web_sys::HtmlInputElement>();
             if let Some(input) = input {
+                if *key_word.borrow() == input.value() {
+                    return;
+                }
                 *key_word.borrow_mut() = input.value();
                 *index.borrow_mut() = 1;
-                refresh.set(!*refresh);
+                *selected_row.borrow_mut() = None;
+                force_update.force_update();
             }
         })
     };
@@ -150,7 +207,8 @@ pub fn user_list() -> Html {
         let force_update = force_update.clone();
         Callback::from(move |_e| {
             if selected_row.borrow().is_none() {
-                *message.borrow_mut() = Some(message_list::warn("please select a record"));
+                *message.borrow_mut() =
+                    Some(message_list::warn(&i18n::t("common.please_select_record")));
             } else {
                 *user_form_closed.borrow_mut() = false;
             }
@@ -158,6 +216,20 @@ pub fn user_list() -> Html {
         })
     };
 
+    let detail = {
+        let selected_row = selected_row.clone();
+        let message = message.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_e| match &*selected_row.borrow() {
+            Some(user) => common::redirect(&format!("/main/user/{}", user.id)),
+            None => {
+                *message.borrow_mut() =
+                    Some(message_list::warn(&i18n::t("common.please_select_record")));
+                force_update.force_update()
+            }
+        })
+    };
+
     let delete = {
         let selected_row = selected_row.clone();
         let message = message.clone();
@@ -165,7 +237,8 @@ pub fn user_list() -> Html {
         let force_update = force_update.clone();
         Callback::from(move |_e| {
             if selected_row.borrow().is_none() {
-                *message.borrow_mut() = Some(message_list::warn("please select a record"));
+                *message.borrow_mut() =
+                    Some(message_list::warn(&i18n::t("common.please_select_record")));
             } else {
                 *confirm_form_closed.borrow_mut() = false;
             }
@@ -176,43 +249,54 @@ pub fn user_list() -> Html {
     let page_change = {
         let index = index.clone();
         let size = size.clone();
-        let refresh = refresh_list.clone();
+        let selected_row = selected_row.clone();
+        let force_update = force_update.clone();
         Callback::from(move |page: Page| {
             *index.borrow_mut() = page.index as i64;
             *size.borrow_mut() = page.size;
-            refresh.set(!*refresh);
+            *selected_row.borrow_mut() = None;
+            force_update.force_update();
         })
     };
 
     let selected_id = selected_row.borrow().clone().map(|x| x.id);
+    let data = search
+        .data
+        .as_deref()
+        .map(|x| x.data.clone())
+        .unwrap_or_default();
+    let total = search.data.as_deref().map(|x| x.total).unwrap_or_default();
     html! {
     <>
     <MessageList value = {(*message.borrow()).clone()} ws = true/>
     if let Some(v) = (*selected_row.clone().borrow()).clone()  {
         if !(*user_form_closed.borrow()){
-            <UserForm value = {v.clone()} onclose={user_form_close} onupdate = {user_form_update}/>
+            <UserForm value = {v.clone()} onclose={user_form_close} onupdate = {user_form_update} onerror = {user_form_error}/>
         }
         if !(*confirm_form_closed.borrow()){
-            <ConfirmForm onclose = {confirm_form_close} onconfirm = {confirm_form_confirm.clone()} content = {"Deleted users <b>can not</b> be recovered!!!<br/> are you sure you want to delete it?"}/>
+            <ConfirmForm onclose = {confirm_form_close} onconfirm = {confirm_form_confirm.clone()} content = {i18n.t("user_list.delete_confirm")}/>
         }
     }
     <div class="search-container">
         <div class="search-input field is-grouped">
         <p class="control is-expanded">
-            <input ref={key_word_ref} class="input" type="text" onkeyup={key_word_change} placeholder="Search"/>
+            <input ref={key_word_ref} class="input" type="text" onkeyup={key_word_change} placeholder={i18n.t("user_list.search")}/>
         </p>
 
         <p class="control">
-            <button class="button is-light is-warning" onclick={edit}>{"Edit"}</button>
+            <button class="button is-light is-info" onclick={detail}>{i18n.t("user_list.detail")}</button>
+        </p>
+        <p class="control">
+            <button class="button is-light is-warning" onclick={edit}>{i18n.t("user_list.edit")}</button>
         </p>
         <p class="control">
-            <button class="button is-light is-danger" onclick={delete}>{"Delete"}</button>
+            <button class="button is-light is-danger" onclick={delete}>{i18n.t("user_list.delete")}</button>
         </p>
         </div>
     </div>
     <div class="table-container">
         {
-            if *loading.borrow() {
+            if search.fetching {
                 html!{
                     <div class="table-loading">
                     </div>
@@ -225,20 +309,20 @@ pub fn user_list() -> Html {
         <table class="table is-bordered is-striped is-narrow is-hoverable">
         <thead>
             <tr>
-            <th><abbr title="Type">{"Type"}</abbr></th>
-            <th><abbr title="Email">{"Email"}</abbr></th>
-            <th><abbr title="Name">{"Name"}</abbr></th>
-            <th><abbr title="Mobile">{"Mobile"}</abbr></th>
-            <th><abbr title="Laston">{"Laston"}</abbr></th>
+            <th><abbr title={i18n.t("user.type")}>{i18n.t("user.type")}</abbr></th>
+            <th><abbr title={i18n.t("user.email")}>{i18n.t("user.email")}</abbr></th>
+            <th><abbr title={i18n.t("user.name")}>{i18n.t("user.name")}</abbr></th>
+            <th><abbr title={i18n.t("user.mobile")}>{i18n.t("user.mobile")}</abbr></th>
+            <th><abbr title={i18n.t("user.laston")}>{i18n.t("user.laston")}</abbr></th>
             // todo: sort
-            <th><abbr title="Created_at"><a href="javascript:void(0)">{"Created_at  "}<i class="fa-solid fa-arrow-down"></i></a></abbr></th>
-            <th><abbr title="Updated_at">{"Updated_at"}</abbr></th>
-            <th><abbr title="Status">{"Status"}</abbr></th>
+            <th><abbr title={i18n.t("user.created_at")}><a href="javascript:void(0)">{i18n.t("user.created_at")}{"  "}<i class="fa-solid fa-arrow-down"></i></a></abbr></th>
+            <th><abbr title={i18n.t("user.updated_at")}>{i18n.t("user.updated_at")}</abbr></th>
+            <th><abbr title={i18n.t("user.status")}>{i18n.t("user.status")}</abbr></th>
             </tr>
         </thead>
         <tbody>
         {
-            data.clone().borrow().iter().map(|x|{
+            data.iter().map(|x|{
                 let user = *(x.user.clone());
                 let formatter = *(x.formatter.clone());
                 let select_row = {
@@ -264,8 +348,8 @@ pub fn user_list() -> Html {
                         {common::create_html("td",formatter.email.as_str())}
                         {common::create_html("td",formatter.name.as_str())}
                         {common::create_html("td",formatter.mobile.as_str())}
-                        {common::create_html("td",formatter.laston.as_str())}
-                        {common::create_html("td",formatter.created_at.as_str())}
+                        <td>{user.laston.map(|x| i18n.format_datetime(x)).unwrap_or_default()}</td>
+                        <td>{i18n.format_datetime(user.created_at)}</td>
                         {common::create_html("td",formatter.updated_at.as_str())}
                         {common::create_html("td",formatter.status.as_str())}
                     </tr>
@@ -278,7 +362,7 @@ pub fn user_list() -> Html {
     <div class="pager-container">
     {
         html!{
-            <Pager total = { *total.borrow() as usize } index = {*index.borrow() as usize} onpagechanged = {page_change}/>
+            <Pager total = { total as usize } index = {*index.borrow() as usize} onpagechanged = {page_change}/>
         }
     }
     </div>
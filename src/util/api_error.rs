@@ -0,0 +1,328 @@
+use crate::i18n;
+use crate::util::error::ErrorKind;
+use serde_json::Value;
+use std::fmt::Display;
+
+// answered when a code was already sent and is still valid
+pub const HINT_STATUS: u16 = 452;
+
+// what went wrong with a call to the server, whichever client made it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ApiError {
+    // 0 when no response arrived, e.g. the network is down
+    pub status: u16,
+    // business code from the body, e.g. 452100000
+    pub code: Option<i64>,
+    // from the server when it sent one, otherwise empty; see `user_message`
+    pub message: String,
+    pub field_errors: Vec<FieldError>,
+    pub request_id: Option<String>,
+    // the response body, for endpoints that attach data to errors
+    pub details: Option<Value>,
+    // aborted on purpose, e.g. the page that asked went away; nothing to show
+    pub cancelled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+fn str_field<'a>(body: &'a Value, keys: &[&str]) -> Option<&'a str> {
+    keys.iter()
+        .find_map(|k| body.get(k).and_then(|x| x.as_str()))
+        .filter(|x| !x.is_empty())
+}
+
+// accepts `[{"field": .., "message": ..}]` as well as `{"field": ["message", ..]}`
+fn parse_field_errors(body: &Value) -> Vec<FieldError> {
+    let errors = match ["field_errors", "errors"].iter().find_map(|k| body.get(k)) {
+        Some(v) => v,
+        None => return vec![],
+    };
+    match errors {
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|x| {
+                Some(FieldError {
+                    field: str_field(x, &["field", "name"])?.to_string(),
+                    message: str_field(x, &["message", "msg"])
+                        .unwrap_or_default()
+                        .to_string(),
+                })
+            })
+            .collect(),
+        Value::Object(map) => map
+            .iter()
+            .flat_map(|(field, v)| {
+                let messages = match v {
+                    Value::Array(x) => x.iter().filter_map(|m| m.as_str()).collect::<Vec<_>>(),
+                    Value::String(x) => vec![x.as_str()],
+                    _ => vec![],
+                };
+                messages.into_iter().map(|m| FieldError {
+                    field: field.clone(),
+                    message: m.to_string(),
+                })
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+impl ApiError {
+    // builds the error from a failed response; the body may be anything, including empty
+    pub fn from_response(status: u16, request_id: Option<String>, body: &str) -> Self {
+        let details = serde_json::from_str::<Value>(body).ok();
+        let mut res = ApiError {
+            status,
+            request_id,
+            ..Default::default()
+        };
+        if let Some(v) = &details {
+            res.message = str_field(v, &["msg", "message", "error"])
+                .unwrap_or_default()
+                .to_string();
+            res.code = ["err_code", "code"]
+                .iter()
+                .find_map(|k| v.get(k).and_then(|x| x.as_i64()));
+            res.field_errors = parse_field_errors(v);
+            if res.request_id.is_none() {
+                res.request_id = str_field(v, &["request_id"]).map(|x| x.to_string());
+            }
+        } else if !body.trim().is_empty() && body.len() <= 200 {
+            // plain text bodies from proxies are still better than nothing
+            res.message = body.trim().to_string();
+        }
+        res.details = details;
+        res
+    }
+
+    pub fn network(msg: impl Display) -> Self {
+        ApiError {
+            message: msg.to_string(),
+            ..Default::default()
+        }
+    }
+
+    // the client gave up waiting, reported like a 408 from the server
+    pub fn timeout() -> Self {
+        ApiError {
+            status: 408,
+            ..Default::default()
+        }
+    }
+
+    pub fn cancelled() -> Self {
+        ApiError {
+            cancelled: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_network(&self) -> bool {
+        self.status == 0 && !self.cancelled
+    }
+
+    // business codes start with the status they stand for, e.g. 452100000
+    pub fn is_hint(&self) -> bool {
+        self.status == HINT_STATUS
+            || self
+                .code
+                .is_some_and(|x| x / 1_000_000 == HINT_STATUS as i64)
+    }
+
+    pub fn field_error(&self, field: &str) -> Option<&str> {
+        self.field_errors
+            .iter()
+            .find(|x| x.field == field)
+            .map(|x| x.message.as_str())
+    }
+
+    // what the page shows; the server message when there is one, a localized fallback otherwise
+    pub fn user_message(&self) -> String {
+        let msg = if self.cancelled {
+            i18n::t("api_error.cancelled")
+        } else if !self.message.is_empty() {
+            self.message.clone()
+        } else if let Some(v) = self.field_errors.first() {
+            format!("{}: {}", v.field, v.message)
+        } else {
+            i18n::t(match self.status {
+                0 => "api_error.network",
+                400 | 422 => "api_error.bad_request",
+                401 => "api_error.unauthorized",
+                403 => "api_error.forbidden",
+                404 => "api_error.not_found",
+                408 | 504 => "api_error.timeout",
+                429 => "api_error.too_many_requests",
+                500..=599 => "api_error.server",
+                _ => "api_error.unknown",
+            })
+        };
+        match &self.request_id {
+            // quoted in bug reports to find the server log
+            Some(id) if self.status >= 500 => i18n::t_args(
+                "api_error.with_request_id",
+                &[("message", msg.as_str()), ("id", id.as_str())],
+            ),
+            _ => msg,
+        }
+    }
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.user_message())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<gloo_net::Error> for ApiError {
+    fn from(err: gloo_net::Error) -> Self {
+        match err {
+            gloo_net::Error::SerdeError(e) => ApiError::from(e),
+            e => ApiError::network(e),
+        }
+    }
+}
+
+impl From<serde_json::Error> for ApiError {
+    fn from(err: serde_json::Error) -> Self {
+        ApiError {
+            message: i18n::t_args(
+                "api_error.invalid_response",
+                &[("error", err.to_string().as_str())],
+            ),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<ErrorKind> for ApiError {
+    fn from(err: ErrorKind) -> Self {
+        ApiError {
+            message: err.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<ApiError> for ErrorKind {
+    fn from(err: ApiError) -> Self {
+        if err.is_hint() {
+            ErrorKind::Hint(err.user_message())
+        } else {
+            ErrorKind::ServerError(err.user_message())
+        }
+    }
+}
+
+// the generated clients share one error shape; entities are ignored in favour of the raw body
+macro_rules! impl_from_cli_error {
+    ($cli:ident) => {
+        impl<T: std::fmt::Debug> From<$cli::apis::Error<T>> for ApiError {
+            fn from(err: $cli::apis::Error<T>) -> Self {
+                match err {
+                    $cli::apis::Error::ResponseError(res) => {
+                        ApiError::from_response(res.status.as_u16(), None, &res.content)
+                    }
+                    $cli::apis::Error::Serde(e) => ApiError::from(e),
+                    e => ApiError::network(e),
+                }
+            }
+        }
+    };
+}
+
+impl_from_cli_error!(evolve_axum_cli);
+
+pub type ApiResult<T> = Result<T, ApiError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field_error(field: &str, message: &str) -> FieldError {
+        FieldError {
+            field: field.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn from_response_json() {
+        let body = r#"{"err_code": 400100001, "msg": "invalid input", "request_id": "req-1",
+            "field_errors": [{"field": "email", "message": "already taken"}, {"name": "pwd", "msg": "too short"}]}"#;
+        let err = ApiError::from_response(400, None, body);
+        assert_eq!(err.status, 400);
+        assert_eq!(err.code, Some(400100001));
+        assert_eq!(err.message, "invalid input");
+        assert_eq!(err.request_id.as_deref(), Some("req-1"));
+        assert_eq!(
+            err.field_errors,
+            vec![
+                field_error("email", "already taken"),
+                field_error("pwd", "too short")
+            ]
+        );
+        assert_eq!(err.field_error("pwd"), Some("too short"));
+        assert_eq!(err.field_error("name"), None);
+        assert!(err.details.is_some());
+    }
+
+    #[test]
+    fn from_response_field_error_map() {
+        let body =
+            r#"{"code": 422, "errors": {"email": ["required", "invalid"], "name": "too long"}}"#;
+        let err = ApiError::from_response(422, None, body);
+        assert_eq!(err.code, Some(422));
+        assert_eq!(err.message, "");
+        assert_eq!(
+            err.field_errors,
+            vec![
+                field_error("email", "required"),
+                field_error("email", "invalid"),
+                field_error("name", "too long")
+            ]
+        );
+    }
+
+    #[test]
+    fn from_response_request_id() {
+        // the header wins over the body
+        let body = r#"{"message": "boom", "request_id": "from-body"}"#;
+        let err = ApiError::from_response(500, Some("from-header".to_string()), body);
+        assert_eq!(err.request_id.as_deref(), Some("from-header"));
+        assert_eq!(err.message, "boom");
+
+        let err = ApiError::from_response(500, None, r#"{"request_id": ""}"#);
+        assert_eq!(err.request_id, None);
+    }
+
+    #[test]
+    fn from_response_not_json() {
+        let err = ApiError::from_response(502, None, " Bad Gateway \n");
+        assert_eq!(err.message, "Bad Gateway");
+        assert_eq!(err.code, None);
+        assert!(err.field_errors.is_empty());
+        assert!(err.details.is_none());
+
+        assert_eq!(ApiError::from_response(502, None, "").message, "");
+        assert_eq!(
+            ApiError::from_response(502, None, &"x".repeat(201)).message,
+            ""
+        );
+    }
+
+    #[test]
+    fn hint() {
+        assert!(ApiError::from_response(HINT_STATUS, None, "").is_hint());
+        assert!(ApiError::from_response(200, None, r#"{"code": 452100000}"#).is_hint());
+        assert!(!ApiError::from_response(400, None, r#"{"code": 400100000}"#).is_hint());
+        assert!(ApiError::from_response(0, None, "").is_network());
+        assert!(!ApiError::cancelled().is_network());
+    }
+}
@@ -1,53 +1,27 @@
+use crate::query;
+use crate::storage::keys;
+use crate::util::config;
 use crate::util::error::ErrorKind;
-use crate::util::error::ToError;
+use crate::util::validation::{self, Validator};
 use evolve_axum_cli::apis::configuration::{ApiKey, Configuration};
-use fancy_regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json;
 use yew::virtual_dom::VNode;
 
 pub type BasicResult<T, E = ErrorKind> = Result<T, E>;
 
-const BASE_URL: &str = "http://localhost:3000";
-
 pub fn validate_email(email: &str) -> BasicResult<()> {
-    if email.is_empty() {
-        return Err("please type in email".to_validation_error());
-    }
-    let reg = Regex::new(r#"\w[-\w.+]*@([A-Za-z0-9][-A-Za-z0-9]+\.)+[A-Za-z]{2,14}"#)?;
-    if !reg.is_match(email)? {
-        return Err("invalid email".to_validation_error());
-    }
+    validation::email().validate(email, &())?;
     Ok(())
 }
 
 pub fn validate_pwd(pwd: &str) -> BasicResult<()> {
-    if pwd.is_empty() {
-        return Err("please type in password".to_validation_error());
-    }
-    let reg = Regex::new(r#"(?=.*[a-z])(?=.*[0-9])[a-zA-Z0-9]{6,18}"#)?; //6位字母+数字,字母开头
-    if !reg.is_match(pwd)? {
-        return Err("invalid passowrd: length>=6, a-z and 0-9 is demanded".to_validation_error());
-    }
+    validation::password().validate(pwd, &())?;
     Ok(())
 }
 
 pub fn validate_pwd_confirm(pwd: &str, pwd_confirm: &str) -> BasicResult<()> {
-    validate_pwd(pwd_confirm)?;
-    if pwd != pwd_confirm {
-        return Err("confirm password must as same as password".to_validation_error());
-    }
-    Ok(())
-}
-
-pub fn validate_code(code: &str) -> BasicResult<()> {
-    if code.is_empty() {
-        return Err("please type in code".to_validation_error());
-    }
-    let reg = Regex::new(r#"^\d{6}$"#)?; //6位字母+数字,字母开头
-    if !reg.is_match(code)? {
-        return Err("invalid code: length=6 and 0-9 is demanded".to_validation_error());
-    }
+    validation::password_confirm("pwd").validate(pwd_confirm, &[("pwd", pwd)])?;
     Ok(())
 }
 
@@ -61,23 +35,35 @@ pub struct CurrentUser {
     pub laston: Option<i64>,
     pub created_at: i64,
     pub updated_at: Option<i64>,
+    // absent on servers without permissions, everything is allowed then
+    #[serde(default)]
+    pub permissions: Option<Vec<String>>,
+}
+
+impl CurrentUser {
+    pub fn can(&self, permission: &str) -> bool {
+        self.permissions
+            .as_ref()
+            .is_none_or(|x| x.iter().any(|p| p == permission || p == "*"))
+    }
 }
 
 pub fn get_token() -> BasicResult<String> {
-    let str = get_local_storage(crate::util::TOKEN_KEY)
+    let str = keys::TOKEN
+        .get()
         .ok_or(ErrorKind::OtherError(String::from("get token failed")))?;
     Ok(str)
 }
 
 pub fn get_cli_config_without_token() -> BasicResult<Configuration> {
     let mut ret = Configuration::default();
-    ret.base_path = BASE_URL.to_string();
+    ret.base_path = config::base_url().to_string();
     Ok(ret)
 }
 
 pub fn get_cli_config() -> BasicResult<Configuration> {
     let mut ret = Configuration::default();
-    ret.base_path = BASE_URL.to_string();
+    ret.base_path = config::base_url().to_string();
     ret.api_key = Some(ApiKey {
         prefix: None,
         key: get_token()?,
@@ -86,34 +72,25 @@ pub fn get_cli_config() -> BasicResult<Configuration> {
 }
 
 pub fn get_current_user() -> BasicResult<CurrentUser> {
-    let str = get_local_storage("current_user").ok_or(ErrorKind::OtherError(String::from(
-        "current user str is null",
-    )))?;
-    let res = serde_json::from_str::<CurrentUser>(&str)?;
+    let res = keys::CURRENT_USER
+        .get()
+        .ok_or(ErrorKind::OtherError(String::from("current user str is null")))?;
     Ok(res)
 }
 
 pub fn delete_current_user() -> BasicResult<()> {
-    del_local_storage(crate::util::TOKEN_KEY);
-    del_local_storage("current_user");
-    del_local_storage("selected_navbar_name");
-    del_local_storage("selected_navbar_parent_name");
+    keys::TOKEN.remove();
+    keys::CURRENT_USER.remove();
+    // cached results belong to the user that signed out
+    query::clear();
     Ok(())
 }
 
-pub fn set_local_storage(key: &str, value: &str) {
-    let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
-    local_storage.set_item(key, value).unwrap();
-}
-
-pub fn get_local_storage(key: &str) -> Option<String> {
-    let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
-    local_storage.get_item(key).unwrap()
-}
-
-pub fn del_local_storage(key: &str) {
-    let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
-    local_storage.delete(key).unwrap()
+pub fn logout() {
+    delete_current_user().unwrap_or_else(|x| {
+        log::error!("{:?}", x);
+    });
+    redirect("/login");
 }
 
 pub fn redirect(path: &str) {
@@ -137,16 +114,32 @@ pub fn create_html(tag: &str, inner_html: &str) -> VNode {
     vnode
 }
 
-#[allow(dead_code)]
-#[derive(PartialEq)]
-pub enum ValidStatus {
-    Valid,
-    InValid(String),
-    None,
+// for text from the outside, e.g. a user agent, that ends up in `create_html`
+pub fn escape_html(text: &str) -> String {
+    let mut ret = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => ret.push_str("&amp;"),
+            '<' => ret.push_str("&lt;"),
+            '>' => ret.push_str("&gt;"),
+            '"' => ret.push_str("&quot;"),
+            '\'' => ret.push_str("&#39;"),
+            c => ret.push(c),
+        }
+    }
+    ret
 }
 
-impl Default for ValidStatus {
-    fn default() -> Self {
-        ValidStatus::None
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_html_neutralizes_markup() {
+        assert_eq!(
+            escape_html(r#"<img src=x onerror="alert('1')">&"#),
+            "&lt;img src=x onerror=&quot;alert(&#39;1&#39;)&quot;&gt;&amp;"
+        );
+        assert_eq!(escape_html("Firefox 131 / Linux"), "Firefox 131 / Linux");
     }
 }
@@ -0,0 +1,15 @@
+// settings baked in at build time, e.g. `EVOLVE_PASSWORD_POLICY=.. trunk build`
+
+// where the server lives, e.g. `EVOLVE_BASE_URL=https://evolve.example.com`; the generated
+// client, the api and the socket are all served below it
+pub fn base_url() -> &'static str {
+    option_env!("EVOLVE_BASE_URL")
+        .unwrap_or("http://localhost:8881")
+        .trim_end_matches('/')
+}
+
+// json in the shape of `PasswordPolicy`; replaces the policy of the server, fields left out
+// keep their defaults
+pub fn password_policy() -> Option<&'static str> {
+    option_env!("EVOLVE_PASSWORD_POLICY")
+}
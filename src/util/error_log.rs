@@ -0,0 +1,110 @@
+use crate::util::tauri;
+use js_sys::{Function, Promise, Reflect};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use wasm_bindgen::{JsCast, JsValue};
+
+// enough for a bug report without growing for the lifetime of the tab
+const CAPACITY: usize = 100;
+
+thread_local! {
+    static ENTRIES: RefCell<VecDeque<ErrorEntry>> = const { RefCell::new(VecDeque::new()) };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorSource {
+    Panic,
+    Script,
+    Api,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorEntry {
+    pub time: String,
+    pub source: ErrorSource,
+    pub message: String,
+}
+
+impl std::fmt::Display for ErrorEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {:?}: {}", self.time, self.source, self.message)
+    }
+}
+
+pub fn record(source: ErrorSource, message: &str) {
+    let entry = ErrorEntry {
+        time: chrono::Utc::now().to_rfc3339(),
+        source,
+        message: message.to_string(),
+    };
+    if tauri::is_tauri() {
+        // the native side appends it to the log file
+        #[derive(Serialize)]
+        struct Args<'a> {
+            entry: &'a str,
+        }
+        tauri::invoke_detached(
+            "append_error_log",
+            &Args {
+                entry: &entry.to_string(),
+            },
+        );
+    }
+    // `try_` so an error raised while the log is borrowed is dropped rather than panicking again
+    ENTRIES.with(|x| {
+        if let Ok(mut entries) = x.try_borrow_mut() {
+            if entries.len() == CAPACITY {
+                entries.pop_front();
+            }
+            entries.push_back(entry);
+        }
+    });
+}
+
+pub fn entries() -> Vec<ErrorEntry> {
+    ENTRIES.with(|x| {
+        x.try_borrow()
+            .map(|x| x.iter().cloned().collect())
+            .unwrap_or_default()
+    })
+}
+
+// plain text meant to be pasted into a bug report
+pub fn report() -> String {
+    let window = web_sys::window();
+    let url = window
+        .as_ref()
+        .and_then(|x| x.location().href().ok())
+        .unwrap_or_default();
+    let agent = window
+        .as_ref()
+        .and_then(|x| x.navigator().user_agent().ok())
+        .unwrap_or_default();
+    let mut lines = vec![
+        format!("time: {}", chrono::Utc::now().to_rfc3339()),
+        format!("version: {}", env!("CARGO_PKG_VERSION")),
+        format!("url: {}", url),
+        format!("user agent: {}", agent),
+        String::new(),
+    ];
+    lines.extend(entries().iter().rev().map(|x| x.to_string()));
+    lines.join("\n")
+}
+
+// writes the report to the clipboard; false where the clipboard api is missing, e.g. over plain http
+pub fn copy_report() -> bool {
+    let clipboard = match web_sys::window()
+        .and_then(|x| Reflect::get(&x.navigator(), &JsValue::from_str("clipboard")).ok())
+        .filter(|x| x.is_object())
+    {
+        Some(v) => v,
+        None => return false,
+    };
+    Reflect::get(&clipboard, &JsValue::from_str("writeText"))
+        .ok()
+        .and_then(|x| x.dyn_into::<Function>().ok())
+        .and_then(|x| x.call1(&clipboard, &JsValue::from_str(&report())).ok())
+        .is_some_and(|x| x.is_instance_of::<Promise>())
+}
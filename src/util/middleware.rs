@@ -0,0 +1,304 @@
+use crate::util::api_error::ApiError;
+use crate::util::common;
+use futures::channel::oneshot;
+use gloo::timers::callback::Timeout;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use web_sys::AbortController;
+
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+thread_local! {
+    static PIPELINE: RefCell<Vec<Rc<dyn Middleware>>> = RefCell::new(vec![
+        Rc::new(Auth),
+        Rc::new(Tracing),
+        Rc::new(Logging),
+    ]);
+}
+
+// one attempt of a request, as the middlewares see it
+#[derive(Debug, Clone)]
+pub struct RequestInfo {
+    pub method: String,
+    pub url: String,
+    // the same for every attempt, so retries can be told apart from new requests
+    pub request_id: String,
+    pub attempt: u32,
+    // ms since the epoch
+    pub started_at: f64,
+}
+
+impl RequestInfo {
+    pub fn new(method: &str, url: &str) -> Self {
+        RequestInfo {
+            method: method.to_string(),
+            url: url.to_string(),
+            request_id: uuid::Uuid::new_v4().to_string(),
+            attempt: 1,
+            started_at: js_sys::Date::now(),
+        }
+    }
+
+    pub fn elapsed_ms(&self) -> f64 {
+        js_sys::Date::now() - self.started_at
+    }
+}
+
+// hooks around every request; all of them default to doing nothing
+pub trait Middleware {
+    // may add headers to the outgoing request
+    fn on_request(&self, _info: &RequestInfo, _headers: &mut Vec<(String, String)>) {}
+    fn on_response(&self, _info: &RequestInfo, _status: u16) {}
+    fn on_error(&self, _info: &RequestInfo, _err: &ApiError) {}
+}
+
+// appended after the built-in auth, tracing and logging steps
+pub fn push(middleware: impl Middleware + 'static) {
+    PIPELINE.with(|x| x.borrow_mut().push(Rc::new(middleware)));
+}
+
+fn pipeline() -> Vec<Rc<dyn Middleware>> {
+    PIPELINE.with(|x| x.borrow().clone())
+}
+
+pub fn headers(info: &RequestInfo) -> Vec<(String, String)> {
+    let mut headers = vec![];
+    for m in pipeline() {
+        m.on_request(info, &mut headers);
+    }
+    headers
+}
+
+pub fn response(info: &RequestInfo, status: u16) {
+    for m in pipeline() {
+        m.on_response(info, status);
+    }
+}
+
+pub fn error(info: &RequestInfo, err: &ApiError) {
+    for m in pipeline() {
+        m.on_error(info, err);
+    }
+}
+
+// the server still reads the token from `evolve_token`, not `Authorization`
+struct Auth;
+
+impl Middleware for Auth {
+    fn on_request(&self, _info: &RequestInfo, headers: &mut Vec<(String, String)>) {
+        if let Ok(v) = common::get_token() {
+            headers.push((crate::util::TOKEN_KEY.to_string(), format!("Bearer {}", v)));
+        }
+    }
+}
+
+struct Tracing;
+
+impl Middleware for Tracing {
+    fn on_request(&self, info: &RequestInfo, headers: &mut Vec<(String, String)>) {
+        headers.push((REQUEST_ID_HEADER.to_string(), info.request_id.clone()));
+    }
+}
+
+struct Logging;
+
+impl Middleware for Logging {
+    fn on_request(&self, info: &RequestInfo, _headers: &mut Vec<(String, String)>) {
+        log::debug!(
+            "{} {} [{}] attempt {}",
+            info.method,
+            info.url,
+            info.request_id,
+            info.attempt
+        );
+    }
+
+    fn on_response(&self, info: &RequestInfo, status: u16) {
+        log::debug!(
+            "{} {} [{}] -> {} in {:.0}ms",
+            info.method,
+            info.url,
+            info.request_id,
+            status,
+            info.elapsed_ms()
+        );
+    }
+
+    fn on_error(&self, info: &RequestInfo, err: &ApiError) {
+        if !err.cancelled {
+            log::warn!(
+                "{} {} [{}] failed in {:.0}ms: {:?}",
+                info.method,
+                info.url,
+                info.request_id,
+                info.elapsed_ms(),
+                err
+            );
+        }
+    }
+}
+
+#[derive(Default)]
+struct CancelState {
+    cancelled: bool,
+    controllers: Vec<AbortController>,
+    waiters: Vec<oneshot::Sender<()>>,
+}
+
+// aborts every request it was handed once cancelled; see `use_cancel_token`
+#[derive(Clone, Default)]
+pub struct CancelToken(Rc<RefCell<CancelState>>);
+
+impl PartialEq for CancelToken {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.borrow().cancelled
+    }
+
+    pub fn cancel(&self) {
+        let (controllers, waiters) = {
+            let mut state = self.0.borrow_mut();
+            state.cancelled = true;
+            (
+                std::mem::take(&mut state.controllers),
+                std::mem::take(&mut state.waiters),
+            )
+        };
+        for c in controllers {
+            c.abort();
+        }
+        for w in waiters {
+            w.send(()).unwrap_or_default();
+        }
+    }
+
+    pub(crate) fn track(&self, controller: &AbortController) {
+        if self.is_cancelled() {
+            controller.abort();
+        } else {
+            self.0.borrow_mut().controllers.push(controller.clone());
+        }
+    }
+
+    pub(crate) fn untrack(&self, controller: &AbortController) {
+        self.0
+            .borrow_mut()
+            .controllers
+            .retain(|x| !js_sys::Object::is(x, controller));
+    }
+
+    // resolves once cancelled, for futures that can not be aborted
+    pub async fn cancelled(&self) {
+        let rx = {
+            let mut state = self.0.borrow_mut();
+            if state.cancelled {
+                return;
+            }
+            let (tx, rx) = oneshot::channel();
+            state.waiters.push(tx);
+            rx
+        };
+        rx.await.unwrap_or_default();
+    }
+}
+
+pub async fn sleep(ms: u32) {
+    let (tx, rx) = oneshot::channel::<()>();
+    let _timeout = Timeout::new(ms, move || {
+        tx.send(()).unwrap_or_default();
+    });
+    rx.await.unwrap_or_default();
+}
+
+// aborts `controller` after `ms`; the flag tells a timeout from a cancellation
+pub struct Deadline {
+    _timeout: Option<Timeout>,
+    expired: Rc<Cell<bool>>,
+}
+
+impl Deadline {
+    pub fn new(ms: Option<u32>, controller: &AbortController) -> Self {
+        let expired = Rc::new(Cell::new(false));
+        let timeout = ms.map(|ms| {
+            let expired = expired.clone();
+            let controller = controller.clone();
+            Timeout::new(ms, move || {
+                expired.set(true);
+                controller.abort();
+            })
+        });
+        Deadline {
+            _timeout: timeout,
+            expired,
+        }
+    }
+
+    pub fn expired(&self) -> bool {
+        self.expired.get()
+    }
+}
+
+// exponential with jitter: 300ms, 600ms, 1.2s .. capped at 5s
+pub fn backoff_ms(attempt: u32) -> u32 {
+    let base = (300u32 << attempt.saturating_sub(1).min(4)).min(5000);
+    base / 2 + rand::random::<u32>() % (base / 2 + 1)
+}
+
+// network trouble and gateway errors are worth another try, anything the server decided is not
+pub fn is_transient(err: &ApiError) -> bool {
+    !err.cancelled && matches!(err.status, 0 | 408 | 502 | 503 | 504)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(status: u16) -> ApiError {
+        ApiError {
+            status,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn backoff_bounds() {
+        let expected = [
+            (1, 150, 300),
+            (2, 300, 600),
+            (3, 600, 1200),
+            (5, 2400, 4800),
+        ];
+        for _ in 0..100 {
+            for (attempt, min, max) in expected {
+                let ms = backoff_ms(attempt);
+                assert!((min..=max).contains(&ms), "attempt {}: {}ms", attempt, ms);
+            }
+            // attempt 0 is treated as the first, later ones stop growing
+            assert!(backoff_ms(0) <= 300);
+            assert!(backoff_ms(50) <= 5000);
+            assert!(backoff_ms(u32::MAX) >= 2400);
+        }
+    }
+
+    #[test]
+    fn transient_errors() {
+        for v in [0, 408, 502, 503, 504] {
+            assert!(is_transient(&status(v)), "{}", v);
+        }
+        for v in [400, 401, 403, 404, 422, 429, 452, 500] {
+            assert!(!is_transient(&status(v)), "{}", v);
+        }
+        assert!(is_transient(&ApiError::network("offline")));
+        assert!(is_transient(&ApiError::timeout()));
+        assert!(!is_transient(&ApiError::cancelled()));
+    }
+}
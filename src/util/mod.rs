@@ -2,6 +2,14 @@
 
 pub const TOKEN_KEY: &str = "evolve_token";
 
+pub mod api_error;
 pub mod common;
+pub mod config;
 pub mod error;
+pub mod error_log;
+pub mod middleware;
+pub mod password_policy;
 pub mod request;
+pub mod tauri;
+pub mod use_cancel_token;
+pub mod validation;
@@ -0,0 +1,386 @@
+use crate::i18n;
+use crate::util::config;
+use crate::util::request::{self, Host};
+use crate::util::validation::{
+    FieldValues, ValidationCode, ValidationError, ValidationResult, Validator,
+};
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+lazy_static! {
+    // bundled so the check works offline and never sends the password anywhere
+    static ref COMMON_PASSWORDS: HashSet<&'static str> =
+        include_str!("common_passwords.txt")
+            .lines()
+            .map(|x| x.trim())
+            .filter(|x| !x.is_empty())
+            .collect();
+}
+
+thread_local! {
+    static POLICY: RefCell<Option<PasswordPolicy>> = const { RefCell::new(None) };
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub max_length: usize,
+    pub require_lowercase: bool,
+    pub require_uppercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    pub allow_symbols: bool,
+    pub deny_common: bool,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 6,
+            max_length: 64,
+            require_lowercase: true,
+            require_uppercase: false,
+            require_digit: true,
+            require_symbol: false,
+            allow_symbols: true,
+            deny_common: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Requirement {
+    MinLength(usize),
+    MaxLength(usize),
+    Lowercase,
+    Uppercase,
+    Digit,
+    Symbol,
+    NoSymbols,
+    NotCommon,
+}
+
+impl Requirement {
+    pub fn label(&self) -> String {
+        match self {
+            Requirement::MinLength(n) => {
+                i18n::t_args("password.min_length", &[("n", &n.to_string())])
+            }
+            Requirement::MaxLength(n) => {
+                i18n::t_args("password.max_length", &[("n", &n.to_string())])
+            }
+            Requirement::Lowercase => i18n::t("password.lowercase"),
+            Requirement::Uppercase => i18n::t("password.uppercase"),
+            Requirement::Digit => i18n::t("password.digit"),
+            Requirement::Symbol => i18n::t("password.symbol"),
+            Requirement::NoSymbols => i18n::t("password.no_symbols"),
+            Requirement::NotCommon => i18n::t("password.not_common"),
+        }
+    }
+
+    fn is_met(&self, pwd: &str) -> bool {
+        match self {
+            Requirement::MinLength(n) => pwd.chars().count() >= *n,
+            Requirement::MaxLength(n) => pwd.chars().count() <= *n,
+            Requirement::Lowercase => pwd.chars().any(|c| c.is_lowercase()),
+            Requirement::Uppercase => pwd.chars().any(|c| c.is_uppercase()),
+            Requirement::Digit => pwd.chars().any(|c| c.is_ascii_digit()),
+            Requirement::Symbol => pwd.chars().any(is_symbol),
+            Requirement::NoSymbols => !pwd.chars().any(is_symbol),
+            Requirement::NotCommon => !is_common(pwd),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Strength {
+    VeryWeak,
+    Weak,
+    Fair,
+    Good,
+    Strong,
+}
+
+impl Strength {
+    pub fn score(&self) -> usize {
+        *self as usize
+    }
+
+    pub fn label(&self) -> String {
+        i18n::t(match self {
+            Strength::VeryWeak => "password.very_weak",
+            Strength::Weak => "password.weak",
+            Strength::Fair => "password.fair",
+            Strength::Good => "password.good",
+            Strength::Strong => "password.strong",
+        })
+    }
+
+    pub fn class(&self) -> &'static str {
+        match self {
+            Strength::VeryWeak | Strength::Weak => "is-danger",
+            Strength::Fair => "is-warning",
+            Strength::Good => "is-info",
+            Strength::Strong => "is-success",
+        }
+    }
+}
+
+// a space is allowed anywhere but does not count as a symbol
+fn is_symbol(c: char) -> bool {
+    !c.is_alphanumeric() && !c.is_whitespace()
+}
+
+fn is_common(pwd: &str) -> bool {
+    COMMON_PASSWORDS.contains(pwd.to_lowercase().as_str())
+}
+
+impl PasswordPolicy {
+    // the configured policy, else the one loaded from the server, or the default until it arrives
+    pub fn current() -> PasswordPolicy {
+        POLICY
+            .with(|x| x.borrow().clone())
+            .or_else(PasswordPolicy::configured)
+            .unwrap_or_default()
+    }
+
+    fn configured() -> Option<PasswordPolicy> {
+        let json = config::password_policy()?;
+        serde_json::from_str(json)
+            .map_err(|e| log::warn!("invalid configured password policy, ignored: {}", e))
+            .ok()
+    }
+
+    pub fn requirements(&self) -> Vec<Requirement> {
+        let mut v = vec![
+            Requirement::MinLength(self.min_length),
+            Requirement::MaxLength(self.max_length),
+        ];
+        if self.require_lowercase {
+            v.push(Requirement::Lowercase);
+        }
+        if self.require_uppercase {
+            v.push(Requirement::Uppercase);
+        }
+        if self.require_digit {
+            v.push(Requirement::Digit);
+        }
+        if !self.allow_symbols {
+            v.push(Requirement::NoSymbols);
+        } else if self.require_symbol {
+            v.push(Requirement::Symbol);
+        }
+        if self.deny_common {
+            v.push(Requirement::NotCommon);
+        }
+        v
+    }
+
+    pub fn checklist(&self, pwd: &str) -> Vec<(Requirement, bool)> {
+        self.requirements()
+            .into_iter()
+            .map(|x| {
+                let met = x.is_met(pwd);
+                (x, met)
+            })
+            .collect()
+    }
+
+    pub fn strength(&self, pwd: &str) -> Strength {
+        if pwd.is_empty() || (self.deny_common && is_common(pwd)) {
+            return Strength::VeryWeak;
+        }
+        let len = pwd.chars().count();
+        let classes = [
+            pwd.chars().any(|c| c.is_lowercase()),
+            pwd.chars().any(|c| c.is_uppercase()),
+            pwd.chars().any(|c| c.is_ascii_digit()),
+            pwd.chars().any(is_symbol),
+        ]
+        .iter()
+        .filter(|x| **x)
+        .count();
+        let mut score = 0;
+        if len >= self.min_length {
+            score += 1;
+        }
+        if len >= 12 {
+            score += 1;
+        }
+        if classes >= 2 {
+            score += 1;
+        }
+        if classes >= 3 {
+            score += 1;
+        }
+        // unmet requirements cap the meter so it never reads "Strong" for a rejected password
+        if self.checklist(pwd).iter().any(|(_, met)| !met) {
+            score = score.min(1);
+        }
+        match score {
+            0 => Strength::VeryWeak,
+            1 => Strength::Weak,
+            2 => Strength::Fair,
+            3 => Strength::Good,
+            _ => Strength::Strong,
+        }
+    }
+}
+
+impl Validator for PasswordPolicy {
+    fn validate(&self, value: &str, _values: &dyn FieldValues) -> ValidationResult {
+        let unmet = self
+            .checklist(value)
+            .into_iter()
+            .filter(|(_, met)| !met)
+            .map(|(x, _)| x.label())
+            .collect::<Vec<_>>();
+        if unmet.is_empty() {
+            return Ok(());
+        }
+        Err(ValidationError {
+            field: "password",
+            code: ValidationCode::Custom("validation.password_policy"),
+            message: None,
+            args: vec![("unmet", unmet.join(", "))],
+        })
+    }
+}
+
+// validates against whatever policy is current at the time of the check
+pub struct CurrentPolicy;
+
+impl Validator for CurrentPolicy {
+    fn validate(&self, value: &str, values: &dyn FieldValues) -> ValidationResult {
+        PasswordPolicy::current().validate(value, values)
+    }
+}
+
+pub async fn load() {
+    if POLICY.with(|x| x.borrow().is_some()) {
+        return;
+    }
+    if let Some(policy) = PasswordPolicy::configured() {
+        POLICY.with(|x| *x.borrow_mut() = Some(policy));
+        return;
+    }
+    let policy = match request::get::<PasswordPolicy, Vec<(&str, &str)>, &str>(
+        Host::ApiBase,
+        "/auth/password_policy",
+        None,
+    )
+    .await
+    {
+        Ok(res) => res.data.unwrap_or_default(),
+        Err(err) => {
+            log::warn!("load password policy failed, using default: {}", err);
+            PasswordPolicy::default()
+        }
+    };
+    POLICY.with(|x| *x.borrow_mut() = Some(policy));
+}
+
+#[hook]
+pub fn use_password_policy() -> PasswordPolicy {
+    let force_update = use_force_update();
+    use_effect_with((), move |_| {
+        spawn_local(async move {
+            load().await;
+            force_update.force_update();
+        });
+    });
+    PasswordPolicy::current()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unmet(policy: &PasswordPolicy, pwd: &str) -> Vec<Requirement> {
+        policy
+            .checklist(pwd)
+            .into_iter()
+            .filter(|(_, met)| !met)
+            .map(|(x, _)| x)
+            .collect()
+    }
+
+    #[test]
+    fn checklist_default_policy() {
+        let policy = PasswordPolicy::default();
+        assert_eq!(
+            policy
+                .checklist("xk3mzq")
+                .into_iter()
+                .map(|(x, _)| x)
+                .collect::<Vec<_>>(),
+            vec![
+                Requirement::MinLength(6),
+                Requirement::MaxLength(64),
+                Requirement::Lowercase,
+                Requirement::Digit,
+                Requirement::NotCommon,
+            ]
+        );
+        assert_eq!(unmet(&policy, "xk3mzq"), vec![]);
+        assert_eq!(
+            unmet(&policy, "XK"),
+            vec![
+                Requirement::MinLength(6),
+                Requirement::Lowercase,
+                Requirement::Digit
+            ]
+        );
+        assert_eq!(unmet(&policy, "abc123"), vec![Requirement::NotCommon]);
+        assert_eq!(
+            unmet(&policy, &"a1".repeat(33)),
+            vec![Requirement::MaxLength(64)]
+        );
+    }
+
+    #[test]
+    fn checklist_symbols() {
+        let require = PasswordPolicy {
+            require_symbol: true,
+            ..Default::default()
+        };
+        assert_eq!(unmet(&require, "xk3m zq"), vec![Requirement::Symbol]);
+        assert_eq!(unmet(&require, "xk3m!zq"), vec![]);
+
+        let deny = PasswordPolicy {
+            allow_symbols: false,
+            require_symbol: true,
+            ..Default::default()
+        };
+        assert_eq!(unmet(&deny, "xk3m zq"), vec![]);
+        assert_eq!(unmet(&deny, "xk3m!zq"), vec![Requirement::NoSymbols]);
+    }
+
+    #[test]
+    fn strength() {
+        let policy = PasswordPolicy::default();
+        assert_eq!(policy.strength(""), Strength::VeryWeak);
+        assert_eq!(policy.strength("password"), Strength::VeryWeak);
+        assert_eq!(policy.strength("xk3"), Strength::Weak);
+        assert_eq!(policy.strength("xk3mzq"), Strength::Fair);
+        assert_eq!(policy.strength("Xk3mzq"), Strength::Good);
+        assert_eq!(policy.strength("Xk3mzq!vb7pw"), Strength::Strong);
+        // spaces add length but no character class
+        assert_eq!(policy.strength("xk3m zq"), Strength::Fair);
+    }
+
+    #[test]
+    fn strength_capped_by_unmet_requirements() {
+        let policy = PasswordPolicy {
+            require_symbol: true,
+            ..Default::default()
+        };
+        assert_eq!(policy.strength("Xk3mzqvb7pwr"), Strength::Weak);
+        assert_eq!(policy.strength("Xk3mzq!vb7pw"), Strength::Strong);
+    }
+}
@@ -1,11 +1,15 @@
+use crate::util::api_error::{ApiError, ApiResult};
 use crate::util::common;
-use crate::util::error::ToError;
-use common::BasicResult;
+use crate::util::config;
+use crate::util::middleware::{self, CancelToken, Deadline, RequestInfo};
+use evolve_axum_cli::apis::configuration::Configuration;
+use futures::future::{self, Either};
 use gloo_net::http::{Method, RequestBuilder};
-use gloo_net::websocket::futures::WebSocket;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use wasm_bindgen_futures::spawn_local;
+use std::future::Future;
+use web_sys::AbortController;
 
 #[derive(Deserialize)]
 pub struct ResultData<T> {
@@ -14,139 +18,431 @@ pub struct ResultData<T> {
     pub total: Option<usize>,
 }
 
-#[allow(unused)]
 pub enum Host {
     ApiBase,
     Base,
+    // the socket server, `wss://` whenever `Base` is served over https
+    Ws,
 }
 
 impl std::fmt::Display for Host {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Host::ApiBase => f.write_str("http://localhost:8881/api"),
-            Host::Base => f.write_str("http://localhost:8881"),
+            Host::ApiBase => write!(f, "{}/api", config::base_url()),
+            Host::Base => f.write_str(config::base_url()),
+            Host::Ws => f.write_str(&ws_url(config::base_url())),
         }
     }
 }
 
-fn build_header(req: RequestBuilder) -> RequestBuilder {
-    let req = req.header("Content-type", "application/json");
-    match common::get_token() {
-        Ok(v) => req.header(crate::util::TOKEN_KEY, format!("Bearer {}", v).as_str()),
-        Err(_) => req,
+fn ws_url(base: &str) -> String {
+    match base.split_once("://") {
+        Some(("https", rest)) => format!("wss://{rest}"),
+        Some((_, rest)) => format!("ws://{rest}"),
+        None => format!("ws://{base}"),
     }
 }
 
-fn build_request(method: Method, host: Host, path: &str) -> RequestBuilder {
-    let url = &format!("{}{}", host, path);
-    let req = RequestBuilder::new(url).method(method);
-    build_header(req)
+const DEFAULT_TIMEOUT_MS: u32 = 15_000;
+// for idempotent methods only, see `RequestOptions::retries`
+const DEFAULT_RETRIES: u32 = 2;
+
+// how a request is sent; `RequestOptions::default()` is what the free functions below use
+#[derive(Clone, PartialEq)]
+pub struct RequestOptions {
+    timeout_ms: Option<u32>,
+    // `None` picks by method: GET, PUT and DELETE retry, POST never does
+    retries: Option<u32>,
+    cancel: Option<CancelToken>,
 }
 
-async fn send<Res>(req: gloo_net::http::Request) -> BasicResult<ResultData<Res>>
+impl Default for RequestOptions {
+    fn default() -> Self {
+        RequestOptions {
+            timeout_ms: Some(DEFAULT_TIMEOUT_MS),
+            retries: None,
+            cancel: None,
+        }
+    }
+}
+
+// the pieces that are rebuilt for every attempt
+struct Plan {
+    method: Method,
+    url: String,
+    query: Vec<(String, String)>,
+    body: Option<String>,
+}
+
+impl RequestOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn timeout(mut self, ms: u32) -> Self {
+        self.timeout_ms = Some(ms);
+        self
+    }
+
+    pub fn no_timeout(mut self) -> Self {
+        self.timeout_ms = None;
+        self
+    }
+
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = Some(retries);
+        self
+    }
+
+    // aborts the request once `token` is cancelled, see `use_cancel_token`
+    pub fn cancel(mut self, token: &CancelToken) -> Self {
+        self.cancel = Some(token.clone());
+        self
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(|x| x.is_cancelled())
+    }
+
+    pub async fn get<'a, Res, Param, V>(
+        &self,
+        host: Host,
+        path: &str,
+        params: Option<Param>,
+    ) -> ApiResult<ResultData<Res>>
+    where
+        Param: IntoIterator<Item = (&'a str, V)>,
+        Res: DeserializeOwned,
+        V: AsRef<str>,
+    {
+        let query = params
+            .into_iter()
+            .flatten()
+            .map(|(k, v)| (k.to_string(), v.as_ref().to_string()))
+            .collect();
+        self.execute(Plan {
+            method: Method::GET,
+            url: format!("{}{}", host, path),
+            query,
+            body: None,
+        })
+        .await
+    }
+
+    pub async fn put<Res, Body>(
+        &self,
+        host: Host,
+        path: &str,
+        body: &Body,
+    ) -> ApiResult<ResultData<Res>>
+    where
+        Body: Serialize,
+        Res: DeserializeOwned,
+    {
+        self.with_body(Method::PUT, host, path, body).await
+    }
+
+    pub async fn post<Res, Body>(
+        &self,
+        host: Host,
+        path: &str,
+        body: &Body,
+    ) -> ApiResult<ResultData<Res>>
+    where
+        Body: Serialize,
+        Res: DeserializeOwned,
+    {
+        self.with_body(Method::POST, host, path, body).await
+    }
+
+    pub async fn delete<Res, Body>(
+        &self,
+        host: Host,
+        path: &str,
+        body: &Body,
+    ) -> ApiResult<ResultData<Res>>
+    where
+        Body: Serialize,
+        Res: DeserializeOwned,
+    {
+        self.with_body(Method::DELETE, host, path, body).await
+    }
+
+    async fn with_body<Res, Body>(
+        &self,
+        method: Method,
+        host: Host,
+        path: &str,
+        body: &Body,
+    ) -> ApiResult<ResultData<Res>>
+    where
+        Body: Serialize,
+        Res: DeserializeOwned,
+    {
+        self.execute(Plan {
+            method,
+            url: format!("{}{}", host, path),
+            query: vec![],
+            body: Some(serde_json::to_string(body)?),
+        })
+        .await
+    }
+
+    async fn execute<Res>(&self, plan: Plan) -> ApiResult<ResultData<Res>>
+    where
+        Res: DeserializeOwned,
+    {
+        let idempotent = plan.method != Method::POST && plan.method != Method::PATCH;
+        let retries = self
+            .retries
+            .unwrap_or(if idempotent { DEFAULT_RETRIES } else { 0 });
+        let mut info = RequestInfo::new(&plan.method.to_string(), &plan.url);
+        loop {
+            let err = match self.attempt(&plan, &info).await {
+                Ok(v) => return Ok(v),
+                Err(err) => err,
+            };
+            middleware::error(&info, &err);
+            if info.attempt > retries || !middleware::is_transient(&err) {
+                return Err(err);
+            }
+            middleware::sleep(middleware::backoff_ms(info.attempt)).await;
+            if self.is_cancelled() {
+                return Err(ApiError::cancelled());
+            }
+            info.attempt += 1;
+        }
+    }
+
+    async fn attempt<Res>(&self, plan: &Plan, info: &RequestInfo) -> ApiResult<ResultData<Res>>
+    where
+        Res: DeserializeOwned,
+    {
+        let controller = AbortController::new()
+            .map_err(|e| ApiError::network(format!("create abort controller failed: {:?}", e)))?;
+        // lives until the body is read, so a stalled body also times out
+        let deadline = Deadline::new(self.timeout_ms, &controller);
+        if let Some(token) = &self.cancel {
+            token.track(&controller);
+        }
+        let signal = controller.signal();
+        let mut req = RequestBuilder::new(&plan.url)
+            .method(plan.method.clone())
+            .abort_signal(Some(&signal))
+            .header("Content-type", "application/json");
+        if !plan.query.is_empty() {
+            req = req.query(plan.query.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+        }
+        for (name, value) in middleware::headers(info) {
+            req = req.header(&name, &value);
+        }
+        let res = match &plan.body {
+            Some(body) => req.body(body.clone()),
+            None => req.build(),
+        };
+        let res = match res {
+            Ok(req) => send(req, info).await,
+            Err(err) => Err(ApiError::from(err)),
+        };
+        if let Some(token) = &self.cancel {
+            token.untrack(&controller);
+        }
+        let with_id = |err: ApiError| ApiError {
+            request_id: Some(info.request_id.clone()),
+            ..err
+        };
+        match res {
+            Err(_) if self.is_cancelled() => Err(with_id(ApiError::cancelled())),
+            Err(_) if deadline.expired() => Err(with_id(ApiError::timeout())),
+            res => res,
+        }
+    }
+
+    // runs a generated client call with the same timeout, retries, cancellation and hooks
+    pub async fn cli<T, E, F, Fut>(&self, name: &str, with_token: bool, f: F) -> ApiResult<T>
+    where
+        F: Fn(Configuration) -> Fut,
+        Fut: Future<Output = Result<T, evolve_axum_cli::apis::Error<E>>>,
+        E: std::fmt::Debug,
+    {
+        // the generated api does not say which calls are idempotent, so only explicit retries
+        let retries = self.retries.unwrap_or_default();
+        let mut info = RequestInfo::new("CLI", name);
+        loop {
+            let mut config = if with_token {
+                common::get_cli_config()
+            } else {
+                common::get_cli_config_without_token()
+            }?;
+            // a client per attempt, so the headers carry this attempt's request id
+            config.client = cli_client(middleware::headers(&info))?;
+            let err = match self.race(&info, f(config)).await {
+                Ok(v) => return Ok(v),
+                Err(err) => err,
+            };
+            middleware::error(&info, &err);
+            // the token expired or the session was revoked remotely; a sign in may well
+            // answer 401 without that meaning anything
+            if with_token && err.status == 401 {
+                common::logout();
+                return Err(err);
+            }
+            if info.attempt > retries || !middleware::is_transient(&err) {
+                return Err(err);
+            }
+            middleware::sleep(middleware::backoff_ms(info.attempt)).await;
+            if self.is_cancelled() {
+                return Err(ApiError::cancelled());
+            }
+            info.attempt += 1;
+        }
+    }
+
+    // the generated futures can not be aborted, so they are dropped instead
+    async fn race<T, E, Fut>(&self, info: &RequestInfo, call: Fut) -> ApiResult<T>
+    where
+        Fut: Future<Output = Result<T, evolve_axum_cli::apis::Error<E>>>,
+        E: std::fmt::Debug,
+    {
+        let timeout = async {
+            match self.timeout_ms {
+                Some(ms) => middleware::sleep(ms).await,
+                None => future::pending().await,
+            }
+        };
+        let cancelled = async {
+            match &self.cancel {
+                Some(token) => token.cancelled().await,
+                None => future::pending().await,
+            }
+        };
+        let stop = future::select(Box::pin(timeout), Box::pin(cancelled));
+        match future::select(Box::pin(call), stop).await {
+            Either::Left((Ok(v), _)) => {
+                middleware::response(info, 200);
+                Ok(v)
+            }
+            Either::Left((Err(err), _)) => {
+                let err = ApiError::from(err);
+                if err.status != 0 {
+                    middleware::response(info, err.status);
+                }
+                // the server's own id when the body has one, ours otherwise
+                Err(ApiError {
+                    request_id: err.request_id.or_else(|| Some(info.request_id.clone())),
+                    ..err
+                })
+            }
+            Either::Right((Either::Left(_), _)) => Err(ApiError::timeout()),
+            Either::Right((Either::Right(_), _)) => Err(ApiError::cancelled()),
+        }
+    }
+}
+
+// the generated client sends whatever its `reqwest::Client` has as default headers
+fn cli_client(headers: Vec<(String, String)>) -> ApiResult<reqwest::Client> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        match (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            (Ok(name), Ok(value)) => {
+                map.append(name, value);
+            }
+            _ => log::warn!("skipping invalid header {}", name),
+        }
+    }
+    reqwest::Client::builder()
+        .default_headers(map)
+        .build()
+        .map_err(|e| ApiError::network(format!("create client failed: {}", e)))
+}
+
+async fn send<Res>(req: gloo_net::http::Request, info: &RequestInfo) -> ApiResult<ResultData<Res>>
 where
     Res: DeserializeOwned,
 {
     let response = req.send().await?;
     let status = response.status();
-    // let url = response.url();
-    if status == 401 || status == 404 {
-        common::redirect(&format!("/{status}"));
-
-        return Ok(ResultData {
-            data: None,
-            msg: None,
-            total: None,
-        });
-    }
-    let status_first = status / 100;
-    let result: ResultData<Res> = response.json().await.map_err(|e| {
-        log::error!("json umarshal error: {}", e);
-        e
-    })?;
-    match status_first {
-        4 | 5 => match status {
-            452 => Err(result.msg.unwrap().to_hint()),
-            _ => Err(result.msg.unwrap().to_server_error()),
-        },
-        _ => Ok(result),
+    middleware::response(info, status);
+    // the server's own id when it sends one, ours otherwise
+    let request_id = response
+        .headers()
+        .get(middleware::REQUEST_ID_HEADER)
+        .or_else(|| Some(info.request_id.clone()));
+    let body = response.text().await?;
+    if status / 100 == 4 || status / 100 == 5 {
+        let err = ApiError::from_response(status, request_id, &body);
+        match status {
+            // the token expired or the session was revoked remotely
+            401 => common::logout(),
+            404 => common::redirect("/404"),
+            _ => {}
+        }
+        return Err(err);
     }
-}
-
-pub fn open_ws() -> BasicResult<WebSocket> {
-    let token = common::get_token().unwrap();
-    spawn_local(async move {
-        get::<common::CurrentUser, Vec<(&str, &str)>, _>(
-            Host::ApiBase,
-            "/user/get_current_user",
-            None,
-        )
-        .await
-        .unwrap();
-    });
-
-    let url = format!("ws://localhost:8881/ws/ws/{}", token);
-    let ws = WebSocket::open(&url)
-        .map_err(|err| log::info!("open ws error: {:#?}", err))
-        .unwrap();
-    Ok(ws)
+    serde_json::from_str(&body).map_err(|e| {
+        log::error!("json umarshal error: {}", e);
+        ApiError {
+            status,
+            request_id,
+            ..ApiError::from(e)
+        }
+    })
 }
 
 pub async fn get<'a, Res, Param, V>(
     host: Host,
     path: &str,
     params: Option<Param>,
-) -> BasicResult<ResultData<Res>>
+) -> ApiResult<ResultData<Res>>
 where
     Param: IntoIterator<Item = (&'a str, V)>,
     Res: DeserializeOwned,
     V: AsRef<str>,
 {
-    let req = match params {
-        Some(p) => build_request(Method::GET, host, path).query(p),
-        None => build_request(Method::GET, host, path),
-    };
-
-    send(req.build()?).await
+    RequestOptions::default().get(host, path, params).await
 }
 
 #[allow(unused)]
-pub async fn put<'a, Res, Body>(host: Host, path: &str, body: &Body) -> BasicResult<ResultData<Res>>
+pub async fn put<Res, Body>(host: Host, path: &str, body: &Body) -> ApiResult<ResultData<Res>>
 where
     Body: Serialize,
     Res: DeserializeOwned,
 {
-    let body = serde_json::to_string(body)?;
-    let req = build_request(Method::PUT, host, path).body(body)?;
-    send(req).await
+    RequestOptions::default().put(host, path, body).await
 }
 
 #[allow(unused)]
-pub async fn post<'a, Res, Body>(
-    host: Host,
-    path: &str,
-    body: &Body,
-) -> BasicResult<ResultData<Res>>
+pub async fn post<Res, Body>(host: Host, path: &str, body: &Body) -> ApiResult<ResultData<Res>>
 where
     Body: Serialize,
     Res: DeserializeOwned,
 {
-    let body = serde_json::to_string(body)?;
-    let req = build_request(Method::POST, host, path).body(body)?;
-    send(req).await
+    RequestOptions::default().post(host, path, body).await
 }
 
 #[allow(unused)]
-pub async fn delete<'a, Res, Body>(
-    host: Host,
-    path: &str,
-    body: &Body,
-) -> BasicResult<ResultData<Res>>
+pub async fn delete<Res, Body>(host: Host, path: &str, body: &Body) -> ApiResult<ResultData<Res>>
 where
     Body: Serialize,
     Res: DeserializeOwned,
 {
-    let body = serde_json::to_string(body)?;
-    let req = build_request(Method::DELETE, host, path).body(body)?;
-    send(req).await
+    RequestOptions::default().delete(host, path, body).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ws_url_follows_the_scheme() {
+        assert_eq!(
+            ws_url("https://evolve.example.com"),
+            "wss://evolve.example.com"
+        );
+        assert_eq!(ws_url("http://localhost:8881"), "ws://localhost:8881");
+        assert_eq!(ws_url("localhost:8881"), "ws://localhost:8881");
+    }
 }
@@ -0,0 +1,95 @@
+use crate::util::common::BasicResult;
+use crate::util::error::{ErrorKind, ToError};
+use js_sys::{Function, Promise, Reflect, JSON};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+
+// `window.__TAURI__` is injected because `withGlobalTauri` is on in tauri.conf.json
+fn global() -> Option<JsValue> {
+    let window = web_sys::window()?;
+    Reflect::get(&window, &JsValue::from_str("__TAURI__"))
+        .ok()
+        .filter(|x| x.is_object())
+}
+
+fn lookup(path: &[&str]) -> Option<JsValue> {
+    path.iter().try_fold(global()?, |obj, key| {
+        Reflect::get(&obj, &JsValue::from_str(key))
+            .ok()
+            .filter(|x| !x.is_undefined() && !x.is_null())
+    })
+}
+
+fn js_error(err: JsValue) -> ErrorKind {
+    err.as_string()
+        .unwrap_or_else(|| format!("{:?}", err))
+        .to_basic_error()
+}
+
+pub fn is_tauri() -> bool {
+    global().is_some()
+}
+
+fn call<Args: Serialize>(cmd: &str, args: &Args) -> BasicResult<JsValue> {
+    // the api moved between tauri releases
+    let invoke = lookup(&["core", "invoke"])
+        .or_else(|| lookup(&["tauri", "invoke"]))
+        .or_else(|| lookup(&["invoke"]))
+        .and_then(|x| x.dyn_into::<Function>().ok())
+        .ok_or_else(|| "tauri invoke is not available".to_basic_error())?;
+    let args = JSON::parse(&serde_json::to_string(args)?).map_err(js_error)?;
+    invoke
+        .call2(&JsValue::NULL, &JsValue::from_str(cmd), &args)
+        .map_err(js_error)
+}
+
+pub async fn invoke<T, Args>(cmd: &str, args: &Args) -> BasicResult<T>
+where
+    T: DeserializeOwned,
+    Args: Serialize,
+{
+    let promise = call(cmd, args)?.dyn_into::<Promise>().map_err(js_error)?;
+    let res = JsFuture::from(promise).await.map_err(js_error)?;
+    let json = JSON::stringify(&res)
+        .map_err(js_error)?
+        .as_string()
+        .unwrap_or_default();
+    Ok(serde_json::from_str(&json)?)
+}
+
+// fire and forget; usable where no executor may run afterwards, e.g. in the panic hook
+pub fn invoke_detached<Args: Serialize>(cmd: &str, args: &Args) {
+    if let Err(err) = call(cmd, args) {
+        log::warn!("tauri invoke {} failed: {}", cmd, err);
+    }
+}
+
+// calls `f` with the payload of every `event` emitted by the native side
+pub fn listen<T, F>(event: &str, f: F)
+where
+    T: DeserializeOwned,
+    F: Fn(T) + 'static,
+{
+    let listen = match lookup(&["event", "listen"]).and_then(|x| x.dyn_into::<Function>().ok()) {
+        Some(v) => v,
+        None => return,
+    };
+    let handler = Closure::<dyn Fn(JsValue)>::new(move |e: JsValue| {
+        let payload = Reflect::get(&e, &JsValue::from_str("payload")).unwrap_or(e);
+        let json = JSON::stringify(&payload)
+            .ok()
+            .and_then(|x| x.as_string())
+            .unwrap_or_default();
+        match serde_json::from_str::<T>(&json) {
+            Ok(v) => f(v),
+            Err(err) => log::warn!("bad tauri event payload: {}", err),
+        }
+    });
+    if let Err(err) = listen.call2(&JsValue::NULL, &JsValue::from_str(event), handler.as_ref()) {
+        log::warn!("listen tauri event failed: {:?}", err);
+    }
+    // listeners live as long as the app
+    handler.forget();
+}
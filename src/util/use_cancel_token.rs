@@ -0,0 +1,13 @@
+use crate::util::middleware::CancelToken;
+use yew::prelude::*;
+
+// a token cancelled when the component unmounts, so its requests do not outlive it
+#[hook]
+pub fn use_cancel_token() -> CancelToken {
+    let token = use_memo((), |_| CancelToken::new());
+    {
+        let token = (*token).clone();
+        use_effect_with((), move |_| move || token.cancel());
+    }
+    (*token).clone()
+}
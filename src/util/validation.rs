@@ -0,0 +1,415 @@
+use crate::i18n;
+use crate::util::error::ErrorKind;
+use crate::util::password_policy::CurrentPolicy;
+use fancy_regex::Regex;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::rc::Rc;
+
+lazy_static! {
+    pub static ref EMAIL_REGEX: Regex =
+        Regex::new(r#"\w[-\w.+]*@([A-Za-z0-9][-A-Za-z0-9]+\.)+[A-Za-z]{2,14}"#).unwrap();
+    pub static ref CODE_REGEX: Regex = Regex::new(r#"^\d{6}$"#).unwrap();
+}
+
+// codes double as message keys, so the ui can localize them
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationCode {
+    Required,
+    Length { min: usize, max: usize },
+    Pattern,
+    Mismatch { other: &'static str },
+    Custom(&'static str),
+}
+
+impl ValidationCode {
+    pub fn key(&self) -> &'static str {
+        match self {
+            ValidationCode::Required => "validation.required",
+            ValidationCode::Length { .. } => "validation.length",
+            ValidationCode::Pattern => "validation.pattern",
+            ValidationCode::Mismatch { .. } => "validation.mismatch",
+            ValidationCode::Custom(key) => key,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    pub field: &'static str,
+    pub code: ValidationCode,
+    // message key overriding the one of the code
+    pub message: Option<&'static str>,
+    pub args: Vec<(&'static str, String)>,
+}
+
+fn field_name(field: &str) -> String {
+    let key = format!("field.{}", field);
+    match i18n::t(&key) {
+        v if v == key => field.to_string(),
+        v => v,
+    }
+}
+
+impl ValidationError {
+    pub fn new(field: &'static str, code: ValidationCode) -> Self {
+        Self {
+            field,
+            code,
+            message: None,
+            args: vec![],
+        }
+    }
+
+    // localized in the current language
+    pub fn message(&self) -> String {
+        let mut args = vec![("field", field_name(self.field))];
+        match &self.code {
+            ValidationCode::Length { min, max } => {
+                args.push(("min", min.to_string()));
+                args.push(("max", max.to_string()));
+            }
+            ValidationCode::Mismatch { other } => args.push(("other", field_name(other))),
+            _ => {}
+        }
+        args.extend(self.args.iter().cloned());
+        let args = args
+            .iter()
+            .map(|(k, v)| (*k, v.as_str()))
+            .collect::<Vec<_>>();
+        i18n::t_args(self.message.unwrap_or(self.code.key()), &args)
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message())
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl From<ValidationError> for ErrorKind {
+    fn from(err: ValidationError) -> Self {
+        ErrorKind::ValidationError(err.message())
+    }
+}
+
+pub type ValidationResult = Result<(), ValidationError>;
+
+// lookup of sibling field values, used by rules like `matches_field`
+pub trait FieldValues {
+    fn field(&self, name: &str) -> &str;
+}
+
+impl FieldValues for () {
+    fn field(&self, _name: &str) -> &str {
+        ""
+    }
+}
+
+impl<const N: usize> FieldValues for [(&str, &str); N] {
+    fn field(&self, name: &str) -> &str {
+        self.iter()
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| *v)
+            .unwrap_or_default()
+    }
+}
+
+impl FieldValues for HashMap<&str, String> {
+    fn field(&self, name: &str) -> &str {
+        self.get(name).map(|x| x.as_str()).unwrap_or_default()
+    }
+}
+
+pub trait Validator {
+    fn validate(&self, value: &str, values: &dyn FieldValues) -> ValidationResult;
+}
+
+impl<T: Validator + ?Sized> Validator for Rc<T> {
+    fn validate(&self, value: &str, values: &dyn FieldValues) -> ValidationResult {
+        (**self).validate(value, values)
+    }
+}
+
+pub trait ValidatorExt: Validator + Sized + 'static {
+    // runs `next` only when this one passes
+    fn and<V: Validator + 'static>(self, next: V) -> Rules {
+        Rules(vec![Rc::new(self), Rc::new(next)])
+    }
+
+    // `key` is looked up in the message catalog
+    fn message(self, key: &'static str) -> WithMessage<Self> {
+        WithMessage {
+            inner: self,
+            message: key,
+        }
+    }
+}
+
+impl<T: Validator + Sized + 'static> ValidatorExt for T {}
+
+#[derive(Clone, Default)]
+pub struct Rules(Vec<Rc<dyn Validator>>);
+
+impl Rules {
+    pub fn and<V: Validator + 'static>(mut self, next: V) -> Rules {
+        self.0.push(Rc::new(next));
+        self
+    }
+}
+
+impl Validator for Rules {
+    fn validate(&self, value: &str, values: &dyn FieldValues) -> ValidationResult {
+        for rule in self.0.iter() {
+            rule.validate(value, values)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct WithMessage<V> {
+    inner: V,
+    message: &'static str,
+}
+
+impl<V: Validator> Validator for WithMessage<V> {
+    fn validate(&self, value: &str, values: &dyn FieldValues) -> ValidationResult {
+        self.inner.validate(value, values).map_err(|mut e| {
+            e.message = Some(self.message);
+            e
+        })
+    }
+}
+
+pub struct Required {
+    field: &'static str,
+}
+
+impl Validator for Required {
+    fn validate(&self, value: &str, _values: &dyn FieldValues) -> ValidationResult {
+        if value.is_empty() {
+            return Err(ValidationError::new(self.field, ValidationCode::Required));
+        }
+        Ok(())
+    }
+}
+
+pub struct Length {
+    field: &'static str,
+    min: usize,
+    max: usize,
+}
+
+impl Validator for Length {
+    fn validate(&self, value: &str, _values: &dyn FieldValues) -> ValidationResult {
+        let len = value.chars().count();
+        if len < self.min || len > self.max {
+            return Err(ValidationError::new(
+                self.field,
+                ValidationCode::Length {
+                    min: self.min,
+                    max: self.max,
+                },
+            ));
+        }
+        Ok(())
+    }
+}
+
+pub struct Pattern {
+    field: &'static str,
+    regex: &'static Regex,
+}
+
+impl Validator for Pattern {
+    fn validate(&self, value: &str, _values: &dyn FieldValues) -> ValidationResult {
+        // a regex that blows its backtrack limit counts as no match
+        if !self.regex.is_match(value).unwrap_or(false) {
+            return Err(ValidationError::new(self.field, ValidationCode::Pattern));
+        }
+        Ok(())
+    }
+}
+
+pub struct MatchesField {
+    field: &'static str,
+    other: &'static str,
+}
+
+impl Validator for MatchesField {
+    fn validate(&self, value: &str, values: &dyn FieldValues) -> ValidationResult {
+        if values.field(self.other) != value {
+            return Err(ValidationError::new(
+                self.field,
+                ValidationCode::Mismatch { other: self.other },
+            ));
+        }
+        Ok(())
+    }
+}
+
+pub struct Custom<F> {
+    field: &'static str,
+    key: &'static str,
+    f: F,
+}
+
+impl<F> Validator for Custom<F>
+where
+    F: Fn(&str, &dyn FieldValues) -> bool,
+{
+    fn validate(&self, value: &str, values: &dyn FieldValues) -> ValidationResult {
+        if !(self.f)(value, values) {
+            return Err(ValidationError::new(
+                self.field,
+                ValidationCode::Custom(self.key),
+            ));
+        }
+        Ok(())
+    }
+}
+
+pub fn required(field: &'static str) -> Required {
+    Required { field }
+}
+
+pub fn length(field: &'static str, min: usize, max: usize) -> Length {
+    Length { field, min, max }
+}
+
+pub fn regex(field: &'static str, regex: &'static Regex) -> Pattern {
+    Pattern { field, regex }
+}
+
+pub fn matches_field(field: &'static str, other: &'static str) -> MatchesField {
+    MatchesField { field, other }
+}
+
+pub fn custom<F>(field: &'static str, key: &'static str, f: F) -> Custom<F>
+where
+    F: Fn(&str, &dyn FieldValues) -> bool,
+{
+    Custom { field, key, f }
+}
+
+pub fn email() -> Rules {
+    required("email").and(regex("email", &EMAIL_REGEX).message("validation.email_invalid"))
+}
+
+// checked against the configured password policy
+pub fn password() -> Rules {
+    required("password").and(CurrentPolicy)
+}
+
+// `pwd_field` is the name of the field the confirmation has to match
+pub fn password_confirm(pwd_field: &'static str) -> Rules {
+    password().and(
+        matches_field("confirm_password", pwd_field).message("validation.pwd_confirm_mismatch"),
+    )
+}
+
+pub fn code() -> Rules {
+    required("code").and(regex("code", &CODE_REGEX).message("validation.code_invalid"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check<V: Validator>(v: &V, value: &str) -> Result<(), ValidationCode> {
+        v.validate(value, &()).map_err(|e| e.code)
+    }
+
+    #[test]
+    fn email_rules() {
+        let v = email();
+        assert_eq!(check(&v, ""), Err(ValidationCode::Required));
+        assert_eq!(check(&v, "hello"), Err(ValidationCode::Pattern));
+        assert_eq!(check(&v, "hello@"), Err(ValidationCode::Pattern));
+        assert_eq!(check(&v, "hello@example"), Err(ValidationCode::Pattern));
+        assert_eq!(check(&v, "hello@example.com"), Ok(()));
+        assert_eq!(check(&v, "first.last+tag@mail.example.org"), Ok(()));
+    }
+
+    #[test]
+    fn email_messages() {
+        let v = email();
+        assert_eq!(
+            v.validate("", &()).unwrap_err().message(),
+            "please type in email"
+        );
+        assert_eq!(
+            v.validate("hello", &()).unwrap_err().message(),
+            "invalid email"
+        );
+    }
+
+    #[test]
+    fn password_rules() {
+        let v = password();
+        let policy = ValidationCode::Custom("validation.password_policy");
+        assert_eq!(check(&v, ""), Err(ValidationCode::Required));
+        assert_eq!(check(&v, "abc12"), Err(policy.clone()));
+        assert_eq!(check(&v, "abcdefgh"), Err(policy.clone()));
+        assert_eq!(check(&v, "12345678"), Err(policy.clone()));
+        assert_eq!(check(&v, "ABCDEF12"), Err(policy.clone()));
+        assert_eq!(check(&v, "abc123"), Err(policy.clone()));
+        assert_eq!(check(&v, "Password1"), Err(policy));
+        assert_eq!(check(&v, "river42x"), Ok(()));
+        assert_eq!(check(&v, "River42x"), Ok(()));
+        assert_eq!(check(&v, "river42!x"), Ok(()));
+        assert_eq!(
+            v.validate("abc123", &()).unwrap_err().message(),
+            "invalid password, unmet: not a commonly used password"
+        );
+    }
+
+    #[test]
+    fn password_confirm_rules() {
+        let v = password_confirm("pwd");
+        let values = &[("pwd", "river42x")];
+        assert_eq!(v.validate("river42x", values), Ok(()));
+        let err = v.validate("river42y", values).unwrap_err();
+        assert_eq!(err.code, ValidationCode::Mismatch { other: "pwd" });
+        assert_eq!(err.message(), "confirm password must as same as password");
+        assert_eq!(
+            v.validate("", values).unwrap_err().code,
+            ValidationCode::Required
+        );
+    }
+
+    #[test]
+    fn code_rules() {
+        let v = code();
+        assert_eq!(check(&v, ""), Err(ValidationCode::Required));
+        assert_eq!(check(&v, "12345"), Err(ValidationCode::Pattern));
+        assert_eq!(check(&v, "1234567"), Err(ValidationCode::Pattern));
+        assert_eq!(check(&v, "12345a"), Err(ValidationCode::Pattern));
+        assert_eq!(check(&v, "123456"), Ok(()));
+    }
+
+    #[test]
+    fn combinators() {
+        let v = required("name").and(length("name", 2, 4));
+        assert_eq!(check(&v, ""), Err(ValidationCode::Required));
+        assert_eq!(
+            check(&v, "a"),
+            Err(ValidationCode::Length { min: 2, max: 4 })
+        );
+        assert_eq!(check(&v, "名字"), Ok(()));
+        assert_eq!(
+            check(&v, "abcde"),
+            Err(ValidationCode::Length { min: 2, max: 4 })
+        );
+
+        let v = custom("name", "validation.no_admin", |v, _| v != "admin");
+        assert_eq!(
+            check(&v, "admin"),
+            Err(ValidationCode::Custom("validation.no_admin"))
+        );
+        assert_eq!(check(&v, "root"), Ok(()));
+    }
+}